@@ -0,0 +1,223 @@
+//! Run one SQL script against many projects, writing one output per project.
+
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+use crate::formatter;
+use crate::repl::split_statements;
+use crate::{Error, QuerySession, Result, SessionOptions};
+
+/// Placeholder in the output template replaced by the project name.
+pub const PROJECT_PLACEHOLDER: &str = "{project}";
+
+/// What to run and where to write it.
+#[derive(Debug, Clone)]
+pub struct BatchOptions {
+    /// Glob matching project directories (`~/` is expanded)
+    pub projects_glob: String,
+    /// SQL script run against each project
+    pub script: String,
+    /// Output path template containing `{project}`; `.csv` selects CSV, otherwise TSV
+    pub out_template: String,
+}
+
+/// Per-run counts.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BatchSummary {
+    /// Projects with output written
+    pub written: usize,
+    /// Matched directories without Claude Code sessions
+    pub skipped: usize,
+    /// Projects whose script or output failed
+    pub failed: usize,
+}
+
+/// Run the batch. Progress and per-project errors go to stderr.
+///
+/// # Errors
+/// Returns error if the glob pattern is invalid or two projects would write
+/// to the same output file.
+pub fn run(options: &BatchOptions, base: &SessionOptions) -> Result<BatchSummary> {
+    let pattern = expand_home(&options.projects_glob);
+    let paths = glob::glob(&pattern)
+        .map_err(|e| Error::Io(io::Error::new(io::ErrorKind::InvalidInput, e)))?;
+    let dirs: Vec<PathBuf> = paths
+        .filter_map(std::result::Result::ok)
+        .filter(|p| p.is_dir())
+        .collect();
+
+    let mut summary = BatchSummary::default();
+    for (path, (name, out)) in dirs.iter().zip(plan_outputs(&dirs, &options.out_template)?) {
+        let session_options = SessionOptions {
+            project_dir: Some(path.clone()),
+            data_dirs: Vec::new(),
+            ..base.clone()
+        };
+
+        match run_project(session_options, &options.script, &out) {
+            Ok(rows) => {
+                summary.written += 1;
+                eprintln!("{name}: {rows} row(s) -> {}", out.display());
+            }
+            Err(Error::NoSessions { .. }) => {
+                summary.skipped += 1;
+                eprintln!("{name}: no sessions, skipped");
+            }
+            Err(e) => {
+                summary.failed += 1;
+                eprintln!("{name}: {e}");
+            }
+        }
+    }
+    Ok(summary)
+}
+
+/// Project name and output path for each directory, in order. Directories
+/// sharing a name are told apart by their parents (`a-widget`, `b-widget`).
+///
+/// # Errors
+/// Returns error if two directories still map to the same output path, e.g.
+/// when the template has no `{project}`.
+fn plan_outputs(dirs: &[PathBuf], template: &str) -> Result<Vec<(String, PathBuf)>> {
+    let names = project_names(dirs);
+    let outputs: Vec<(String, PathBuf)> = names
+        .into_iter()
+        .map(|name| {
+            let out = PathBuf::from(template.replace(PROJECT_PLACEHOLDER, &name));
+            (name, out)
+        })
+        .collect();
+
+    for (i, (_, out)) in outputs.iter().enumerate() {
+        if let Some(j) = outputs[..i].iter().position(|(_, other)| other == out) {
+            return Err(Error::Io(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "{} and {} would both write {}",
+                    dirs[j].display(),
+                    dirs[i].display(),
+                    out.display()
+                ),
+            )));
+        }
+    }
+    Ok(outputs)
+}
+
+/// Directory names, each extended with parent directories until it is unique.
+fn project_names(dirs: &[PathBuf]) -> Vec<String> {
+    let parts: Vec<Vec<String>> = dirs.iter().map(|d| name_parts(d)).collect();
+    let mut depths = vec![1; dirs.len()];
+    loop {
+        let names: Vec<String> = parts
+            .iter()
+            .zip(&depths)
+            .map(|(parts, &depth)| parts[parts.len().saturating_sub(depth)..].join("-"))
+            .collect();
+        let mut extended = false;
+        for (i, name) in names.iter().enumerate() {
+            let clashes = names
+                .iter()
+                .enumerate()
+                .any(|(j, other)| j != i && other == name);
+            if clashes && depths[i] < parts[i].len() {
+                depths[i] += 1;
+                extended = true;
+            }
+        }
+        if !extended {
+            return names;
+        }
+    }
+}
+
+fn name_parts(path: &Path) -> Vec<String> {
+    let parts: Vec<String> = path
+        .components()
+        .filter_map(|c| match c {
+            Component::Normal(part) => Some(part.to_string_lossy().into_owned()),
+            _ => None,
+        })
+        .collect();
+    if parts.is_empty() {
+        vec![path.to_string_lossy().into_owned()]
+    } else {
+        parts
+    }
+}
+
+/// Run the script for one project and write its results; returns total rows.
+fn run_project(options: SessionOptions, script: &str, out: &Path) -> Result<usize> {
+    let session = QuerySession::with_options(options)?;
+    let csv = out
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("csv"));
+
+    let mut rows = 0;
+    let mut sections = Vec::new();
+    for stmt in split_statements(script) {
+        let result = session.query(stmt)?;
+        rows += result.row_count();
+        sections.push(if csv {
+            formatter::format_csv(result.columns(), result.rows())
+        } else {
+            result.to_tsv()
+        });
+    }
+
+    if let Some(parent) = out.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(out, sections.join("\n\n") + "\n")?;
+    Ok(rows)
+}
+
+fn expand_home(pattern: &str) -> String {
+    match (pattern.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest).to_string_lossy().into_owned(),
+        _ => pattern.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plan_outputs_disambiguates_shared_names() {
+        let dirs: Vec<PathBuf> = [
+            "/home/me/a/widget",
+            "/home/me/b/widget",
+            "/home/me/b/gadget",
+        ]
+        .iter()
+        .map(PathBuf::from)
+        .collect();
+        let outputs = plan_outputs(&dirs, "reports/{project}.csv").unwrap();
+        let paths: Vec<&str> = outputs.iter().map(|(_, p)| p.to_str().unwrap()).collect();
+        assert_eq!(
+            paths,
+            [
+                "reports/a-widget.csv",
+                "reports/b-widget.csv",
+                "reports/gadget.csv"
+            ]
+        );
+        assert_eq!(outputs[2].0, "gadget");
+    }
+
+    #[test]
+    fn test_plan_outputs_rejects_collisions() {
+        let dirs = vec![PathBuf::from("/x/one"), PathBuf::from("/x/two")];
+        let err = plan_outputs(&dirs, "report.tsv").unwrap_err();
+        assert!(err.to_string().contains("would both write report.tsv"));
+        assert!(plan_outputs(&dirs[..1], "report.tsv").is_ok());
+    }
+
+    #[test]
+    fn test_expand_home() {
+        assert_eq!(expand_home("/abs/*"), "/abs/*");
+        assert!(!expand_home("~/code/*").starts_with('~'));
+    }
+}
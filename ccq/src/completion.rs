@@ -0,0 +1,293 @@
+//! REPL line-editing helper: tab completion for dot commands, view names,
+//! view columns, session IDs, common SQL keywords and functions, and file
+//! paths, plus highlighting.
+
+use std::borrow::Cow;
+
+use rustyline::completion::{Completer, FilenameCompleter, Pair};
+use rustyline::highlight::{CmdKind, Highlighter};
+use rustyline::hint::Hinter;
+use rustyline::validate::Validator;
+use rustyline::{Context, Helper};
+
+use crate::QuerySession;
+use crate::highlight;
+
+pub(crate) const KEYWORDS: &[&str] = &[
+    "SELECT", "FROM", "WHERE", "GROUP", "BY", "ORDER", "HAVING", "LIMIT", "OFFSET", "JOIN",
+    "LEFT", "RIGHT", "INNER", "OUTER", "FULL", "CROSS", "ON", "USING", "AS", "AND", "OR", "NOT",
+    "IN", "IS", "NULL", "LIKE", "ILIKE", "BETWEEN", "CASE", "WHEN", "THEN", "ELSE", "END",
+    "DISTINCT", "UNION", "ALL", "EXCEPT", "INTERSECT", "WITH", "ASC", "DESC", "CREATE",
+    "REPLACE", "TEMP", "VIEW", "TABLE", "MACRO", "DESCRIBE", "EXPLAIN", "ANALYZE", "QUALIFY",
+    "OVER", "PARTITION", "INTERVAL", "CAST", "FILTER", "TRUE", "FALSE",
+];
+
+const FUNCTIONS: &[&str] = &[
+    "count", "sum", "avg", "min", "max", "any_value", "arg_max", "arg_min", "list",
+    "string_agg", "coalesce", "round", "greatest", "least", "row_number", "lag", "lead",
+    "json_extract", "json_extract_string", "json_type", "json_array_length", "json_keys",
+    "unnest", "regexp_matches", "regexp_extract", "regexp_replace", "starts_with", "contains",
+    "lower", "upper", "length", "substr", "split_part", "trim", "strftime", "date_trunc",
+    "date_diff", "epoch", "now",
+];
+
+/// Dot commands that take a view name as their argument.
+const VIEW_ARG_COMMANDS: &[&str] = &[".schema", ".s"];
+
+/// Dot commands taking a file path, with the path's argument position.
+const PATH_ARG_COMMANDS: &[(&str, usize)] =
+    &[(".read", 1), (".output", 1), (".once", 1), (".export", 2)];
+
+/// Dot commands whose first argument is a session ID or prefix.
+const SESSION_ARG_COMMANDS: &[&str] = &[".open", ".transcript", ".tree", ".sessions"];
+
+/// rustyline helper providing completion and highlighting.
+pub struct ReplHelper {
+    dot_commands: &'static [&'static str],
+    views: Vec<String>,
+    columns: Vec<String>,
+    session_ids: Vec<String>,
+    continuation_prompt: Option<&'static str>,
+    files: FilenameCompleter,
+}
+
+impl ReplHelper {
+    pub fn new(dot_commands: &'static [&'static str], views: &[&str], columns: Vec<String>) -> Self {
+        let mut columns = columns;
+        columns.sort();
+        columns.dedup();
+        Self {
+            dot_commands,
+            views: views.iter().map(ToString::to_string).collect(),
+            columns,
+            session_ids: Vec::new(),
+            continuation_prompt: None,
+            files: FilenameCompleter::new(),
+        }
+    }
+
+    /// Dim this prompt when it is shown.
+    #[must_use]
+    pub const fn with_continuation_prompt(mut self, prompt: &'static str) -> Self {
+        self.continuation_prompt = Some(prompt);
+        self
+    }
+
+    /// Complete these session IDs after `.open`, `.transcript`, `.tree`,
+    /// and `.sessions`.
+    #[must_use]
+    pub fn with_session_ids(mut self, session_ids: Vec<String>) -> Self {
+        self.session_ids = session_ids;
+        self
+    }
+
+    /// Build a helper with the columns of each view, read via `DESCRIBE`,
+    /// and the session IDs found at startup. Views that fail to describe
+    /// contribute no columns.
+    pub fn load(session: &QuerySession, dot_commands: &'static [&'static str], views: &[&str]) -> Self {
+        let columns = views
+            .iter()
+            .filter_map(|view| session.query(&format!("DESCRIBE {view}")).ok())
+            .flat_map(|result| result.rows().iter().map(|row| row[0].clone()).collect::<Vec<_>>())
+            .collect();
+        Self::new(dot_commands, views, columns).with_session_ids(session.info().session_ids())
+    }
+
+    /// Replacement start and candidates for the word ending at `pos`.
+    fn candidates(&self, line: &str, pos: usize) -> (usize, Vec<String>) {
+        let before = &line[..pos];
+        let start = before
+            .rfind(|c: char| !(c.is_alphanumeric() || c == '_' || c == '.'))
+            .map_or(0, |i| i + 1);
+        let word = &before[start..];
+
+        // Dot command name
+        if word.starts_with('.') && before[..start].trim().is_empty() {
+            return (start, prefixed(self.dot_commands.iter().copied(), word));
+        }
+
+        // Session IDs contain `-`, so the word runs back to the last space
+        if let Some((command, 1)) = argument_at(before)
+            && SESSION_ARG_COMMANDS.iter().any(|c| c.eq_ignore_ascii_case(command))
+        {
+            let start = before.rfind(char::is_whitespace).map_or(0, |i| i + 1);
+            let all = command.eq_ignore_ascii_case(".open").then_some("all");
+            let ids = all.into_iter().chain(self.session_ids.iter().map(String::as_str));
+            return (start, prefixed(ids, &before[start..]));
+        }
+
+        // Qualified `alias.col`: complete the part after the last dot
+        let (start, word) = word
+            .rfind('.')
+            .map_or((start, word), |i| (start + i + 1, &word[i + 1..]));
+        if word.is_empty() {
+            return (start, Vec::new());
+        }
+
+        let command = before.split_whitespace().next().unwrap_or_default();
+        if VIEW_ARG_COMMANDS.contains(&command) {
+            return (start, prefixed(self.views.iter().map(String::as_str), word));
+        }
+
+        let lowercase = !word.chars().any(char::is_uppercase);
+        let keywords = KEYWORDS.iter().map(|k| {
+            if lowercase {
+                k.to_lowercase()
+            } else {
+                (*k).to_string()
+            }
+        });
+        let mut out = prefixed(self.views.iter().map(String::as_str), word);
+        out.extend(prefixed(self.columns.iter().map(String::as_str), word));
+        out.extend(prefixed(FUNCTIONS.iter().copied(), word));
+        out.extend(keywords.filter(|k| starts_with_ignore_case(k, word)));
+        out.dedup();
+        (start, out)
+    }
+}
+
+/// Whether the word ending at `pos` is a dot command's file path argument.
+fn is_path_arg(line: &str, pos: usize) -> bool {
+    let Some((command, index)) = argument_at(&line[..pos]) else {
+        return false;
+    };
+    PATH_ARG_COMMANDS
+        .iter()
+        .any(|&(name, arg)| name.eq_ignore_ascii_case(command) && arg == index)
+}
+
+/// The command at the start of `before` and the position of the argument
+/// being typed at its end (1 for the first argument).
+fn argument_at(before: &str) -> Option<(&str, usize)> {
+    let mut words = before.split_whitespace();
+    let command = words.next()?;
+    let typed = words.count();
+    let index = if before.ends_with(char::is_whitespace) { typed + 1 } else { typed };
+    Some((command, index))
+}
+
+fn starts_with_ignore_case(candidate: &str, prefix: &str) -> bool {
+    candidate
+        .get(..prefix.len())
+        .is_some_and(|head| head.eq_ignore_ascii_case(prefix))
+}
+
+fn prefixed<'a>(candidates: impl Iterator<Item = &'a str>, prefix: &str) -> Vec<String> {
+    candidates
+        .filter(|c| starts_with_ignore_case(c, prefix))
+        .map(str::to_string)
+        .collect()
+}
+
+impl Completer for ReplHelper {
+    type Candidate = Pair;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<Pair>)> {
+        if is_path_arg(line, pos) {
+            return self.files.complete_path(line, pos);
+        }
+        let (start, candidates) = self.candidates(line, pos);
+        Ok((
+            start,
+            candidates
+                .into_iter()
+                .map(|c| Pair {
+                    display: c.clone(),
+                    replacement: c,
+                })
+                .collect(),
+        ))
+    }
+}
+
+impl Hinter for ReplHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ReplHelper {
+    fn highlight<'l>(&self, line: &'l str, _pos: usize) -> Cow<'l, str> {
+        Cow::Owned(highlight::highlight_sql(line, KEYWORDS, &self.views))
+    }
+
+    fn highlight_prompt<'b, 's: 'b, 'p: 'b>(
+        &'s self,
+        prompt: &'p str,
+        _default: bool,
+    ) -> Cow<'b, str> {
+        if self.continuation_prompt == Some(prompt) {
+            Cow::Owned(highlight::dim(prompt))
+        } else {
+            Cow::Borrowed(prompt)
+        }
+    }
+
+    fn highlight_char(&self, _line: &str, _pos: usize, kind: CmdKind) -> bool {
+        kind != CmdKind::MoveCursor
+    }
+}
+
+impl Validator for ReplHelper {}
+
+impl Helper for ReplHelper {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn helper() -> ReplHelper {
+        ReplHelper::new(
+            &[".help", ".schema", ".show", ".quit"],
+            &["messages", "tool_uses"],
+            vec!["type".to_string(), "timestamp".to_string(), "tool_name".to_string()],
+        )
+    }
+
+    #[test]
+    fn test_complete_dot_commands_and_view_args() {
+        let h = helper();
+        assert_eq!(h.candidates(".s", 2), (0, vec![".schema".to_string(), ".show".to_string()]));
+        assert_eq!(h.candidates(".schema to", 10), (8, vec!["tool_uses".to_string()]));
+    }
+
+    #[test]
+    fn test_complete_session_ids() {
+        let ids = vec!["0b1e-77".to_string(), "0b2f-88".to_string(), "9c3d-99".to_string()];
+        let h = helper().with_session_ids(ids);
+        assert_eq!(h.candidates(".tree 0b1", 9), (6, vec!["0b1e-77".to_string()]));
+        assert_eq!(h.candidates(".transcript 0b1e-", 17), (12, vec!["0b1e-77".to_string()]));
+        assert_eq!(h.candidates(".open a", 7), (6, vec!["all".to_string()]));
+        assert_eq!(h.candidates(".sessions ", 10).1.len(), 3);
+        assert!(h.candidates(".tree 0b1e-77 0", 15).1.is_empty());
+    }
+
+    #[test]
+    fn test_is_path_arg() {
+        assert!(is_path_arg(".read ", 6));
+        assert!(is_path_arg(".read scr", 9));
+        assert!(!is_path_arg(".read", 5));
+        assert!(!is_path_arg(".export ", 8));
+        assert!(is_path_arg(".export csv out", 15));
+        assert!(!is_path_arg("SELECT ", 7));
+    }
+
+    #[test]
+    fn test_complete_sql_words() {
+        let h = helper();
+        let (start, words) = h.candidates("SELECT ty FROM messages", 9);
+        assert_eq!((start, words), (7, vec!["type".to_string()]));
+
+        let (_, words) = h.candidates("sel", 3);
+        assert_eq!(words, vec!["select".to_string()]);
+        let (_, words) = h.candidates("SEL", 3);
+        assert_eq!(words, vec!["SELECT".to_string()]);
+
+        let (start, words) = h.candidates("SELECT m.tool", 13);
+        assert_eq!(start, 9);
+        assert_eq!(words, vec!["tool_uses".to_string(), "tool_name".to_string()]);
+    }
+}
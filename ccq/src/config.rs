@@ -0,0 +1,282 @@
+//! User configuration loaded from `~/.config/ccq/config.toml`.
+//!
+//! ```toml
+//! # Queries run before the prompt appears, e.g. to materialize slow views
+//! warmup = [
+//!   "CREATE OR REPLACE TEMP TABLE recent AS SELECT * FROM messages WHERE timestamp > now() - INTERVAL 7 DAY",
+//!   { name = "budgets", sql = "CREATE OR REPLACE TEMP TABLE budgets AS SELECT ..." },
+//! ]
+//!
+//! # DuckDB macros: a query becomes a table macro (`FROM tools()`), anything
+//! # else a scalar macro (`SELECT usd(input_tokens, output_tokens)`)
+//! [macros]
+//! tools = "SELECT tool_name, count(*) AS n FROM tool_uses GROUP BY ALL ORDER BY n DESC"
+//! usd = { params = ["i", "o"], sql = "i * 3e-6 + o * 15e-6" }
+//!
+//! # Prices (USD per million tokens) for the `model_pricing` table behind the
+//! # `costs` view, replacing a built-in prefix or adding a new one; cache
+//! # rates default to 1.25x (write) and 0.1x (read) the input rate
+//! [pricing]
+//! "claude-opus-4-5" = { input = 5.0, output = 25.0, cache_write = 6.25, cache_read = 0.5 }
+//! "my-gateway-model" = { input = 2.0, output = 8.0 }
+//!
+//! # Line editing and prompt for the interactive REPL
+//! [repl]
+//! edit_mode = "vi"
+//! prompt = "{project} ({sessions})> "
+//! ```
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::pricing::ModelRates;
+use crate::utils::{ccq_config_dir, sql_ident};
+use crate::{Error, Result};
+
+/// Environment variable overriding the config file location.
+pub const CONFIG_ENV: &str = "CCQ_CONFIG";
+
+/// Parsed configuration; every section is optional.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Queries to run at startup, in order
+    pub warmup: Vec<WarmupQuery>,
+    /// Macros registered at startup, by name
+    pub macros: BTreeMap<String, MacroDef>,
+    /// Interactive REPL settings
+    pub repl: ReplConfig,
+    /// Per-model price overrides for `model_pricing`, by model-name prefix
+    pub pricing: BTreeMap<String, ModelRates>,
+}
+
+/// `[repl]`: key bindings and prompt for the interactive REPL.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ReplConfig {
+    /// Key bindings, `emacs` (the default) or `vi`
+    pub edit_mode: EditMode,
+    /// Prompt template; see [`expand_prompt`] for the placeholders
+    pub prompt: Option<String>,
+}
+
+/// Line-editing key bindings.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EditMode {
+    #[default]
+    Emacs,
+    Vi,
+}
+
+/// Values substituted into a prompt template.
+#[derive(Debug, Clone, Copy)]
+pub struct PromptContext<'a> {
+    /// Project directory name, or `all`
+    pub project: &'a str,
+    /// Session files loaded, `None` under `--no-count`
+    pub sessions: Option<usize>,
+    /// Current `.mode`
+    pub mode: &'a str,
+}
+
+/// Fill in a prompt template's `{project}`, `{sessions}` (`?` when not
+/// counted), and `{mode}` placeholders.
+#[allow(clippy::literal_string_with_formatting_args)] // template placeholders
+pub fn expand_prompt(template: &str, context: &PromptContext<'_>) -> String {
+    let sessions = context.sessions.map_or_else(|| "?".to_string(), |n| n.to_string());
+    template
+        .replace("{project}", context.project)
+        .replace("{sessions}", &sessions)
+        .replace("{mode}", context.mode)
+}
+
+/// A startup query, optionally named for progress output.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+pub enum WarmupQuery {
+    Sql(String),
+    Named { name: String, sql: String },
+}
+
+impl WarmupQuery {
+    /// SQL to execute.
+    pub fn sql(&self) -> &str {
+        match self {
+            Self::Sql(sql) | Self::Named { sql, .. } => sql,
+        }
+    }
+
+    /// Label for progress output: the name, or the first line of the SQL.
+    pub fn label(&self) -> &str {
+        match self {
+            Self::Named { name, .. } => name,
+            Self::Sql(sql) => sql.trim().lines().next().unwrap_or_default(),
+        }
+    }
+}
+
+/// A user macro body, optionally with parameters.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+pub enum MacroDef {
+    Sql(String),
+    WithParams { params: Vec<String>, sql: String },
+}
+
+impl MacroDef {
+    /// `CREATE OR REPLACE MACRO` statement defining this macro as `name`: a
+    /// table macro when the body is a query, a scalar macro otherwise.
+    pub fn create_sql(&self, name: &str) -> String {
+        let (params, body): (&[String], &str) = match self {
+            Self::Sql(sql) => (&[], sql),
+            Self::WithParams { params, sql } => (params, sql),
+        };
+        let body = body.trim().trim_end_matches(';');
+        let first = body.split_whitespace().next().unwrap_or_default().to_uppercase();
+        let table = if matches!(first.as_str(), "SELECT" | "WITH" | "FROM") { "TABLE " } else { "" };
+        format!(
+            "CREATE OR REPLACE MACRO {}({}) AS {table}{body}",
+            sql_ident(name),
+            params.join(", ")
+        )
+    }
+}
+
+impl Config {
+    /// Config file location: `$CCQ_CONFIG`, else `~/.config/ccq/config.toml`.
+    pub fn path() -> Option<PathBuf> {
+        std::env::var_os(CONFIG_ENV)
+            .map(PathBuf::from)
+            .or_else(|| ccq_config_dir().map(|dir| dir.join("config.toml")))
+    }
+
+    /// Load the config file, or defaults when it doesn't exist.
+    ///
+    /// # Errors
+    /// Returns error if the file exists but can't be read or parsed.
+    pub fn load() -> Result<Self> {
+        match Self::path() {
+            Some(path) if path.exists() => Self::load_from(&path),
+            _ => Ok(Self::default()),
+        }
+    }
+
+    /// Load a specific config file.
+    ///
+    /// # Errors
+    /// Returns error if the file can't be read or parsed.
+    pub fn load_from(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path)?;
+        toml::from_str(&text).map_err(|e| Error::Config {
+            path: path.to_path_buf(),
+            message: e.message().to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_warmup_mixed_forms() {
+        let config: Config = toml::from_str(
+            r#"
+            warmup = [
+              "SELECT 1\nFROM t",
+              { name = "budgets", sql = "SELECT 2" },
+            ]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.warmup.len(), 2);
+        assert_eq!(config.warmup[0].label(), "SELECT 1");
+        assert_eq!(config.warmup[1].label(), "budgets");
+        assert_eq!(config.warmup[1].sql(), "SELECT 2");
+    }
+
+    #[test]
+    fn test_parse_macros() {
+        let config: Config = toml::from_str(
+            r#"
+            [macros]
+            tools = "SELECT tool_name FROM tool_uses;"
+            usd = { params = ["i", "o"], sql = "i * 3e-6 + o * 15e-6" }
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.macros["tools"].create_sql("tools"),
+            "CREATE OR REPLACE MACRO \"tools\"() AS TABLE SELECT tool_name FROM tool_uses"
+        );
+        assert_eq!(
+            config.macros["usd"].create_sql("usd"),
+            "CREATE OR REPLACE MACRO \"usd\"(i, o) AS i * 3e-6 + o * 15e-6"
+        );
+    }
+
+    #[test]
+    fn test_empty_config_is_default() {
+        let config: Config = toml::from_str("").unwrap();
+        assert!(config.warmup.is_empty());
+        assert_eq!(config.repl.edit_mode, EditMode::Emacs);
+        assert!(config.repl.prompt.is_none());
+    }
+
+    #[test]
+    fn test_parse_repl_section_and_expand_prompt() {
+        let config: Config = toml::from_str(
+            r#"
+            [repl]
+            edit_mode = "vi"
+            prompt = "{project} ({sessions}) {mode}> "
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.repl.edit_mode, EditMode::Vi);
+        let template = config.repl.prompt.unwrap();
+        let context = PromptContext {
+            project: "cc-query",
+            sessions: Some(12),
+            mode: "table",
+        };
+        assert_eq!(expand_prompt(&template, &context), "cc-query (12) table> ");
+        let uncounted = PromptContext {
+            sessions: None,
+            ..context
+        };
+        assert_eq!(expand_prompt("{sessions}> ", &uncounted), "?> ");
+        assert!(toml::from_str::<Config>("[repl]\nedit_mode = \"nano\"").is_err());
+    }
+
+    #[test]
+    fn test_parse_pricing_overrides() {
+        let config: Config = toml::from_str(
+            r#"
+            [pricing]
+            "claude-opus-4-5" = { input = 5.0, output = 25.0, cache_write = 6.25, cache_read = 0.5 }
+            "my-gateway-model" = { input = 2, output = 8 }
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.pricing.len(), 2);
+        assert_eq!(config.pricing["claude-opus-4-5"].cache_read, Some(0.5));
+        let gateway = config.pricing["my-gateway-model"];
+        assert!((gateway.output - 8.0).abs() < f64::EPSILON);
+        assert_eq!(gateway.cache_write, None);
+        assert!(toml::from_str::<Config>("[pricing]\nx = { input = 1.0 }").is_err());
+    }
+
+    #[test]
+    fn test_load_from_reports_path_on_error() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = tmp.path().join("config.toml");
+        fs::write(&path, "warmup = 3").unwrap();
+        let err = Config::load_from(&path).unwrap_err();
+        assert!(err.to_string().contains("config.toml"));
+    }
+}
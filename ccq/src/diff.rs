@@ -0,0 +1,196 @@
+//! Side-by-side comparison of two sessions.
+
+use std::fmt::Write;
+
+use crate::formatter;
+use crate::utils::sql_quote;
+use crate::{QuerySession, Result};
+
+/// Per-session metrics as (label, scalar query). `{s}` is the quoted session ID.
+const METRICS: &[(&str, &str)] = &[
+    ("messages", "SELECT count(*) FROM messages WHERE sessionId = {s}"),
+    ("human prompts", "SELECT count(*) FROM human_messages WHERE sessionId = {s}"),
+    ("assistant messages", "SELECT count(*) FROM assistant_messages WHERE sessionId = {s}"),
+    ("agent messages", "SELECT count(*) FROM messages WHERE sessionId = {s} AND isAgent"),
+    (
+        "duration (min)",
+        "SELECT round(epoch(max(timestamp) - min(timestamp)) / 60, 1) FROM messages WHERE sessionId = {s}",
+    ),
+    (
+        "input tokens",
+        "SELECT coalesce(sum(input_tokens), 0) FROM response_usage WHERE sessionId = {s}",
+    ),
+    (
+        "output tokens",
+        "SELECT coalesce(sum(output_tokens), 0) FROM response_usage WHERE sessionId = {s}",
+    ),
+    (
+        "cache read tokens",
+        "SELECT coalesce(sum(cache_read_tokens), 0) FROM response_usage WHERE sessionId = {s}",
+    ),
+    (
+        "cache creation tokens",
+        "SELECT coalesce(sum(cache_creation_tokens), 0) FROM response_usage WHERE sessionId = {s}",
+    ),
+    ("tool calls", "SELECT count(*) FROM tool_uses WHERE sessionId = {s}"),
+    ("tool errors", "SELECT count(*) FROM tool_results WHERE sessionId = {s} AND is_error"),
+    (
+        "tool error rate (%)",
+        "SELECT coalesce(round(100.0 * count(*) FILTER (WHERE is_error) / nullif(count(*), 0), 1), 0) FROM tool_results WHERE sessionId = {s}",
+    ),
+    (
+        "api errors",
+        "SELECT count(*) FROM assistant_messages WHERE sessionId = {s} AND isApiErrorMessage",
+    ),
+    (
+        "files touched",
+        "SELECT count(DISTINCT file_path) FROM file_operations WHERE sessionId = {s}",
+    ),
+    (
+        "files modified",
+        "SELECT count(DISTINCT file_path) FROM file_operations WHERE sessionId = {s} AND tool_name IN ('Write', 'Edit')",
+    ),
+];
+
+/// Compare two sessions and return the rendered report.
+///
+/// # Errors
+/// Returns error if either session can't be resolved or a query fails.
+pub fn diff(session: &QuerySession, a: &str, b: &str) -> Result<String> {
+    let a = session.resolve_session_id(a)?;
+    let b = session.resolve_session_id(b)?;
+    let a_label = short_id(&a);
+    let b_label = short_id(&b);
+    let header = |first: &str| {
+        vec![
+            first.to_string(),
+            a_label.to_string(),
+            b_label.to_string(),
+            "Δ".to_string(),
+        ]
+    };
+
+    let a_values = metric_values(session, &a)?;
+    let b_values = metric_values(session, &b)?;
+    let metric_rows: Vec<Vec<String>> = METRICS
+        .iter()
+        .zip(a_values.into_iter().zip(b_values))
+        .map(|((label, _), (x, y))| {
+            let d = delta(&x, &y);
+            vec![(*label).to_string(), x, y, d]
+        })
+        .collect();
+
+    let tools = session.query(&tool_mix_sql(&a, &b))?;
+    let tool_rows: Vec<Vec<String>> = tools
+        .rows()
+        .iter()
+        .map(|row| {
+            let d = delta(&row[1], &row[2]);
+            vec![row[0].clone(), row[1].clone(), row[2].clone(), d]
+        })
+        .collect();
+
+    let files = session.query(&file_overlap_sql(&a, &b))?;
+    let file_row = files.rows().first().cloned().unwrap_or_default();
+
+    let table_options = formatter::TableOptions {
+        row_count: false,
+        ..formatter::TableOptions::default()
+    };
+    let mut out = format!("A: {a}\nB: {b}\n\n");
+    out.push_str(&formatter::format_table_with(
+        &header("metric"),
+        &metric_rows,
+        &table_options,
+    ));
+    out.push_str("\n\nTool mix\n");
+    out.push_str(&formatter::format_table_with(
+        &header("tool"),
+        &tool_rows,
+        &table_options,
+    ));
+    if let [only_a, only_b, both] = file_row.as_slice() {
+        let _ = write!(
+            out,
+            "\n\nFiles: {only_a} only in A, {only_b} only in B, {both} in both"
+        );
+    }
+    Ok(out)
+}
+
+fn metric_values(session: &QuerySession, session_id: &str) -> Result<Vec<String>> {
+    let quoted = sql_quote(session_id);
+    let select = METRICS
+        .iter()
+        .map(|(_, sql)| format!("({})", sql.replace("{s}", &quoted)))
+        .collect::<Vec<_>>()
+        .join(",\n  ");
+    let result = session.query(&format!("SELECT\n  {select}"))?;
+    Ok(result.rows().first().cloned().unwrap_or_default())
+}
+
+fn tool_mix_sql(a: &str, b: &str) -> String {
+    let (a, b) = (sql_quote(a), sql_quote(b));
+    format!(
+        "SELECT
+           tool_name,
+           count(*) FILTER (WHERE sessionId = {a}) as a,
+           count(*) FILTER (WHERE sessionId = {b}) as b
+         FROM tool_uses
+         WHERE sessionId IN ({a}, {b})
+         GROUP BY tool_name
+         ORDER BY a + b DESC, tool_name"
+    )
+}
+
+fn file_overlap_sql(a: &str, b: &str) -> String {
+    let (a, b) = (sql_quote(a), sql_quote(b));
+    format!(
+        "SELECT
+           count(*) FILTER (WHERE in_a AND NOT in_b),
+           count(*) FILTER (WHERE in_b AND NOT in_a),
+           count(*) FILTER (WHERE in_a AND in_b)
+         FROM (
+           SELECT file_path, bool_or(sessionId = {a}) as in_a, bool_or(sessionId = {b}) as in_b
+           FROM file_operations
+           WHERE sessionId IN ({a}, {b}) AND file_path IS NOT NULL
+           GROUP BY file_path
+         )"
+    )
+}
+
+fn short_id(id: &str) -> &str {
+    id.get(..8).unwrap_or(id)
+}
+
+/// `b - a` with a sign, or empty when either side isn't numeric.
+fn delta(a: &str, b: &str) -> String {
+    if let (Ok(x), Ok(y)) = (a.parse::<i64>(), b.parse::<i64>()) {
+        return format!("{:+}", y - x);
+    }
+    match (a.parse::<f64>(), b.parse::<f64>()) {
+        (Ok(x), Ok(y)) => format!("{:+.1}", y - x),
+        _ => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delta() {
+        assert_eq!(delta("10", "25"), "+15");
+        assert_eq!(delta("7", "2"), "-5");
+        assert_eq!(delta("1.5", "1.2"), "-0.3");
+        assert_eq!(delta("NULL", "3"), "");
+    }
+
+    #[test]
+    fn test_metric_sql_placeholders() {
+        for (label, sql) in METRICS {
+            assert!(sql.contains("{s}"), "{label} must filter by session");
+        }
+    }
+}
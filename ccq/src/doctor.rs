@@ -0,0 +1,300 @@
+//! Diagnostics for common environment and data problems.
+
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+use duckdb::Connection;
+use rayon::prelude::*;
+use walkdir::WalkDir;
+
+use crate::query_session::{SessionOptions, SCHEMA_COLUMNS};
+use crate::session_loader;
+use crate::utils::{claude_projects_base, resolve_project_dir, sql_quote};
+use crate::Result;
+
+/// Outcome of a single check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Ok,
+    Warning,
+    Problem,
+}
+
+impl Status {
+    const fn marker(self) -> &'static str {
+        match self {
+            Self::Ok => "✓",
+            Self::Warning => "!",
+            Self::Problem => "✗",
+        }
+    }
+}
+
+/// A check result with optional detail lines.
+#[derive(Debug, Clone)]
+pub struct Check {
+    pub status: Status,
+    pub summary: String,
+    pub details: Vec<String>,
+}
+
+impl Check {
+    fn new(status: Status, summary: impl Into<String>) -> Self {
+        Self {
+            status,
+            summary: summary.into(),
+            details: Vec::new(),
+        }
+    }
+}
+
+/// Parse results for one JSONL file.
+#[derive(Debug, Default)]
+struct FileScan {
+    path: PathBuf,
+    malformed: usize,
+    first_malformed: Option<usize>,
+    error: Option<String>,
+}
+
+/// Run all diagnostics for the data selected by `options`.
+///
+/// # Errors
+/// Returns error if the in-memory database can't be opened.
+pub fn run(options: &SessionOptions) -> Result<Vec<Check>> {
+    let mut checks = Vec::new();
+
+    let mut missing = false;
+    for source in source_dirs(options) {
+        if source.exists() {
+            checks.push(Check::new(
+                Status::Ok,
+                format!("Data directory: {}", source.display()),
+            ));
+        } else {
+            missing = true;
+            checks.push(Check::new(
+                Status::Problem,
+                format!("Data directory not found: {}", source.display()),
+            ));
+        }
+    }
+    if missing {
+        return Ok(checks);
+    }
+
+    let conn = Connection::open_in_memory()?;
+    let version: String = conn.query_row("SELECT version()", [], |row| row.get(0))?;
+    checks.push(Check::new(Status::Ok, format!("DuckDB version: {version}")));
+
+    let info = session_loader::get_session_files(
+        options.project_dir.as_deref(),
+        options.session_filter.as_deref(),
+        &options.data_dirs,
+    )?;
+    if info.session_count() == 0 {
+        checks.push(Check::new(Status::Problem, "No session files found"));
+        return Ok(checks);
+    }
+    checks.push(Check::new(
+        Status::Ok,
+        format!(
+            "Found {} session(s), {} agent file(s) in {} director(ies)",
+            info.session_count(),
+            info.agent_count(),
+            info.directories().len()
+        ),
+    ));
+
+    let files: Vec<PathBuf> = info
+        .directories()
+        .iter()
+        .flat_map(|dir| jsonl_files(&dir.path))
+        .collect();
+    let scans: Vec<FileScan> = files.par_iter().map(|path| scan_file(path)).collect();
+    checks.push(unreadable_check(&scans));
+    checks.push(malformed_check(&scans));
+    checks.push(unknown_keys_check(&conn, &info.file_pattern().to_string())?);
+
+    Ok(checks)
+}
+
+/// Print checks; returns true when no check reported a problem.
+pub fn print_report(checks: &[Check]) -> bool {
+    for check in checks {
+        println!("{} {}", check.status.marker(), check.summary);
+        for detail in &check.details {
+            println!("    {detail}");
+        }
+    }
+    !checks.iter().any(|c| c.status == Status::Problem)
+}
+
+fn source_dirs(options: &SessionOptions) -> Vec<PathBuf> {
+    if !options.data_dirs.is_empty() {
+        return options.data_dirs.clone();
+    }
+    vec![options.project_dir.as_ref().map_or_else(claude_projects_base, |p| {
+        resolve_project_dir(&p.to_string_lossy()).claude_data_dir
+    })]
+}
+
+fn jsonl_files(dir: &Path) -> Vec<PathBuf> {
+    WalkDir::new(dir)
+        .into_iter()
+        .filter_map(std::result::Result::ok)
+        .filter(|e| e.file_type().is_file())
+        .map(walkdir::DirEntry::into_path)
+        .filter(|p| p.extension().is_some_and(|ext| ext == "jsonl"))
+        .collect()
+}
+
+/// Count lines that don't parse as JSON. Blank lines are ignored.
+fn scan_file(path: &Path) -> FileScan {
+    let mut scan = FileScan {
+        path: path.to_path_buf(),
+        ..FileScan::default()
+    };
+    let file = match fs::File::open(path) {
+        Ok(f) => f,
+        Err(e) => {
+            scan.error = Some(e.to_string());
+            return scan;
+        }
+    };
+
+    for (i, line) in BufReader::new(file).split(b'\n').enumerate() {
+        let line = match line {
+            Ok(line) => line,
+            Err(e) => {
+                scan.error = Some(e.to_string());
+                break;
+            }
+        };
+        if line.iter().all(u8::is_ascii_whitespace) {
+            continue;
+        }
+        if serde_json::from_slice::<serde_json::Value>(&line).is_err() {
+            scan.malformed += 1;
+            scan.first_malformed.get_or_insert(i + 1);
+        }
+    }
+    scan
+}
+
+fn unreadable_check(scans: &[FileScan]) -> Check {
+    let unreadable: Vec<&FileScan> = scans.iter().filter(|s| s.error.is_some()).collect();
+    if unreadable.is_empty() {
+        return Check::new(Status::Ok, format!("All {} JSONL file(s) readable", scans.len()));
+    }
+    let mut check = Check::new(
+        Status::Problem,
+        format!("{} unreadable file(s)", unreadable.len()),
+    );
+    check.details = unreadable
+        .iter()
+        .map(|s| format!("{}: {}", s.path.display(), s.error.as_deref().unwrap_or_default()))
+        .collect();
+    check
+}
+
+fn malformed_check(scans: &[FileScan]) -> Check {
+    let mut bad: Vec<&FileScan> = scans.iter().filter(|s| s.malformed > 0).collect();
+    if bad.is_empty() {
+        return Check::new(Status::Ok, "No malformed JSONL lines");
+    }
+    bad.sort_by(|a, b| b.malformed.cmp(&a.malformed).then_with(|| a.path.cmp(&b.path)));
+    let total: usize = bad.iter().map(|s| s.malformed).sum();
+    let mut check = Check::new(
+        Status::Warning,
+        format!("{total} malformed line(s) in {} file(s) (skipped by queries)", bad.len()),
+    );
+    check.details = bad
+        .iter()
+        .map(|s| {
+            format!(
+                "{}: {} (first at line {})",
+                s.path.display(),
+                s.malformed,
+                s.first_malformed.unwrap_or_default()
+            )
+        })
+        .collect();
+    check
+}
+
+fn unknown_keys_check(conn: &Connection, pattern_sql: &str) -> Result<Check> {
+    let mut stmt = conn.prepare(&unknown_keys_sql(pattern_sql))?;
+    let unknown = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<std::result::Result<Vec<(String, i64)>, _>>()?;
+
+    if unknown.is_empty() {
+        return Ok(Check::new(Status::Ok, "All message keys are known to the ccq schema"));
+    }
+    let mut check = Check::new(
+        Status::Warning,
+        format!("{} key(s) in the data are unknown to the ccq schema", unknown.len()),
+    );
+    check.details = unknown
+        .iter()
+        .map(|(key, lines)| format!("{key} ({lines} line(s))"))
+        .collect();
+    Ok(check)
+}
+
+fn unknown_keys_sql(pattern_sql: &str) -> String {
+    let known = SCHEMA_COLUMNS
+        .iter()
+        .map(|(key, _)| sql_quote(key))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!(
+        r"
+    SELECT key, count(*) as lines
+    FROM (
+      SELECT unnest(json_keys(json)) as key
+      FROM read_ndjson_objects({pattern_sql}, ignore_errors=true)
+      WHERE json->>'type' IN ('user', 'assistant', 'system')
+    )
+    WHERE key NOT IN ({known})
+    GROUP BY key
+    ORDER BY lines DESC, key
+  "
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_scan_file_counts_malformed_lines() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("s.jsonl");
+        let mut f = fs::File::create(&path).unwrap();
+        writeln!(f, "{{\"type\":\"user\"}}\n\n{{broken\n{{\"ok\":1}}\nalso broken").unwrap();
+
+        let scan = scan_file(&path);
+        assert_eq!(scan.malformed, 2);
+        assert_eq!(scan.first_malformed, Some(3));
+        assert!(scan.error.is_none());
+    }
+
+    #[test]
+    fn test_scan_file_unreadable() {
+        let scan = scan_file(Path::new("/nonexistent/ccq/doctor.jsonl"));
+        assert!(scan.error.is_some());
+        assert_eq!(unreadable_check(&[scan]).status, Status::Problem);
+    }
+
+    #[test]
+    fn test_unknown_keys_sql_excludes_schema_columns() {
+        let sql = unknown_keys_sql("'/p/*.jsonl'");
+        assert!(sql.contains("'uuid', 'type'"));
+        assert!(sql.contains("'toolUseID')"));
+    }
+}
@@ -10,6 +10,36 @@ pub enum Error {
     #[error("No JSONL files found in {}", path.display())]
     NoSessions { path: PathBuf },
 
+    #[error("No session file matching '{0}'")]
+    SessionNotFound(String),
+
+    #[error("Session prefix '{prefix}' is ambiguous ({matches} matches)")]
+    AmbiguousSession { prefix: String, matches: usize },
+
+    #[error("No message with uuid matching '{0}'")]
+    MessageNotFound(String),
+
+    #[error("Message prefix '{prefix}' is ambiguous ({matches} matching messages)")]
+    AmbiguousMessage { prefix: String, matches: usize },
+
+    #[error("Invalid config {}: {message}", path.display())]
+    Config { path: PathBuf, message: String },
+
+    #[error("Undefined environment variable '{0}'")]
+    UndefinedVariable(String),
+
+    #[error("Invalid query name '{0}' (use letters, digits, '-' and '_')")]
+    InvalidQueryName(String),
+
+    #[error("No saved query named '{0}'")]
+    SavedQueryNotFound(String),
+
+    #[error("Missing argument ${0}")]
+    MissingArgument(usize),
+
+    #[error("Refusing to overwrite the session being exported: {}", .0.display())]
+    OverwritesSource(PathBuf),
+
     #[error("Database error: {0}")]
     Database(#[from] duckdb::Error),
 
@@ -0,0 +1,338 @@
+//! Export a session back to importable JSONL with selected content trimmed.
+
+use std::fs;
+use std::io::{BufRead, Write};
+use std::path::PathBuf;
+
+use serde_json::{Map, Value};
+use walkdir::WalkDir;
+
+use crate::session_loader::SessionInfo;
+use crate::{Error, Result};
+
+const TOOL_RESULT_PLACEHOLDER: &str = "[tool result removed by ccq export-session]";
+const IMAGE_PLACEHOLDER: &str = "[image removed by ccq export-session]";
+
+/// Which message content to remove or shrink on export.
+#[derive(Debug, Clone, Default)]
+pub struct ExportOptions {
+    /// Replace tool result content with a placeholder
+    pub drop_tool_results: bool,
+    /// Truncate tool result text to at most this many characters
+    pub truncate_tool_results: Option<usize>,
+    /// Replace image blocks with a text placeholder
+    pub drop_images: bool,
+    /// Remove `file-history-snapshot` lines
+    pub drop_snapshots: bool,
+}
+
+/// Counts reported after an export.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ExportStats {
+    /// Lines read from the source file
+    pub lines: usize,
+    /// Lines rewritten with trimmed content
+    pub modified: usize,
+    /// Lines removed entirely
+    pub dropped: usize,
+    /// Bytes read
+    pub bytes_in: usize,
+    /// Bytes written
+    pub bytes_out: usize,
+}
+
+/// Find the main session file (not an agent file) whose name starts with `prefix`.
+///
+/// # Errors
+/// Returns error if no file or more than one file matches.
+pub fn find_session_file(info: &SessionInfo, prefix: &str) -> Result<PathBuf> {
+    let mut matches: Vec<PathBuf> = info
+        .directories()
+        .iter()
+        .flat_map(|dir| WalkDir::new(&dir.path).into_iter().filter_map(std::result::Result::ok))
+        .filter(|entry| entry.file_type().is_file())
+        .map(walkdir::DirEntry::into_path)
+        .filter(|path| {
+            let name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+            name.starts_with(prefix)
+                && !name.starts_with("agent-")
+                && path.extension().is_some_and(|ext| ext == "jsonl")
+                && !path.to_string_lossy().contains("/subagents/")
+        })
+        .collect();
+    matches.sort();
+    matches.dedup();
+
+    match matches.len() {
+        0 => Err(Error::SessionNotFound(prefix.to_string())),
+        1 => Ok(matches.remove(0)),
+        n => Err(Error::AmbiguousSession {
+            prefix: prefix.to_string(),
+            matches: n,
+        }),
+    }
+}
+
+/// Copy session JSONL from `reader` to `writer`, trimming content per `options`.
+///
+/// Lines that aren't valid JSON are copied through unchanged so the output
+/// never loses data that wasn't explicitly selected for removal.
+///
+/// # Errors
+/// Returns error if reading or writing fails.
+pub fn export_session<R: BufRead, W: Write>(
+    reader: R,
+    mut writer: W,
+    options: &ExportOptions,
+) -> Result<ExportStats> {
+    let mut stats = ExportStats::default();
+
+    for line in reader.lines() {
+        let line = line?;
+        stats.lines += 1;
+        stats.bytes_in += line.len() + 1;
+
+        let Ok(mut value) = serde_json::from_str::<Value>(&line) else {
+            writeln!(writer, "{line}")?;
+            stats.bytes_out += line.len() + 1;
+            continue;
+        };
+
+        match transform_line(&mut value, options) {
+            LineAction::Drop => stats.dropped += 1,
+            LineAction::Keep => {
+                writeln!(writer, "{line}")?;
+                stats.bytes_out += line.len() + 1;
+            }
+            LineAction::Modified => {
+                let out = value.to_string();
+                writeln!(writer, "{out}")?;
+                stats.modified += 1;
+                stats.bytes_out += out.len() + 1;
+            }
+        }
+    }
+    writer.flush()?;
+    Ok(stats)
+}
+
+/// Export the session matching `prefix` to `out` (or stdout).
+///
+/// # Errors
+/// Returns error if the session can't be found or I/O fails.
+pub fn export_to(
+    info: &SessionInfo,
+    prefix: &str,
+    out: Option<&std::path::Path>,
+    options: &ExportOptions,
+) -> Result<ExportStats> {
+    let source = find_session_file(info, prefix)?;
+    let reader = std::io::BufReader::new(fs::File::open(&source)?);
+    match out {
+        Some(path) => {
+            // Creating the output truncates it, which would destroy the source
+            if same_file(path, &source) {
+                return Err(Error::OverwritesSource(path.to_path_buf()));
+            }
+            let writer = std::io::BufWriter::new(fs::File::create(path)?);
+            export_session(reader, writer, options)
+        }
+        None => export_session(reader, std::io::stdout().lock(), options),
+    }
+}
+
+/// Whether both paths exist and resolve to the same file.
+fn same_file(a: &std::path::Path, b: &std::path::Path) -> bool {
+    matches!((fs::canonicalize(a), fs::canonicalize(b)), (Ok(a), Ok(b)) if a == b)
+}
+
+enum LineAction {
+    Keep,
+    Modified,
+    Drop,
+}
+
+fn transform_line(value: &mut Value, options: &ExportOptions) -> LineAction {
+    let Some(obj) = value.as_object_mut() else {
+        return LineAction::Keep;
+    };
+
+    if options.drop_snapshots
+        && obj.get("type").and_then(Value::as_str) == Some("file-history-snapshot")
+    {
+        return LineAction::Drop;
+    }
+
+    let mut modified = false;
+    let trims_results = options.drop_tool_results || options.truncate_tool_results.is_some();
+
+    if let Some(blocks) = obj
+        .get_mut("message")
+        .and_then(|m| m.get_mut("content"))
+        .and_then(Value::as_array_mut)
+    {
+        for block in blocks.iter_mut() {
+            modified |= transform_block(block, options);
+        }
+    }
+
+    // toolUseResult duplicates the tool output for UI rendering; the
+    // conversation replays fine without it.
+    if trims_results && obj.get("toolUseResult").is_some() && has_tool_result(obj) {
+        obj.remove("toolUseResult");
+        modified = true;
+    }
+
+    if modified {
+        LineAction::Modified
+    } else {
+        LineAction::Keep
+    }
+}
+
+fn has_tool_result(obj: &Map<String, Value>) -> bool {
+    obj.get("message")
+        .and_then(|m| m.get("content"))
+        .and_then(Value::as_array)
+        .is_some_and(|blocks| blocks.iter().any(|b| block_type(b) == Some("tool_result")))
+}
+
+fn block_type(block: &Value) -> Option<&str> {
+    block.get("type").and_then(Value::as_str)
+}
+
+fn transform_block(block: &mut Value, options: &ExportOptions) -> bool {
+    match block_type(block) {
+        Some("image") if options.drop_images => {
+            *block = serde_json::json!({ "type": "text", "text": IMAGE_PLACEHOLDER });
+            true
+        }
+        Some("tool_result") => {
+            let Some(content) = block.get_mut("content") else {
+                return false;
+            };
+            if options.drop_tool_results {
+                *content = Value::String(TOOL_RESULT_PLACEHOLDER.to_string());
+                return true;
+            }
+            let mut modified = false;
+            match content {
+                Value::String(text) => {
+                    if let Some(limit) = options.truncate_tool_results {
+                        modified |= truncate_text(text, limit);
+                    }
+                }
+                Value::Array(inner) => {
+                    for item in inner.iter_mut() {
+                        modified |= transform_block(item, options);
+                        if let (Some(limit), Some(Value::String(text))) =
+                            (options.truncate_tool_results, item.get_mut("text"))
+                        {
+                            modified |= truncate_text(text, limit);
+                        }
+                    }
+                }
+                _ => {}
+            }
+            modified
+        }
+        _ => false,
+    }
+}
+
+/// Truncate to `limit` characters, appending a marker. Returns true if changed.
+fn truncate_text(text: &mut String, limit: usize) -> bool {
+    let total = text.chars().count();
+    if total <= limit {
+        return false;
+    }
+    let kept: String = text.chars().take(limit).collect();
+    *text = format!("{kept}… [truncated {} chars]", total - limit);
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOOL_RESULT_LINE: &str = r#"{"type":"user","uuid":"u1","toolUseResult":{"stdout":"xxxxxxxxxx"},"message":{"role":"user","content":[{"type":"tool_result","tool_use_id":"t1","content":"xxxxxxxxxx"}]}}"#;
+
+    #[test]
+    fn test_export_refuses_to_overwrite_source() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let source = tmp.path().join("abc123.jsonl");
+        fs::write(&source, TOOL_RESULT_LINE).unwrap();
+        let dirs = [tmp.path().to_path_buf()];
+        let info = crate::session_loader::get_session_files(None, None, &dirs).unwrap();
+
+        let out = tmp.path().join(".").join("abc123.jsonl");
+        let result = export_to(&info, "abc", Some(&out), &ExportOptions::default());
+        assert!(matches!(result, Err(Error::OverwritesSource(_))));
+        assert_eq!(fs::read_to_string(&source).unwrap(), TOOL_RESULT_LINE);
+    }
+
+    fn run(input: &str, options: &ExportOptions) -> (String, ExportStats) {
+        let mut out = Vec::new();
+        let stats = export_session(input.as_bytes(), &mut out, options).unwrap();
+        (String::from_utf8(out).unwrap(), stats)
+    }
+
+    #[test]
+    fn test_export_passthrough_is_byte_identical() {
+        let input = format!("{TOOL_RESULT_LINE}\nnot json\n");
+        let (out, stats) = run(&input, &ExportOptions::default());
+        assert_eq!(out, input);
+        assert_eq!(stats.modified, 0);
+    }
+
+    #[test]
+    fn test_export_drop_tool_results() {
+        let options = ExportOptions {
+            drop_tool_results: true,
+            ..ExportOptions::default()
+        };
+        let (out, stats) = run(TOOL_RESULT_LINE, &options);
+        let value: Value = serde_json::from_str(out.trim()).unwrap();
+        assert_eq!(
+            value["message"]["content"][0]["content"],
+            TOOL_RESULT_PLACEHOLDER
+        );
+        assert_eq!(value["message"]["content"][0]["tool_use_id"], "t1");
+        assert!(value.get("toolUseResult").is_none());
+        assert_eq!(stats.modified, 1);
+    }
+
+    #[test]
+    fn test_export_truncate_tool_results() {
+        let options = ExportOptions {
+            truncate_tool_results: Some(4),
+            ..ExportOptions::default()
+        };
+        let (out, _) = run(TOOL_RESULT_LINE, &options);
+        let value: Value = serde_json::from_str(out.trim()).unwrap();
+        assert_eq!(
+            value["message"]["content"][0]["content"],
+            "xxxx… [truncated 6 chars]"
+        );
+    }
+
+    #[test]
+    fn test_export_drop_snapshots_and_images() {
+        let input = concat!(
+            r#"{"type":"file-history-snapshot","snapshot":{}}"#,
+            "\n",
+            r#"{"type":"user","message":{"content":[{"type":"image","source":{"data":"AAAA"}}]}}"#,
+            "\n"
+        );
+        let options = ExportOptions {
+            drop_images: true,
+            drop_snapshots: true,
+            ..ExportOptions::default()
+        };
+        let (out, stats) = run(input, &options);
+        assert_eq!(stats.dropped, 1);
+        assert_eq!(stats.modified, 1);
+        assert!(out.contains(IMAGE_PLACEHOLDER));
+        assert!(!out.contains("AAAA"));
+    }
+}
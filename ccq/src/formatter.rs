@@ -1,8 +1,10 @@
 //! Output formatting for query results.
 
 use chrono::{TimeZone, Utc};
+use chrono_tz::Tz;
 use duckdb::types::{TimeUnit, ValueRef};
-use std::fmt::{self, Display, Formatter};
+use std::borrow::Cow;
+use std::fmt::{self, Display, Formatter, Write as _};
 
 /// Wrapper for displaying `ValueRef` without allocation for text.
 pub struct DisplayValueRef<'a>(pub &'a ValueRef<'a>);
@@ -33,8 +35,51 @@ impl Display for DisplayValueRef<'_> {
     }
 }
 
+/// Convert a value to JSON, keeping numbers and booleans typed.
+///
+/// Timestamps and dates use the same text as table output; integers that
+/// don't fit in 64 bits and other exotic types fall back to their display text.
+pub fn json_value(value: &ValueRef) -> serde_json::Value {
+    use serde_json::Value;
+
+    match value {
+        ValueRef::Null => Value::Null,
+        ValueRef::Boolean(b) => Value::Bool(*b),
+        ValueRef::TinyInt(n) => Value::from(*n),
+        ValueRef::SmallInt(n) => Value::from(*n),
+        ValueRef::Int(n) => Value::from(*n),
+        ValueRef::BigInt(n) => Value::from(*n),
+        ValueRef::HugeInt(n) => {
+            i64::try_from(*n).map_or_else(|_| Value::String(n.to_string()), Value::from)
+        }
+        ValueRef::UTinyInt(n) => Value::from(*n),
+        ValueRef::USmallInt(n) => Value::from(*n),
+        ValueRef::UInt(n) => Value::from(*n),
+        ValueRef::UBigInt(n) => Value::from(*n),
+        ValueRef::Float(n) => Value::from(f64::from(*n)),
+        ValueRef::Double(n) => Value::from(*n),
+        _ => Value::String(DisplayValueRef(value).to_string()),
+    }
+}
+
+/// Display text for `value`, with timestamps (stored as UTC) shown in `zone`.
+pub fn display_in_zone(value: &ValueRef, zone: Tz) -> String {
+    match value {
+        ValueRef::Timestamp(unit, val) => format_timestamp_in(*unit, *val, &zone),
+        _ => DisplayValueRef(value).to_string(),
+    }
+}
+
 /// Format a timestamp as "YYYY-MM-DD HH:MM:SS.mmm"
 fn format_timestamp(unit: TimeUnit, value: i64) -> String {
+    format_timestamp_in(unit, value, &Utc)
+}
+
+/// Format a UTC timestamp as "YYYY-MM-DD HH:MM:SS.mmm" in `zone`'s local time.
+fn format_timestamp_in<Z: TimeZone>(unit: TimeUnit, value: i64, zone: &Z) -> String
+where
+    Z::Offset: fmt::Display,
+{
     let micros = match unit {
         TimeUnit::Second => value * 1_000_000,
         TimeUnit::Millisecond => value * 1_000,
@@ -45,7 +90,9 @@ fn format_timestamp(unit: TimeUnit, value: i64) -> String {
     let Some(dt) = Utc.timestamp_micros(micros).single() else {
         return "INVALID_TIMESTAMP".into();
     };
-    dt.format("%Y-%m-%d %H:%M:%S%.3f").to_string()
+    dt.with_timezone(zone)
+        .format("%Y-%m-%d %H:%M:%S%.3f")
+        .to_string()
 }
 
 /// Format a date (days since Unix epoch) to "YYYY-MM-DD"
@@ -57,7 +104,303 @@ fn format_date(days: i32) -> String {
     d.format("%Y-%m-%d").to_string()
 }
 
+/// Whether a value is numeric (and so eligible for number formatting).
+pub const fn is_numeric(value: &ValueRef) -> bool {
+    matches!(
+        value,
+        ValueRef::TinyInt(_)
+            | ValueRef::SmallInt(_)
+            | ValueRef::Int(_)
+            | ValueRef::BigInt(_)
+            | ValueRef::HugeInt(_)
+            | ValueRef::UTinyInt(_)
+            | ValueRef::USmallInt(_)
+            | ValueRef::UInt(_)
+            | ValueRef::UBigInt(_)
+            | ValueRef::Float(_)
+            | ValueRef::Double(_)
+            | ValueRef::Decimal(_)
+    )
+}
+
+/// Rendering options for table output.
+#[derive(Debug, Clone)]
+pub struct TableOptions {
+    /// Append the "(N rows)" footer
+    pub row_count: bool,
+    /// Separators and currency for numeric columns
+    pub numbers: NumberFormat,
+    /// Longest value shown in any column, in characters; longer values end in `…`
+    pub max_width: Option<usize>,
+    /// Per-column width limits, overriding `max_width`
+    pub column_widths: Vec<(String, usize)>,
+    /// Text shown for NULL values instead of `NULL`
+    pub null_value: Option<String>,
+    /// Color the header, dim NULLs, right-align numbers, and show rows whose
+    /// `is_error` column is true in red
+    pub color: bool,
+}
+
+impl Default for TableOptions {
+    fn default() -> Self {
+        Self {
+            row_count: true,
+            numbers: NumberFormat::default(),
+            max_width: None,
+            column_widths: Vec::new(),
+            null_value: None,
+            color: false,
+        }
+    }
+}
+
+impl TableOptions {
+    /// Width limit for `column`, if any.
+    pub fn width_for(&self, column: &str) -> Option<usize> {
+        self.column_widths
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(column))
+            .map(|&(_, width)| width)
+            .or(self.max_width)
+    }
+
+    /// Rows as shown to people: numbers formatted and the values `nulls`
+    /// marks replaced by `null_value`.
+    pub fn display_rows<'a>(
+        &self,
+        columns: &[String],
+        rows: &'a [Vec<String>],
+        nulls: &[Vec<bool>],
+        numeric: &[bool],
+    ) -> Cow<'a, [Vec<String>]> {
+        let mut rows = if self.numbers.is_plain() {
+            Cow::Borrowed(rows)
+        } else {
+            Cow::Owned(self.numbers.apply(columns, rows, numeric))
+        };
+        if let Some(null) = &self.null_value {
+            for (row, row_nulls) in rows.to_mut().iter_mut().zip(nulls) {
+                for (value, &is_null) in row.iter_mut().zip(row_nulls) {
+                    if is_null {
+                        null.clone_into(value);
+                    }
+                }
+            }
+        }
+        rows
+    }
+}
+
+/// When to color interactive output (`--color`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ColorChoice {
+    /// When stdout is a terminal and `NO_COLOR` is unset
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    pub const NAMES: &'static [&'static str] = &["auto", "always", "never"];
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "auto" => Some(Self::Auto),
+            "always" => Some(Self::Always),
+            "never" => Some(Self::Never),
+            _ => None,
+        }
+    }
+
+    /// Resolve the choice for output going to a terminal or not.
+    pub fn enabled(self, terminal: bool) -> bool {
+        match self {
+            Self::Auto => terminal && std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty()),
+            Self::Always => true,
+            Self::Never => false,
+        }
+    }
+}
+
+const HEADER: &str = "\x1b[1;36m";
+const DIM: &str = "\x1b[2m";
+const ERROR: &str = "\x1b[31m";
+const RESET: &str = "\x1b[0m";
+
+/// Width of `text` on screen: characters, not counting ANSI color codes.
+pub fn display_width(text: &str) -> usize {
+    let mut width = 0;
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // Skip to the end of the escape sequence
+            chars.by_ref().find(char::is_ascii_alphabetic);
+        } else {
+            width += 1;
+        }
+    }
+    width
+}
+
+/// Cut `value` to at most `max` characters, marking the cut with `…`.
+pub fn truncate_cell(value: &str, max: usize) -> Cow<'_, str> {
+    if value.chars().count() <= max {
+        return Cow::Borrowed(value);
+    }
+    let kept: String = value.chars().take(max.saturating_sub(1)).collect();
+    Cow::Owned(kept + "…")
+}
+
+/// How numbers are written in table output. TSV, CSV, and JSON output is
+/// never formatted so it stays machine-readable.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NumberFormat {
+    /// Thousands separator; `None` leaves digits ungrouped
+    pub group: Option<char>,
+    /// Decimal mark
+    pub decimal: char,
+    /// Symbol prefixed to cost columns (names containing `cost`, `price`, or ending in `usd`)
+    pub currency: Option<String>,
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        Self {
+            group: None,
+            decimal: '.',
+            currency: None,
+        }
+    }
+}
+
+/// Names accepted by [`NumberFormat::from_style`].
+pub const NUMBER_STYLES: &[&str] = &["plain", "comma", "dot", "space", "locale"];
+
+impl NumberFormat {
+    /// Parse a style name: `plain` (1234567.5), `comma` (1,234,567.5),
+    /// `dot` (1.234.567,5), `space` (1 234 567,5), or `locale` to pick one
+    /// from `LC_ALL` / `LC_NUMERIC` / `LANG`.
+    pub fn from_style(style: &str) -> Option<Self> {
+        let (group, decimal) = match style {
+            "plain" => (None, '.'),
+            "comma" => (Some(','), '.'),
+            "dot" => (Some('.'), ','),
+            "space" => (Some(' '), ','),
+            "locale" => {
+                let locale = ["LC_ALL", "LC_NUMERIC", "LANG"]
+                    .iter()
+                    .find_map(|var| std::env::var(var).ok().filter(|v| !v.is_empty()))
+                    .unwrap_or_default();
+                return Some(Self::for_locale(&locale));
+            }
+            _ => return None,
+        };
+        Some(Self {
+            group,
+            decimal,
+            currency: None,
+        })
+    }
+
+    /// Separators conventionally used by a POSIX locale name like `de_DE.UTF-8`.
+    pub fn for_locale(locale: &str) -> Self {
+        let language = locale.split(['_', '.', '@']).next().unwrap_or_default();
+        let style = match language {
+            "de" | "es" | "it" | "nl" | "pt" | "id" | "da" | "tr" | "el" | "ro" => "dot",
+            "fr" | "ru" | "pl" | "cs" | "sk" | "sv" | "nb" | "nn" | "fi" | "uk" | "hu" | "bg" => {
+                "space"
+            }
+            _ => "comma",
+        };
+        Self::from_style(style).unwrap_or_default()
+    }
+
+    /// Whether values pass through unchanged.
+    pub fn is_plain(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Format one numeric cell; anything that isn't a plain decimal number
+    /// (NULL, `inf`, exponent notation) is returned unchanged.
+    pub fn format(&self, value: &str, currency: bool) -> String {
+        let symbol = self.currency.as_deref().filter(|_| currency);
+        let rounded;
+        let value = match (symbol, value.parse::<f64>()) {
+            (Some(_), Ok(n)) if n.is_finite() => {
+                // Cents, unless that would hide a small nonzero cost
+                let places = if n != 0.0 && n.abs() < 0.01 { 4 } else { 2 };
+                rounded = format!("{n:.places$}");
+                rounded.as_str()
+            }
+            _ => value,
+        };
+
+        let (sign, unsigned) = value.strip_prefix('-').map_or(("", value), |v| ("-", v));
+        let (int, frac) = unsigned.split_once('.').map_or((unsigned, None), |(i, f)| (i, Some(f)));
+        let digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+        if !digits(int) || frac.is_some_and(|f| !digits(f)) {
+            return value.to_string();
+        }
+
+        let mut out = String::with_capacity(value.len() + value.len() / 3 + 4);
+        out.push_str(sign);
+        if let Some(symbol) = symbol {
+            out.push_str(symbol);
+        }
+        for (i, c) in int.chars().enumerate() {
+            if i > 0 && (int.len() - i) % 3 == 0
+                && let Some(group) = self.group
+            {
+                out.push(group);
+            }
+            out.push(c);
+        }
+        if let Some(frac) = frac {
+            out.push(self.decimal);
+            out.push_str(frac);
+        }
+        out
+    }
+
+    /// Format the numeric columns of a result set.
+    pub fn apply(&self, columns: &[String], rows: &[Vec<String>], numeric: &[bool]) -> Vec<Vec<String>> {
+        let currency: Vec<bool> = columns.iter().map(|c| is_currency_column(c)).collect();
+        rows.iter()
+            .map(|row| {
+                row.iter()
+                    .enumerate()
+                    .map(|(i, v)| {
+                        if numeric.get(i).copied().unwrap_or(false) {
+                            self.format(v, currency[i])
+                        } else {
+                            v.clone()
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+fn is_currency_column(name: &str) -> bool {
+    let name = name.to_lowercase();
+    name.contains("cost") || name.contains("price") || name.ends_with("usd")
+}
+
 /// Format results as a table with Unicode box-drawing characters.
+pub fn format_table(columns: &[String], rows: &[Vec<String>]) -> String {
+    format_table_with(columns, rows, &TableOptions::default())
+}
+
+/// Format results as a table with explicit rendering options, treating no
+/// column as numeric and no value as NULL.
+pub fn format_table_with(columns: &[String], rows: &[Vec<String>], options: &TableOptions) -> String {
+    format_table_typed(columns, rows, &[], &[], options)
+}
+
+/// Format results as a table; `numeric` marks columns right-aligned and
+/// `nulls` the values dimmed when `options.color` is set.
 ///
 /// Output format:
 /// ```text
@@ -68,12 +411,42 @@ fn format_date(days: i32) -> String {
 /// └──────────┴───────┘
 /// (N rows)
 /// ```
-pub fn format_table(columns: &[String], rows: &[Vec<String>]) -> String {
+pub fn format_table_typed(
+    columns: &[String],
+    rows: &[Vec<String>],
+    nulls: &[Vec<bool>],
+    numeric: &[bool],
+    options: &TableOptions,
+) -> String {
     if rows.is_empty() {
         // Special case: header only with "(0 rows)"
-        return format!("{}\n(0 rows)", columns.join(" | "));
+        let header = columns.join(" | ");
+        return if options.row_count {
+            format!("{header}\n(0 rows)")
+        } else {
+            header
+        };
     }
 
+    // Apply width limits (`.width`)
+    let limits: Vec<Option<usize>> = columns.iter().map(|c| options.width_for(c)).collect();
+    let rows: Cow<'_, [Vec<String>]> = if limits.iter().all(Option::is_none) {
+        Cow::Borrowed(rows)
+    } else {
+        Cow::Owned(
+            rows.iter()
+                .map(|row| {
+                    row.iter()
+                        .zip(&limits)
+                        .map(|(v, limit)| {
+                            limit.map_or_else(|| v.clone(), |max| truncate_cell(v, max).into_owned())
+                        })
+                        .collect()
+                })
+                .collect(),
+        )
+    };
+
     // Calculate column widths (max of header and data)
     let widths: Vec<usize> = columns
         .iter()
@@ -81,10 +454,10 @@ pub fn format_table(columns: &[String], rows: &[Vec<String>]) -> String {
         .map(|(i, name)| {
             let max_data = rows
                 .iter()
-                .map(|r| r.get(i).map_or(0, String::len))
+                .map(|r| r.get(i).map_or(0, |v| v.chars().count()))
                 .max()
                 .unwrap_or(0);
-            name.len().max(max_data)
+            name.chars().count().max(max_data)
         })
         .collect();
 
@@ -105,7 +478,7 @@ pub fn format_table(columns: &[String], rows: &[Vec<String>]) -> String {
     let header = columns
         .iter()
         .enumerate()
-        .map(|(i, name)| format!("{:width$}", name, width = widths[i]))
+        .map(|(i, name)| paint(options, HEADER, format!("{:width$}", name, width = widths[i])))
         .collect::<Vec<_>>()
         .join(" │ ");
     lines.push(format!("│ {header} │"));
@@ -122,14 +495,12 @@ pub fn format_table(columns: &[String], rows: &[Vec<String>]) -> String {
     lines.push(sep);
 
     // Data rows: │ val1  │ val2  │
-    for row in rows {
-        let row_str = row
-            .iter()
-            .enumerate()
-            .map(|(i, val)| format!("{:width$}", val, width = widths[i]))
-            .collect::<Vec<_>>()
-            .join(" │ ");
-        lines.push(format!("│ {row_str} │"));
+    let error_column = columns.iter().position(|c| c.eq_ignore_ascii_case("is_error"));
+    for (i, row) in rows.iter().enumerate() {
+        let failed = error_column.is_some_and(|i| row.get(i).is_some_and(|v| v == "true"));
+        let row_nulls = nulls.get(i).map_or(&[][..], Vec::as_slice);
+        let line = data_row(row, row_nulls, &widths, numeric, failed, options);
+        lines.push(format!("│ {line} │"));
     }
 
     // Bottom border: └─────┴─────┘
@@ -144,12 +515,320 @@ pub fn format_table(columns: &[String], rows: &[Vec<String>]) -> String {
     lines.push(bottom);
 
     // Row count
-    let row_word = if rows.len() == 1 { "row" } else { "rows" };
-    lines.push(format!("({} {row_word})", rows.len()));
+    if options.row_count {
+        let row_word = if rows.len() == 1 { "row" } else { "rows" };
+        lines.push(format!("({} {row_word})", rows.len()));
+    }
+
+    lines.join("\n")
+}
+
+/// A table printed a row at a time, for results too long to wait for.
+///
+/// Column widths are fixed from a sample of the first rows; later values
+/// wider than their column are cut with `…`.
+pub struct TableStream<'a> {
+    widths: Vec<usize>,
+    numeric: Vec<bool>,
+    error_column: Option<usize>,
+    columns: Vec<String>,
+    options: &'a TableOptions,
+}
+
+impl<'a> TableStream<'a> {
+    pub fn new(
+        columns: &[String],
+        sample: &[Vec<String>],
+        nulls: &[Vec<bool>],
+        numeric: &[bool],
+        options: &'a TableOptions,
+    ) -> Self {
+        let mut stream = Self {
+            widths: columns.iter().map(|name| name.chars().count()).collect(),
+            numeric: numeric.to_vec(),
+            error_column: columns.iter().position(|c| c.eq_ignore_ascii_case("is_error")),
+            columns: columns.to_vec(),
+            options,
+        };
+        for row in stream.cells(sample, nulls).iter() {
+            for (width, value) in stream.widths.iter_mut().zip(row) {
+                *width = (*width).max(value.chars().count());
+            }
+        }
+        stream
+    }
+
+    /// Top border, column names, and the separator below them.
+    pub fn header(&self) -> String {
+        let names = self
+            .columns
+            .iter()
+            .zip(&self.widths)
+            .map(|(name, &width)| paint(self.options, HEADER, format!("{name:width$}")))
+            .collect::<Vec<_>>()
+            .join(" │ ");
+        format!("{}\n│ {names} │\n{}", self.border('┌', '┬', '┐'), self.border('├', '┼', '┤'))
+    }
+
+    /// One data row, formatted like [`format_table_typed`] would.
+    pub fn row(&self, row: &[String], nulls: &[bool]) -> String {
+        let rows = [row.to_vec()];
+        let cells: Vec<String> = self.cells(&rows, &[nulls.to_vec()])[0]
+            .iter()
+            .zip(&self.widths)
+            .map(|(value, &width)| truncate_cell(value, width).into_owned())
+            .collect();
+        let failed = self
+            .error_column
+            .is_some_and(|i| row.get(i).is_some_and(|v| v == "true"));
+        let line = data_row(
+            &cells,
+            nulls,
+            &self.widths,
+            &self.numeric,
+            failed,
+            self.options,
+        );
+        format!("│ {line} │")
+    }
+
+    /// Bottom border.
+    pub fn footer(&self) -> String {
+        self.border('└', '┴', '┘')
+    }
+
+    /// Display values for `rows`: numbers, NULL text, and `.width` limits applied.
+    fn cells<'r>(&self, rows: &'r [Vec<String>], nulls: &[Vec<bool>]) -> Cow<'r, [Vec<String>]> {
+        let options = self.options;
+        let mut rows = options.display_rows(&self.columns, rows, nulls, &self.numeric);
+        for (i, name) in self.columns.iter().enumerate() {
+            if let Some(max) = options.width_for(name) {
+                for row in rows.to_mut() {
+                    if let Some(value) = row.get_mut(i) {
+                        *value = truncate_cell(value, max).into_owned();
+                    }
+                }
+            }
+        }
+        rows
+    }
+
+    fn border(&self, left: char, middle: char, right: char) -> String {
+        let segments: Vec<String> = self.widths.iter().map(|w| "─".repeat(w + 2)).collect();
+        format!("{left}{}{right}", segments.join(&middle.to_string()))
+    }
+}
+
+/// One row's padded cells joined by `│`, styled per `options.color`.
+fn data_row(
+    row: &[String],
+    nulls: &[bool],
+    widths: &[usize],
+    numeric: &[bool],
+    failed: bool,
+    options: &TableOptions,
+) -> String {
+    row.iter()
+        .enumerate()
+        .map(|(i, val)| {
+            let right = options.color && numeric.get(i).copied().unwrap_or(false);
+            let cell = if right {
+                format!("{:>width$}", val, width = widths[i])
+            } else {
+                format!("{:width$}", val, width = widths[i])
+            };
+            if nulls.get(i).copied().unwrap_or(false) {
+                paint(options, DIM, cell)
+            } else if failed {
+                paint(options, ERROR, cell)
+            } else {
+                cell
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" │ ")
+}
+
+fn paint(options: &TableOptions, code: &str, text: String) -> String {
+    if options.color {
+        format!("{code}{text}{RESET}")
+    } else {
+        text
+    }
+}
+
+/// Result rendering selected with `.mode`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputMode {
+    /// Box-drawn table
+    #[default]
+    Table,
+    Tsv,
+    Csv,
+    /// Array of row objects
+    Json,
+    /// GitHub-flavored Markdown table
+    Markdown,
+    /// One `column = value` line per field, rows separated by blank lines
+    Line,
+}
+
+impl OutputMode {
+    /// Mode names, as accepted by [`OutputMode::parse`].
+    pub const NAMES: &[&str] = &["table", "tsv", "csv", "json", "markdown", "line"];
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "table" => Some(Self::Table),
+            "tsv" | "tabs" => Some(Self::Tsv),
+            "csv" => Some(Self::Csv),
+            "json" => Some(Self::Json),
+            "markdown" | "md" => Some(Self::Markdown),
+            "line" => Some(Self::Line),
+            _ => None,
+        }
+    }
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Table => "table",
+            Self::Tsv => "tsv",
+            Self::Csv => "csv",
+            Self::Json => "json",
+            Self::Markdown => "markdown",
+            Self::Line => "line",
+        }
+    }
+}
 
+/// Format results as a JSON array of objects. Values `nulls` marks become
+/// `null` and values in numeric columns become numbers.
+pub fn format_json(
+    columns: &[String],
+    rows: &[Vec<String>],
+    nulls: &[Vec<bool>],
+    numeric: &[bool],
+) -> String {
+    use serde_json::Value;
+
+    let objects: Vec<Value> = rows
+        .iter()
+        .enumerate()
+        .map(|(r, row)| {
+            let row_nulls = nulls.get(r).map_or(&[][..], Vec::as_slice);
+            let object = columns
+                .iter()
+                .zip(row)
+                .enumerate()
+                .map(|(i, (column, value))| {
+                    let value = if row_nulls.get(i).copied().unwrap_or(false) {
+                        Value::Null
+                    } else if numeric.get(i).copied().unwrap_or(false) {
+                        serde_json::from_str(value).unwrap_or_else(|_| Value::String(value.clone()))
+                    } else {
+                        Value::String(value.clone())
+                    };
+                    (column.clone(), value)
+                })
+                .collect();
+            Value::Object(object)
+        })
+        .collect();
+    serde_json::to_string_pretty(&objects).unwrap_or_default()
+}
+
+/// Format results as a Markdown table; numeric columns are right-aligned.
+pub fn format_markdown(columns: &[String], rows: &[Vec<String>], numeric: &[bool]) -> String {
+    let cell = |s: &str| s.replace('|', "\\|").replace(['\n', '\r'], " ");
+    let header: Vec<String> = columns.iter().map(|c| cell(c)).collect();
+    let body: Vec<Vec<String>> = rows
+        .iter()
+        .map(|row| row.iter().map(|v| cell(v)).collect())
+        .collect();
+    let widths: Vec<usize> = header
+        .iter()
+        .enumerate()
+        .map(|(i, h)| {
+            body.iter()
+                .filter_map(|r| r.get(i))
+                .map(|v| v.chars().count())
+                .chain([h.chars().count(), 3])
+                .max()
+                .unwrap_or(3)
+        })
+        .collect();
+    let right = |i: usize| numeric.get(i).copied().unwrap_or(false);
+
+    let line = |cells: &[String]| {
+        let padded = cells
+            .iter()
+            .enumerate()
+            .map(|(i, c)| {
+                if right(i) {
+                    format!("{c:>width$}", width = widths[i])
+                } else {
+                    format!("{c:width$}", width = widths[i])
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" | ");
+        format!("| {padded} |")
+    };
+    let rule = widths
+        .iter()
+        .enumerate()
+        .map(|(i, w)| {
+            if right(i) {
+                format!("{}:", "-".repeat(w - 1))
+            } else {
+                "-".repeat(*w)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" | ");
+
+    let mut lines = vec![line(&header), format!("| {rule} |")];
+    lines.extend(body.iter().map(|r| line(r)));
     lines.join("\n")
 }
 
+/// Format results one field per line, column names right-aligned.
+pub fn format_line(columns: &[String], rows: &[Vec<String>]) -> String {
+    let width = columns.iter().map(|c| c.chars().count()).max().unwrap_or(0);
+    rows.iter()
+        .map(|row| {
+            columns
+                .iter()
+                .zip(row)
+                .map(|(c, v)| format!("{c:>width$} = {v}"))
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// Format results as one record per row, psql `\x` style: a
+/// `-[ RECORD n ]` rule, then a `column: value` line per field. Continuation
+/// lines of multi-line values are indented under the value.
+pub fn format_expanded(columns: &[String], rows: &[Vec<String>], options: &TableOptions) -> String {
+    let width = columns.iter().map(|c| c.chars().count()).max().unwrap_or(0);
+    let indent = " ".repeat(width + 2);
+    let mut out = String::new();
+    for (i, row) in rows.iter().enumerate() {
+        let _ = writeln!(out, "-[ RECORD {} ]{}", i + 1, "-".repeat(width.saturating_sub(4)));
+        for (column, value) in columns.iter().zip(row) {
+            let value = value.replace('\n', &format!("\n{indent}"));
+            let _ = writeln!(out, "{:label$} {value}", format!("{column}:"), label = width + 1);
+        }
+    }
+    if options.row_count {
+        let row_word = if rows.len() == 1 { "row" } else { "rows" };
+        let _ = write!(out, "({} {row_word})", rows.len());
+    }
+    out.trim_end().to_string()
+}
+
 /// Format results as tab-separated values.
 pub fn format_tsv(columns: &[String], rows: &[Vec<String>]) -> String {
     let mut lines = Vec::with_capacity(rows.len() + 1);
@@ -160,10 +839,116 @@ pub fn format_tsv(columns: &[String], rows: &[Vec<String>]) -> String {
     lines.join("\n")
 }
 
+/// Format results as CSV, quoting fields that contain `,`, `"`, or newlines.
+pub fn format_csv(columns: &[String], rows: &[Vec<String>]) -> String {
+    let line = |fields: &[String]| {
+        fields
+            .iter()
+            .map(|f| csv_field(f))
+            .collect::<Vec<_>>()
+            .join(",")
+    };
+    let mut lines = Vec::with_capacity(rows.len() + 1);
+    lines.push(line(columns));
+    for row in rows {
+        lines.push(line(row));
+    }
+    lines.join("\n")
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_number_format_grouping() {
+        let comma = NumberFormat::from_style("comma").unwrap();
+        assert_eq!(comma.format("1234567", false), "1,234,567");
+        assert_eq!(comma.format("-1234.5", false), "-1,234.5");
+        assert_eq!(comma.format("999", false), "999");
+        assert_eq!(comma.format("NULL", false), "NULL");
+        assert_eq!(comma.format("1e-05", false), "1e-05");
+
+        let dot = NumberFormat::for_locale("de_DE.UTF-8");
+        assert_eq!(dot.format("1234567.25", false), "1.234.567,25");
+        assert!(NumberFormat::from_style("plain").unwrap().is_plain());
+        assert!(NumberFormat::from_style("fancy").is_none());
+    }
+
+    #[test]
+    fn test_number_format_currency_columns() {
+        let format = NumberFormat {
+            currency: Some("$".to_string()),
+            ..NumberFormat::from_style("comma").unwrap()
+        };
+        let columns = vec!["model".to_string(), "tokens".to_string(), "cost_usd".to_string()];
+        let rows = vec![vec!["opus".to_string(), "1234567".to_string(), "1234.5".to_string()]];
+        let out = format.apply(&columns, &rows, &[false, true, true]);
+        assert_eq!(out[0], vec!["opus", "1,234,567", "$1,234.50"]);
+        assert_eq!(format.format("0.00042", true), "$0.0004");
+    }
+
+    #[test]
+    fn test_output_mode_parse() {
+        for name in OutputMode::NAMES {
+            assert_eq!(OutputMode::parse(name).unwrap().name(), *name);
+        }
+        assert_eq!(OutputMode::parse("MD"), Some(OutputMode::Markdown));
+        assert_eq!(OutputMode::parse("html"), None);
+    }
+
+    #[test]
+    fn test_format_json_markdown_line() {
+        let columns = vec!["tool".to_string(), "n".to_string()];
+        let rows = vec![
+            vec!["Bash".to_string(), "12".to_string()],
+            vec!["a|b".to_string(), "NULL".to_string()],
+        ];
+        let nulls = vec![vec![false, false], vec![false, true]];
+        let json = format_json(&columns, &rows, &nulls, &[false, true]);
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&json).unwrap(),
+            serde_json::json!([{"tool": "Bash", "n": 12}, {"tool": "a|b", "n": null}])
+        );
+        // Text that merely reads NULL stays a string
+        let text = vec![vec!["NULL".to_string()]];
+        let json = format_json(&columns[..1], &text, &[vec![false]], &[false]);
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&json).unwrap(),
+            serde_json::json!([{"tool": "NULL"}])
+        );
+        assert_eq!(
+            format_markdown(&columns, &rows, &[false, true]),
+            "| tool |    n |\n| ---- | ---: |\n| Bash |   12 |\n| a\\|b | NULL |"
+        );
+        assert_eq!(
+            format_line(&columns, &rows[..1]),
+            "tool = Bash\n   n = 12"
+        );
+    }
+
+    #[test]
+    fn test_format_expanded() {
+        let columns = vec!["uuid".to_string(), "message".to_string()];
+        let rows = vec![
+            vec!["a1".to_string(), "line one\nline two".to_string()],
+            vec!["b2".to_string(), "{}".to_string()],
+        ];
+        assert_eq!(
+            format_expanded(&columns, &rows, &TableOptions::default()),
+            "-[ RECORD 1 ]---\nuuid:    a1\nmessage: line one\n         line two\n\
+             -[ RECORD 2 ]---\nuuid:    b2\nmessage: {}\n(2 rows)"
+        );
+    }
+
     #[test]
     fn test_format_tsv() {
         let columns = vec!["a".to_string(), "b".to_string()];
@@ -174,6 +959,16 @@ mod tests {
         assert_eq!(format_tsv(&columns, &rows), "a\tb\n1\t2\n3\t4");
     }
 
+    #[test]
+    fn test_format_csv_quotes_special_fields() {
+        let columns = vec!["a".to_string(), "b".to_string()];
+        let rows = vec![vec!["x,y".to_string(), "say \"hi\"".to_string()]];
+        assert_eq!(
+            format_csv(&columns, &rows),
+            "a,b\n\"x,y\",\"say \"\"hi\"\"\""
+        );
+    }
+
     #[test]
     fn test_format_table_empty() {
         let columns = vec!["col1".to_string(), "col2".to_string()];
@@ -189,4 +984,136 @@ mod tests {
         assert!(result.contains("┌"));
         assert!(result.contains("(1 row)"));
     }
+
+    #[test]
+    fn test_truncate_cell() {
+        assert_eq!(truncate_cell("short", 10), "short");
+        assert_eq!(truncate_cell("{\"role\":\"user\"}", 8), "{\"role\"…");
+        assert_eq!(truncate_cell("héllo wörld", 5), "héll…");
+    }
+
+    #[test]
+    fn test_format_table_column_widths() {
+        let columns = vec!["id".to_string(), "message".to_string()];
+        let rows = vec![vec!["abcdefgh".to_string(), "x".repeat(50)]];
+        let options = TableOptions {
+            max_width: Some(4),
+            column_widths: vec![("MESSAGE".to_string(), 10)],
+            ..TableOptions::default()
+        };
+        let out = format_table_with(&columns, &rows, &options);
+        assert!(out.contains("│ abc… │ xxxxxxxxx… │"), "{out}");
+    }
+
+    #[test]
+    fn test_json_value() {
+        assert_eq!(json_value(&ValueRef::Null), serde_json::Value::Null);
+        assert_eq!(json_value(&ValueRef::BigInt(7)), serde_json::json!(7));
+        assert_eq!(json_value(&ValueRef::Text(b"hi")), serde_json::json!("hi"));
+        assert_eq!(
+            json_value(&ValueRef::HugeInt(i128::MAX)),
+            serde_json::json!(i128::MAX.to_string())
+        );
+    }
+
+    #[test]
+    fn test_display_in_zone() {
+        let utc = ValueRef::Timestamp(TimeUnit::Microsecond, 1_767_607_200_000_000);
+        assert_eq!(DisplayValueRef(&utc).to_string(), "2026-01-05 10:00:00.000");
+        let zone = "America/Los_Angeles".parse().unwrap();
+        assert_eq!(display_in_zone(&utc, zone), "2026-01-05 02:00:00.000");
+        assert_eq!(display_in_zone(&ValueRef::BigInt(7), zone), "7");
+    }
+
+    #[test]
+    fn test_display_rows_null_value() {
+        let columns = vec!["n".to_string(), "s".to_string()];
+        let rows = vec![
+            vec!["1200".to_string(), "NULL".to_string()],
+            vec!["3".to_string(), "NULL".to_string()],
+        ];
+        let nulls = vec![vec![false, true], vec![false, false]];
+        let options = TableOptions {
+            numbers: NumberFormat::from_style("comma").unwrap(),
+            null_value: Some("∅".to_string()),
+            ..TableOptions::default()
+        };
+        let shown = options.display_rows(&columns, &rows, &nulls, &[true, false]);
+        assert_eq!(shown[0], vec!["1,200", "∅"]);
+        // The text 'NULL' isn't a NULL
+        assert_eq!(shown[1], vec!["3", "NULL"]);
+        let plain = TableOptions::default();
+        let shown = plain.display_rows(&columns, &rows, &nulls, &[true, false]);
+        assert_eq!(shown[0][1], "NULL");
+    }
+
+    #[test]
+    fn test_format_table_color() {
+        let columns = vec!["n".to_string(), "is_error".to_string(), "s".to_string()];
+        let rows = vec![
+            vec!["5".to_string(), "false".to_string(), "NULL".to_string()],
+            vec!["100".to_string(), "true".to_string(), "boom".to_string()],
+        ];
+        let options = TableOptions {
+            color: true,
+            ..TableOptions::default()
+        };
+        let nulls = vec![vec![false, false, true], vec![false; 3]];
+        let out = format_table_typed(&columns, &rows, &nulls, &[true, false, false], &options);
+        assert!(out.contains(&format!("│ {HEADER}n  {RESET} │")), "{out}");
+        assert!(out.contains(&format!("│   5 │ false    │ {DIM}NULL{RESET} │")), "{out}");
+        let failed = format!("│ {ERROR}100{RESET} │ {ERROR}true    {RESET} │");
+        assert!(out.contains(&failed), "{out}");
+        let table: Vec<&str> = out.lines().filter(|l| !l.starts_with('(')).collect();
+        assert!(table.iter().all(|l| display_width(l) == display_width(table[0])));
+
+        let plain = TableOptions::default();
+        let plain = format_table_typed(&columns, &rows, &nulls, &[true, false, false], &plain);
+        assert!(plain.contains("│ 5   │ false    │ NULL │"), "{plain}");
+        assert!(!plain.contains('\x1b'));
+    }
+
+    #[test]
+    fn test_color_choice() {
+        assert_eq!(ColorChoice::parse("ALWAYS"), Some(ColorChoice::Always));
+        assert_eq!(ColorChoice::parse("sometimes"), None);
+        assert!(ColorChoice::Always.enabled(false));
+        assert!(!ColorChoice::Never.enabled(true));
+        assert!(!ColorChoice::Auto.enabled(false));
+    }
+
+    #[test]
+    fn test_format_table_without_row_count() {
+        let columns = vec!["a".to_string()];
+        let rows = vec![vec!["1".to_string()]];
+        let options = TableOptions {
+            row_count: false,
+            ..TableOptions::default()
+        };
+        let result = format_table_with(&columns, &rows, &options);
+        assert!(result.ends_with('┘'));
+        assert_eq!(format_table_with(&columns, &[], &options), "a");
+    }
+
+    #[test]
+    fn test_table_stream_matches_buffered_table() {
+        let columns = vec!["id".to_string(), "name".to_string()];
+        let rows = vec![
+            vec!["1".to_string(), "Alice".to_string()],
+            vec!["2".to_string(), "Bob".to_string()],
+        ];
+        let options = TableOptions {
+            row_count: false,
+            ..TableOptions::default()
+        };
+        let nulls = vec![vec![false, false]; 2];
+        let stream = TableStream::new(&columns, &rows, &nulls, &[true, false], &options);
+        let body: Vec<String> = rows.iter().map(|row| stream.row(row, &nulls[0])).collect();
+        let streamed = [stream.header(), body.join("\n"), stream.footer()];
+        assert_eq!(streamed.join("\n"), format_table_with(&columns, &rows, &options));
+
+        // Rows after the sample are cut to the sampled widths
+        let late = stream.row(&["3".to_string(), "Bartholomew".to_string()], &nulls[0]);
+        assert_eq!(late, "│ 3  │ Bart… │");
+    }
 }
@@ -0,0 +1,1298 @@
+//! Help registry behind `.help`: the command list, per-view topics with
+//! example queries, and general examples.
+
+use std::fmt::Write;
+
+/// A dot command (or input form) and what it does.
+#[derive(Debug, Clone, Copy)]
+pub struct Command {
+    pub usage: &'static str,
+    pub summary: &'static str,
+}
+
+/// A commented example query.
+#[derive(Debug, Clone, Copy)]
+pub struct Example {
+    pub title: &'static str,
+    pub sql: &'static str,
+}
+
+/// Help for one built-in view, shown by `.help <view>`.
+#[derive(Debug, Clone, Copy)]
+pub struct ViewTopic {
+    pub name: &'static str,
+    /// One line, used in the `.help` overview
+    pub summary: &'static str,
+    /// What a row is and which columns need explaining
+    pub details: &'static str,
+    pub examples: &'static [Example],
+}
+
+const fn command(usage: &'static str, summary: &'static str) -> Command {
+    Command { usage, summary }
+}
+
+const fn example(title: &'static str, sql: &'static str) -> Example {
+    Example { title, sql }
+}
+
+pub const COMMANDS: &[Command] = &[
+    command("<sql>;", "Run when the statement ends with ; outside quotes and parens"),
+    command(r"<sql> \g", "Run the statement typed so far, without a ;"),
+    command(".help, .h", "Show this help"),
+    command(".help <view>", "Show a view's columns, meaning, and example queries"),
+    command(".help examples", "Show example queries across views"),
+    command(".schema, .s", "Show schemas for all views"),
+    command(".schema <view>", "Show schema for a specific view"),
+    command(".tables", "List built-in views and tables/views you created"),
+    command(".mode [format]", "Show or set output: table, tsv, csv, json, markdown, line"),
+    command(".last [format]", "Show the previous result again, optionally in another format"),
+    command(".clip [format]", "Copy the previous result to the clipboard (tables as TSV)"),
+    command(".output [file]", "Send all results to a file (no file: back to stdout)"),
+    command(".once <file>", "Send only the next result to a file"),
+    command(".pager on|off", "Page results taller than the terminal through $PAGER"),
+    command(".timer on|off", "Show run time and row count after each query"),
+    command(".maxrows N|off", "Show at most N rows of each result (default 40)"),
+    command(".width [col] N|off", "Cut table values longer than N characters (default 60)"),
+    command(".x [on|off|auto]", "Show each row as column: value lines (auto: when too wide)"),
+    command(
+        ".set [name value]",
+        "List or change settings (confirmrows, maxrows, maxwidth, nullvalue, pager, timer, timezone)",
+    ),
+    command(".info", "Show file counts, message time span, and matched patterns"),
+    command(".grep <regex>", "Search prompts and assistant text (case-insensitive)"),
+    command(".cost [account]", "Show tokens and estimated cost by model (or account type)"),
+    command(".watch <secs> <query>", "Re-run a query every few seconds until Ctrl-C"),
+    command(".sessions [prefix]", "List sessions with project, first/last activity, message count"),
+    command(".check <query>", "Parse and plan a query without running it"),
+    command(".explain <query>", "Show a query's plan as an indented operator tree"),
+    command(
+        ".explain analyze <query>",
+        "Run a query and show its plan with per-operator time and rows",
+    ),
+    command(
+        ".open <prefix>|<uuid>|all",
+        "Narrow the views to matching sessions (all: every session), or open a message's source",
+    ),
+    command(
+        ".export <fmt> <file> [query]",
+        "Write the last (or given) query to csv, json, ndjson, or parquet",
+    ),
+    command(".store <name> [query]", "Save the last (or given) query's result as a temp table"),
+    command(".read <file>", "Run the statements and dot commands in a SQL script"),
+    command(".edit", "Edit the statement being typed (or the last query) in $EDITOR, then run it"),
+    command(".save <name> [query]", "Save a query (default: the last one run) for .run"),
+    command(".saved", "List saved queries"),
+    command(".run <name> [args]", "Run a saved query, replacing $1, $2, ... with args"),
+    command(".reload", "Rediscover session files and rebuild the views"),
+    command(
+        ".transcript <id> [n]",
+        "Show a message with n (default 10) parents and replies, or a session",
+    ),
+    command(".tree <session>", "Show a session's messages as a tree of replies and branches"),
+    command(".source <uuid>", "Open a message's source line in $EDITOR (like .open <uuid>)"),
+    command(".show <uuid> [depth]", "Inspect a message's JSON with parent/children"),
+    command(".paste <table>", "Load pasted TSV/CSV into a temp table (end with a lone .)"),
+    command(".quit, .q", "Exit"),
+];
+
+pub const VIEWS: &[ViewTopic] = &[
+    ViewTopic {
+        name: "messages",
+        summary: "All messages (user, assistant, system)",
+        details: "One row per JSONL line with a uuid, across main sessions and agent \
+                  files. `type` is user, assistant, or system; `message` holds the API \
+                  message as JSON. isAgent/agentId mark subagent files, `project` is the \
+                  project directory slug, and `rownum` is the line number within its file.",
+        examples: &[
+            example(
+                "Messages by type",
+                "SELECT type, count(*) as cnt FROM messages GROUP BY type ORDER BY cnt DESC;",
+            ),
+            example(
+                "Busiest projects",
+                "SELECT project, count(*) as cnt FROM messages GROUP BY project ORDER BY cnt DESC;",
+            ),
+            example(
+                "Agent vs main session breakdown",
+                "SELECT isAgent, count(*) FROM messages GROUP BY isAgent;",
+            ),
+        ],
+    },
+    ViewTopic {
+        name: "user_messages",
+        summary: "User messages with user-specific fields",
+        details: "Messages with type = 'user': typed prompts, tool results (content is an \
+                  array of tool_result blocks), and injected meta text (isMeta). Includes \
+                  todos, toolUseResult, and sourceToolAssistantUUID.",
+        examples: &[
+            example(
+                "Prompts vs tool results",
+                "SELECT json_type(message->'content') as content_type, count(*)\n\
+                 FROM user_messages GROUP BY ALL;",
+            ),
+            example(
+                "Compaction summaries",
+                "SELECT timestamp, sessionId FROM user_messages WHERE isCompactSummary;",
+            ),
+            example(
+                "Meta messages injected by Claude Code",
+                "SELECT timestamp, message->>'content' FROM user_messages WHERE isMeta LIMIT 10;",
+            ),
+        ],
+    },
+    ViewTopic {
+        name: "human_messages",
+        summary: "Human-typed messages (excludes tool results)",
+        details: "User messages whose content is plain text, outside agent files, and not \
+                  meta. `content` is the prompt text.",
+        examples: &[
+            example(
+                "Latest prompts",
+                "SELECT timestamp, content FROM human_messages ORDER BY timestamp DESC LIMIT 10;",
+            ),
+            example(
+                "Prompts per session",
+                "SELECT sessionId, count(*) as prompts FROM human_messages\n\
+                 GROUP BY sessionId ORDER BY prompts DESC;",
+            ),
+            example(
+                "Corrections",
+                "SELECT timestamp, content FROM human_messages\n\
+                 WHERE content ILIKE 'no,%' OR content ILIKE '%instead%';",
+            ),
+        ],
+    },
+    ViewTopic {
+        name: "assistant_messages",
+        summary: "Assistant messages with error, requestId, etc.",
+        details: "Messages with type = 'assistant'. message->'content' is an array of text, \
+                  thinking, and tool_use blocks; message->>'model' and \
+                  message->>'stop_reason' describe the response. isApiErrorMessage marks \
+                  API failures.",
+        examples: &[
+            example(
+                "Recent responses",
+                "SELECT timestamp, message->>'model', message->>'stop_reason'\n\
+                 FROM assistant_messages ORDER BY timestamp DESC LIMIT 10;",
+            ),
+            example(
+                "Stop reasons",
+                "SELECT message->>'stop_reason' as reason, count(*) as cnt\n\
+                 FROM assistant_messages GROUP BY reason ORDER BY cnt DESC;",
+            ),
+            example(
+                "API errors",
+                "SELECT timestamp, error FROM assistant_messages WHERE isApiErrorMessage;",
+            ),
+        ],
+    },
+    ViewTopic {
+        name: "system_messages",
+        summary: "System messages with hooks, retry info, etc.",
+        details: "Messages with type = 'system'. `subtype` says what happened \
+                  (compact_boundary, api_error, ...); `content` is the text; hook, retry, \
+                  and compaction fields are filled in per subtype. logicalParentUuid links \
+                  across compactions.",
+        examples: &[
+            example(
+                "Subtypes",
+                "SELECT subtype, count(*) FROM system_messages GROUP BY subtype;",
+            ),
+            example(
+                "Compactions",
+                "SELECT timestamp, sessionId, compactMetadata FROM system_messages\n\
+                 WHERE subtype = 'compact_boundary';",
+            ),
+            example(
+                "Retried API calls",
+                "SELECT timestamp, retryAttempt, maxRetries, retryInMs FROM system_messages\n\
+                 WHERE retryAttempt IS NOT NULL;",
+            ),
+        ],
+    },
+    ViewTopic {
+        name: "raw_messages",
+        summary: "Raw JSON for each message by uuid",
+        details: "The original JSONL object (`raw`) for every line with a uuid, including \
+                  fields the typed schema drops. Use it when a column is missing from \
+                  `messages`.",
+        examples: &[
+            example(
+                "Top-level keys in use",
+                "SELECT unnest(json_keys(raw)) as key, count(*) FROM raw_messages\n\
+                 GROUP BY key ORDER BY 2 DESC;",
+            ),
+            example(
+                "One message's JSON",
+                "SELECT raw FROM raw_messages WHERE uuid = '<uuid>';",
+            ),
+            example(
+                "Lines carrying an unusual field",
+                "SELECT uuid FROM raw_messages WHERE raw->'isApiErrorMessage' IS NOT NULL;",
+            ),
+        ],
+    },
+    ViewTopic {
+        name: "tool_uses",
+        summary: "All tool calls with unnested content blocks",
+        details: "One row per tool_use block in an assistant message. tool_name is the \
+                  tool, tool_id matches tool_results.tool_use_id, tool_input is the JSON \
+                  input, and block_index is the block's position within its message.",
+        examples: &[
+            example(
+                "Most used tools",
+                "SELECT tool_name, count(*) as uses FROM tool_uses GROUP BY tool_name ORDER BY uses DESC;",
+            ),
+            example(
+                "Tool errors by tool",
+                "SELECT u.tool_name, count(*) as errors\n\
+                 FROM tool_uses u JOIN tool_results r ON r.tool_use_id = u.tool_id\n\
+                 WHERE r.is_error GROUP BY u.tool_name ORDER BY errors DESC;",
+            ),
+            example(
+                "Parallel tool calls (several in one message)",
+                "SELECT uuid, count(*) as calls FROM tool_uses GROUP BY uuid HAVING calls > 1;",
+            ),
+            example(
+                "Subagent launches",
+                "SELECT timestamp, tool_input->>'description' FROM tool_uses WHERE tool_name = 'Task';",
+            ),
+        ],
+    },
+    ViewTopic {
+        name: "tool_results",
+        summary: "Tool results with duration and error status",
+        details: "One row per tool_result block in a user message. tool_use_id joins to \
+                  tool_uses.tool_id; is_error marks failures; result_content is the \
+                  output; duration_ms comes from toolUseResult when recorded.",
+        examples: &[
+            example(
+                "Failed tool calls",
+                "SELECT timestamp, result_content FROM tool_results WHERE is_error\n\
+                 ORDER BY timestamp DESC LIMIT 20;",
+            ),
+            example(
+                "Slowest tools",
+                "SELECT u.tool_name, avg(r.duration_ms) as avg_ms\n\
+                 FROM tool_results r JOIN tool_uses u ON u.tool_id = r.tool_use_id\n\
+                 GROUP BY ALL ORDER BY avg_ms DESC NULLS LAST;",
+            ),
+            example(
+                "Error rate per session",
+                "SELECT sessionId, avg(is_error::INT) as error_rate FROM tool_results\n\
+                 GROUP BY sessionId ORDER BY error_rate DESC;",
+            ),
+        ],
+    },
+    ViewTopic {
+        name: "token_usage",
+        summary: "Token counts per assistant message",
+        details: "One row per assistant message that reports usage. input_tokens excludes \
+                  cache reads (cache_read_tokens) and writes (cache_creation_tokens). \
+                  `model` and `stop_reason` are copied from the message. userType is the only \
+                  account-level field the logs record (normally `external`; there is no account \
+                  or API key ID), and requestId matches the provider's request logs. A response \
+                  with several content blocks is logged as several lines that share message_id, \
+                  requestId, and usage, so sum over response_usage instead.",
+        examples: &[
+            example(
+                "Tokens by model",
+                "SELECT model, sum(input_tokens) as input, sum(output_tokens) as output\n\
+                 FROM token_usage GROUP BY model ORDER BY output DESC;",
+            ),
+            example(
+                "Cache hit ratio per session",
+                "SELECT sessionId,\n\
+                 \x20      sum(cache_read_tokens) / nullif(sum(cache_read_tokens + input_tokens), 0) as hit\n\
+                 FROM token_usage GROUP BY sessionId;",
+            ),
+            example(
+                "Token usage split by account type and model",
+                "SELECT userType, model, sum(output_tokens) as output\n\
+                 FROM token_usage GROUP BY ALL ORDER BY output DESC;",
+            ),
+        ],
+    },
+    ViewTopic {
+        name: "response_usage",
+        summary: "Token counts per API response",
+        details: "token_usage with one row per (message_id, requestId), keeping the response's \
+                  last line. Use it for any sum over tokens: costs, sessions, agents, turns, and \
+                  context_growth are built on it.",
+        examples: &[
+            example(
+                "Tokens per session",
+                "SELECT sessionId, sum(input_tokens) as input, sum(output_tokens) as output\n\
+                 FROM response_usage GROUP BY sessionId ORDER BY output DESC;",
+            ),
+            example(
+                "Content-block lines per response",
+                "SELECT r.requestId, count(*) as lines FROM response_usage r\n\
+                 JOIN token_usage t USING (message_id, requestId)\n\
+                 GROUP BY ALL ORDER BY lines DESC;",
+            ),
+        ],
+    },
+    ViewTopic {
+        name: "bash_commands",
+        summary: "Bash tool calls with extracted command",
+        details: "Bash rows of tool_uses with the input unpacked: `command`, `description`, \
+                  `timeout` (ms), and run_in_background.",
+        examples: &[
+            example(
+                "Most run programs",
+                "SELECT split_part(command, ' ', 1) as program, count(*) as runs\n\
+                 FROM bash_commands GROUP BY program ORDER BY runs DESC;",
+            ),
+            example(
+                "Commands that failed",
+                "SELECT b.command, r.result_content\n\
+                 FROM bash_commands b JOIN tool_results r ON r.tool_use_id = b.tool_id\n\
+                 WHERE r.is_error;",
+            ),
+            example(
+                "Background jobs",
+                "SELECT timestamp, command FROM bash_commands WHERE run_in_background;",
+            ),
+        ],
+    },
+    ViewTopic {
+        name: "file_operations",
+        summary: "Read/Write/Edit/Glob/Grep with file paths",
+        details: "File tool calls from tool_uses. file_path is the file (or search root for \
+                  Glob/Grep); `pattern` is the Glob/Grep pattern.",
+        examples: &[
+            example(
+                "Most edited files",
+                "SELECT file_path, count(*) as edits FROM file_operations\n\
+                 WHERE tool_name IN ('Edit', 'Write') GROUP BY file_path ORDER BY edits DESC;",
+            ),
+            example(
+                "Files read but never changed",
+                "SELECT file_path FROM file_operations GROUP BY file_path\n\
+                 HAVING count(*) FILTER (WHERE tool_name IN ('Edit', 'Write')) = 0;",
+            ),
+            example(
+                "Common search patterns",
+                "SELECT pattern, count(*) FROM file_operations WHERE pattern IS NOT NULL\n\
+                 GROUP BY pattern ORDER BY 2 DESC;",
+            ),
+        ],
+    },
+    ViewTopic {
+        name: "thinking_blocks",
+        summary: "Extended-thinking blocks with text and estimated tokens",
+        details: "One row per `thinking` content block in assistant_messages. estimated_tokens \
+                  is thinking_chars / 4, since usage doesn't report thinking tokens separately; \
+                  has_signature is true when the block carries a signature. Join to token_usage \
+                  or tool_uses on uuid.",
+        examples: &[
+            example(
+                "Thinking volume per session",
+                "SELECT sessionId, count(*) as blocks, sum(estimated_tokens) as est_tokens\n\
+                 FROM thinking_blocks GROUP BY sessionId ORDER BY est_tokens DESC;",
+            ),
+            example(
+                "Longest thoughts",
+                "SELECT timestamp, thinking_chars, left(thinking, 120) as preview\n\
+                 FROM thinking_blocks ORDER BY thinking_chars DESC LIMIT 10;",
+            ),
+            example(
+                "What the model thought before each tool call",
+                "SELECT t.tool_name, left(k.thinking, 100) as thinking\n\
+                 FROM thinking_blocks k JOIN tool_uses t USING (uuid) ORDER BY k.timestamp DESC;",
+            ),
+        ],
+    },
+    ViewTopic {
+        name: "text_blocks",
+        summary: "Assistant prose, one row per text block",
+        details: "One row per `text` content block in assistant_messages (tool calls and \
+                  thinking live in tool_uses and thinking_blocks). block_index counts text \
+                  blocks within the message; text_chars is the block's length.",
+        examples: &[
+            example(
+                "Search what Claude said",
+                "SELECT timestamp, left(text, 120) as text FROM text_blocks\n\
+                 WHERE text ILIKE '%migration%' ORDER BY timestamp DESC;",
+            ),
+            example(
+                "Wordiest sessions",
+                "SELECT sessionId, sum(text_chars) as chars, count(*) as blocks\n\
+                 FROM text_blocks GROUP BY sessionId ORDER BY chars DESC;",
+            ),
+            example(
+                "Final reply of each session",
+                "SELECT sessionId, arg_max(left(text, 200), timestamp) as last_reply\n\
+                 FROM text_blocks WHERE NOT isAgent GROUP BY sessionId;",
+            ),
+        ],
+    },
+    ViewTopic {
+        name: "attachments",
+        summary: "Images and documents sent to the model, with estimated size",
+        details: "One row per `image` or `document` content block in user messages, including \
+                  ones nested in tool results (tool_use_id is set for those, e.g. Read on a \
+                  PNG). source_type is base64, text, or url; estimated_bytes is the decoded size \
+                  for base64 and text sources.",
+        examples: &[
+            example(
+                "Bytes sent by media type",
+                "SELECT media_type, count(*) as n, sum(estimated_bytes) as bytes\n\
+                 FROM attachments GROUP BY media_type ORDER BY bytes DESC;",
+            ),
+            example(
+                "Largest attachments",
+                "SELECT timestamp, sessionId, media_type, estimated_bytes\n\
+                 FROM attachments ORDER BY estimated_bytes DESC NULLS LAST LIMIT 10;",
+            ),
+            example(
+                "Pasted vs tool-read images",
+                "SELECT tool_use_id IS NULL as pasted, count(*)\n\
+                 FROM attachments WHERE attachment_type = 'image' GROUP BY ALL;",
+            ),
+        ],
+    },
+    ViewTopic {
+        name: "todo_events",
+        summary: "Todo list snapshots, one row per item per message",
+        details: "Expands the todos column of user_messages: every message carrying a todo list \
+                  yields one row per item, with its position, status (pending, in_progress, \
+                  completed), and the list's size. Consecutive snapshots show how each item \
+                  moved through its statuses.",
+        examples: &[
+            example(
+                "How long each todo stayed in progress",
+                "SELECT sessionId, content,\n\
+                        min(timestamp) FILTER (WHERE status = 'completed')\n\
+                          - min(timestamp) FILTER (WHERE status = 'in_progress') as took\n\
+                 FROM todo_events GROUP BY sessionId, content ORDER BY took DESC NULLS LAST;",
+            ),
+            example(
+                "Todos never completed",
+                "SELECT sessionId, content FROM todo_events GROUP BY sessionId, content\n\
+                 HAVING count(*) FILTER (WHERE status = 'completed') = 0;",
+            ),
+            example(
+                "Latest list per session",
+                "SELECT sessionId, position, status, content FROM todo_events\n\
+                 QUALIFY timestamp = max(timestamp) OVER (PARTITION BY sessionId)\n\
+                 ORDER BY sessionId, position;",
+            ),
+        ],
+    },
+    ViewTopic {
+        name: "hooks",
+        summary: "Hooks run and hook errors, one row each",
+        details: "Unnests hookInfos (is_error false) and hookErrors (is_error true) from \
+                  system_messages. event comes from the message subtype (stop_hook_summary gives \
+                  stop); command and exit_code are filled when the entry records them, error \
+                  holds the error text.",
+        examples: &[
+            example(
+                "Hook failure rate by event",
+                "SELECT event, count(*) FILTER (WHERE is_error) as errors,\n\
+                        count(*) FILTER (WHERE NOT is_error) as runs\n\
+                 FROM hooks GROUP BY event ORDER BY errors DESC;",
+            ),
+            example(
+                "Recent hook errors",
+                "SELECT timestamp, sessionId, event, left(error, 120) as error\n\
+                 FROM hooks WHERE is_error ORDER BY timestamp DESC LIMIT 20;",
+            ),
+            example(
+                "Hooks that blocked Claude from continuing",
+                "SELECT timestamp, event, command FROM hooks WHERE preventedContinuation;",
+            ),
+        ],
+    },
+    ViewTopic {
+        name: "errors",
+        summary: "API, system, and tool errors in one place",
+        details: "Unions assistant messages flagged isApiErrorMessage (error_kind api), system \
+                  messages with an error payload (system), and tool_results with is_error \
+                  (tool). error_message is the readable text; detail is the API error code, the \
+                  system subtype, or the tool_use_id to join tool_uses on.",
+        examples: &[
+            example(
+                "What went wrong today",
+                "SELECT error_kind, count(*) FROM errors\n\
+                 WHERE timestamp > current_date GROUP BY error_kind;",
+            ),
+            example(
+                "Most common tool errors by tool",
+                "SELECT u.tool_name, count(*) as errors FROM errors e\n\
+                 JOIN tool_uses u ON u.tool_id = e.detail\n\
+                 WHERE e.error_kind = 'tool' GROUP BY u.tool_name ORDER BY errors DESC;",
+            ),
+            example(
+                "Error timeline of a session",
+                "SELECT timestamp, error_kind, left(error_message, 100) as error_message\n\
+                 FROM errors WHERE starts_with(sessionId::VARCHAR, 'abc123') ORDER BY timestamp;",
+            ),
+        ],
+    },
+    ViewTopic {
+        name: "sessions",
+        summary: "One row per session: span, message counts, tools, tokens, agents",
+        details: "Aggregates messages, tool_uses, and response_usage by sessionId, counting agent \
+                  (subagent) messages toward their parent session. tools is the sorted list of \
+                  distinct tool names; total_tokens adds input, output, and both cache counts.",
+        examples: &[
+            example(
+                "Longest sessions",
+                "SELECT sessionId, project, duration, messages FROM sessions\n\
+                 ORDER BY duration DESC LIMIT 10;",
+            ),
+            example(
+                "Token-heaviest sessions this week",
+                "SELECT sessionId, total_tokens, tool_calls FROM sessions\n\
+                 WHERE last > now() - INTERVAL 7 DAY ORDER BY total_tokens DESC;",
+            ),
+            example(
+                "Sessions that used a tool",
+                "SELECT sessionId, first FROM sessions WHERE list_contains(tools, 'WebFetch');",
+            ),
+        ],
+    },
+    ViewTopic {
+        name: "threads",
+        summary: "Each message's thread root, depth, and uuid path (recursive parentUuid walk)",
+        details: "Walks parentUuid chains within each session. A message with no parent, or \
+                  whose parent isn't loaded, starts a thread at depth 0. path lists uuids from \
+                  the root down to the message itself, so a message with several children marks \
+                  a branch point.",
+        examples: &[
+            example(
+                "Deepest threads",
+                "SELECT sessionId, root_uuid, max(depth) as depth FROM threads\n\
+                 GROUP BY ALL ORDER BY depth DESC LIMIT 10;",
+            ),
+            example(
+                "Branch points (messages with more than one reply)",
+                "SELECT sessionId, parentUuid, count(*) as replies FROM threads\n\
+                 WHERE parentUuid IS NOT NULL GROUP BY ALL HAVING count(*) > 1;",
+            ),
+            example(
+                "Everything under a message",
+                "SELECT uuid, depth FROM threads\n\
+                 WHERE list_contains(path, '<uuid>'::UUID) ORDER BY depth;",
+            ),
+        ],
+    },
+    ViewTopic {
+        name: "agents",
+        summary: "One row per subagent run: spawning Task call, span, messages, tokens",
+        details: "Groups agent-*.jsonl messages by file. tool_use_id is the sourceToolUseID \
+                  recorded on the agent's messages; parent_uuid, subagent_type, and description \
+                  come from the matching Task tool_use in the parent conversation and are NULL \
+                  when that call isn't loaded.",
+        examples: &[
+            example(
+                "Most expensive subagent runs",
+                "SELECT agentId, subagent_type, description, total_tokens FROM agents\n\
+                 ORDER BY total_tokens DESC LIMIT 10;",
+            ),
+            example(
+                "Subagent share of each session's tokens",
+                "SELECT s.sessionId,\n\
+                   sum(a.total_tokens) / any_value(s.total_tokens) as agent_share\n\
+                 FROM sessions s JOIN agents a USING (sessionId) GROUP BY ALL;",
+            ),
+            example(
+                "Agents whose spawning call isn't loaded",
+                "SELECT agentId, sessionId, first FROM agents WHERE parent_uuid IS NULL;",
+            ),
+        ],
+    },
+    ViewTopic {
+        name: "edits",
+        summary: "Edit/MultiEdit replacements: file, old/new strings, char delta",
+        details: "Edit calls give one row with edit_index 0; MultiEdit calls give one row per \
+                  entry in their edits list, numbered from 0. char_delta is length(new_string) - \
+                  length(old_string) for a single replacement, so replace_all edits may change \
+                  the file by a multiple of it.",
+        examples: &[
+            example(
+                "Most edited files",
+                "SELECT file_path, count(*) as edits, sum(char_delta) as net_chars FROM edits\n\
+                 GROUP BY file_path ORDER BY edits DESC LIMIT 10;",
+            ),
+            example(
+                "Largest deletions",
+                "SELECT file_path, char_delta, left(old_string, 80) FROM edits\n\
+                 ORDER BY char_delta LIMIT 10;",
+            ),
+            example(
+                "Edits that touched a function",
+                "SELECT timestamp, file_path FROM edits\n\
+                 WHERE old_string LIKE '%fn build_create_views_sql%';",
+            ),
+        ],
+    },
+    ViewTopic {
+        name: "web_requests",
+        summary: "WebFetch/WebSearch calls: URL or query, result size, duration",
+        details: "url and domain are set for WebFetch, query for WebSearch; prompt is the \
+                  WebFetch instruction. is_error, result_chars, and duration_ms come from the \
+                  matching tool_results row and are NULL when the call has no loaded result.",
+        examples: &[
+            example(
+                "Most fetched domains",
+                "SELECT domain, count(*) as fetches, sum(result_chars) as chars FROM web_requests\n\
+                 WHERE tool_name = 'WebFetch' GROUP BY domain ORDER BY fetches DESC;",
+            ),
+            example(
+                "Recent searches",
+                "SELECT timestamp, query FROM web_requests\n\
+                 WHERE tool_name = 'WebSearch' ORDER BY timestamp DESC LIMIT 20;",
+            ),
+            example(
+                "Failed or slow fetches",
+                "SELECT url, is_error, duration_ms FROM web_requests\n\
+                 WHERE is_error OR duration_ms > 10000;",
+            ),
+        ],
+    },
+    ViewTopic {
+        name: "task_launches",
+        summary: "Task calls: description, prompt, subagent_type, model, launched agent",
+        details: "One row per Task tool_use. agentId is the agent that made the call (NULL from \
+                  the main conversation); launched_agentId and the agent_* columns describe the \
+                  run it started, matched through the agents view's tool_use_id, and are NULL \
+                  when that agent file isn't loaded.",
+        examples: &[
+            example(
+                "Subagent types by launches and tokens",
+                "SELECT subagent_type, count(*) as launches, sum(agent_tokens) as tokens\n\
+                 FROM task_launches GROUP BY subagent_type ORDER BY launches DESC;",
+            ),
+            example(
+                "Launches with a model override",
+                "SELECT timestamp, subagent_type, model, description FROM task_launches\n\
+                 WHERE model IS NOT NULL;",
+            ),
+            example(
+                "Launches with no agent file",
+                "SELECT sessionId, timestamp, description FROM task_launches\n\
+                 WHERE launched_agentId IS NULL;",
+            ),
+        ],
+    },
+    ViewTopic {
+        name: "mcp_tool_uses",
+        summary: "MCP tool calls with server and tool split out of the name",
+        details: "Only tool_uses named mcp__<server>__<tool>. The name is split at the first \
+                  double underscore after the prefix, so server names may contain single \
+                  underscores.",
+        examples: &[
+            example(
+                "Calls per MCP server",
+                "SELECT server, count(*) as calls, count(DISTINCT tool) as tools\n\
+                 FROM mcp_tool_uses GROUP BY server ORDER BY calls DESC;",
+            ),
+            example(
+                "Most used tools on a server",
+                "SELECT tool, count(*) as calls FROM mcp_tool_uses\n\
+                 WHERE server = 'github' GROUP BY tool ORDER BY calls DESC;",
+            ),
+            example(
+                "MCP failure rate by server",
+                "SELECT u.server, avg(r.is_error::INT) as error_rate FROM mcp_tool_uses u\n\
+                 JOIN tool_results r ON r.tool_use_id = u.tool_id GROUP BY u.server;",
+            ),
+        ],
+    },
+    ViewTopic {
+        name: "tool_calls",
+        summary: "Tool uses joined to results: latency, is_error, result length",
+        details: "One row per tool_use; the result columns are NULL for calls with no loaded \
+                  result (interrupted, or still running). latency is measured between the two \
+                  messages' timestamps, so it includes permission prompts; duration_ms is the \
+                  tool's own reported run time, when recorded.",
+        examples: &[
+            example(
+                "Slowest tools on average",
+                "SELECT tool_name, count(*) as calls, avg(latency) as avg_latency FROM tool_calls\n\
+                 GROUP BY tool_name ORDER BY avg_latency DESC;",
+            ),
+            example(
+                "Error rate by tool",
+                "SELECT tool_name, avg(is_error::INT) as error_rate FROM tool_calls\n\
+                 WHERE result_uuid IS NOT NULL GROUP BY tool_name ORDER BY error_rate DESC;",
+            ),
+            example(
+                "Calls that never got a result",
+                "SELECT sessionId, timestamp, tool_name FROM tool_calls WHERE result_uuid IS NULL;",
+            ),
+        ],
+    },
+    ViewTopic {
+        name: "compactions",
+        summary: "Context compactions: trigger, pre/post tokens, and the summary text",
+        details: "One row per compact_boundary system message, joined to the compact-summary \
+                  user message that follows it (its parentUuid is the boundary). A summary \
+                  without a loaded boundary still gets a row, with the metadata columns NULL. \
+                  post_tokens is NULL when Claude Code didn't record it.",
+        examples: &[
+            example(
+                "Compactions per session",
+                "SELECT sessionId, count(*) as compactions, avg(pre_tokens) as avg_pre_tokens\n\
+                 FROM compactions GROUP BY sessionId ORDER BY compactions DESC;",
+            ),
+            example(
+                "Manual vs automatic compactions by week",
+                "SELECT date_trunc('week', timestamp) as week, trigger, count(*) FROM compactions\n\
+                 GROUP BY ALL ORDER BY week;",
+            ),
+            example(
+                "How much each compaction saved",
+                "SELECT timestamp, pre_tokens, post_tokens, pre_tokens - post_tokens as saved\n\
+                 FROM compactions WHERE post_tokens IS NOT NULL;",
+            ),
+        ],
+    },
+    ViewTopic {
+        name: "interruptions",
+        summary: "User interruptions: kind, session, time, and the tool stopped",
+        details: "kind is 'tool' when a running tool call was interrupted, 'rejected' when the \
+                  user declined a tool call, and 'prompt' when a response was stopped outside a \
+                  tool call (tool_name is then NULL). uuid and timestamp are those of the user \
+                  message carrying the notice; text is the notice itself.",
+        examples: &[
+            example(
+                "Tools most often interrupted or declined",
+                "SELECT tool_name, kind, count(*) FROM interruptions\n\
+                 WHERE tool_name IS NOT NULL GROUP BY ALL ORDER BY count(*) DESC;",
+            ),
+            example(
+                "Sessions with the most interruptions",
+                "SELECT sessionId, count(*) as interruptions FROM interruptions\n\
+                 GROUP BY sessionId ORDER BY interruptions DESC LIMIT 10;",
+            ),
+            example(
+                "What Claude was doing when stopped",
+                "SELECT i.timestamp, c.tool_name, c.tool_input FROM interruptions i\n\
+                 JOIN tool_calls c USING (tool_id) ORDER BY i.timestamp DESC;",
+            ),
+        ],
+    },
+    ViewTopic {
+        name: "costs",
+        summary: "Estimated USD per API response, by token kind",
+        details: "Prices come from the model_pricing table (prefix, input, output, cache_write, \
+                  cache_read in USD per million tokens), created at startup from published list \
+                  prices plus any [pricing] entries in the config file. Each response uses the \
+                  longest prefix its model name starts with; models with no match have NULL \
+                  costs. Estimates ignore batch and long-context discounts. userType and \
+                  requestId come from response_usage for splitting costs by account type or \
+                  matching provider billing exports.",
+        examples: &[
+            example(
+                "Cost per session",
+                "SELECT sessionId, round(sum(cost_usd), 2) as usd FROM costs\n\
+                 GROUP BY sessionId ORDER BY usd DESC LIMIT 10;",
+            ),
+            example(
+                "Where the money goes",
+                "SELECT sum(input_cost) as input, sum(output_cost) as output,\n\
+                   sum(cache_write_cost) as cache_write, sum(cache_read_cost) as cache_read\n\
+                 FROM costs;",
+            ),
+            example(
+                "Models without a price",
+                "SELECT DISTINCT model FROM costs WHERE price_prefix IS NULL;",
+            ),
+            example(
+                "Cost per account type and month",
+                "SELECT userType, date_trunc('month', timestamp) as month,\n\
+                   round(sum(cost_usd), 2) as usd\n\
+                 FROM costs GROUP BY ALL ORDER BY month, userType;",
+            ),
+        ],
+    },
+    ViewTopic {
+        name: "daily_usage",
+        summary: "Per day and project: sessions, messages, tool calls, tokens, cost",
+        details: "Days are UTC calendar dates of the message timestamps. Subagent activity \
+                  counts toward its project. cost_usd sums the costs view, so models without a \
+                  price add tokens but no cost.",
+        examples: &[
+            example(
+                "Last 30 days across projects",
+                "SELECT day, sum(output_tokens) as output, round(sum(cost_usd), 2) as usd\n\
+                 FROM daily_usage WHERE day > current_date - 30 GROUP BY day ORDER BY day;",
+            ),
+            example(
+                "Busiest days for one project",
+                "SELECT day, messages, tool_calls FROM daily_usage\n\
+                 WHERE project LIKE '%cc-query%' ORDER BY messages DESC LIMIT 10;",
+            ),
+            example(
+                "Monthly spend by project",
+                "SELECT date_trunc('month', day) as month, project,\n\
+                   round(sum(cost_usd), 2) as usd\n\
+                 FROM daily_usage GROUP BY ALL ORDER BY month, usd DESC;",
+            ),
+        ],
+    },
+    ViewTopic {
+        name: "model_usage",
+        summary: "Model per assistant message, with switch and off-primary flags",
+        details: "A conversation is one session file, so the main thread and each subagent are \
+                  compared only with themselves. previous_model is the model of the \
+                  conversation's prior assistant message; primary_model is the one it used most. \
+                  Synthetic messages (model '<synthetic>', e.g. local API errors) are left out.",
+        examples: &[
+            example(
+                "Model switches, newest first",
+                "SELECT timestamp, sessionId, previous_model, model FROM model_usage\n\
+                 WHERE model_changed ORDER BY timestamp DESC;",
+            ),
+            example(
+                "Share of replies from a non-primary model, per session",
+                "SELECT sessionId, avg(off_primary::INT) as off_primary_share FROM model_usage\n\
+                 GROUP BY sessionId HAVING off_primary_share > 0 ORDER BY off_primary_share DESC;",
+            ),
+            example(
+                "Replies per model by day",
+                "SELECT timestamp::DATE as day, model, count(*) FROM model_usage\n\
+                 GROUP BY ALL ORDER BY day;",
+            ),
+        ],
+    },
+    ViewTopic {
+        name: "slash_commands",
+        summary: "Slash-command invocations with command name and arguments",
+        details: "Covers prompts recorded in Claude Code's <command-name>/<command-args> wrapper \
+                  and prompts typed as /name args. command is the name without its leading slash \
+                  (plugin and MCP commands keep their namespace, e.g. plugin:cmd); args is empty \
+                  when none were given. Subagent messages are excluded.",
+        examples: &[
+            example(
+                "Most used commands",
+                "SELECT command, count(*) as uses FROM slash_commands\n\
+                 GROUP BY command ORDER BY uses DESC;",
+            ),
+            example(
+                "Recent invocations with arguments",
+                "SELECT timestamp, command, args FROM slash_commands\n\
+                 WHERE args <> '' ORDER BY timestamp DESC LIMIT 20;",
+            ),
+            example(
+                "Commands per project",
+                "SELECT project, command, count(*) FROM slash_commands\n\
+                 GROUP BY ALL ORDER BY project;",
+            ),
+        ],
+    },
+    ViewTopic {
+        name: "git_activity",
+        summary: "Branch changes and git commands as a per-session timeline",
+        details: "kind 'branch' rows mark where a conversation's gitBranch first appears or \
+                  changes, with previous_branch NULL at the start. kind 'command' rows are Bash \
+                  calls running git, alone or chained with ;, &&, ||, or |; subcommand is the \
+                  first git invocation's (commit, checkout, ...), gitBranch is the branch \
+                  recorded on the calling message, and is_error comes from its tool result.",
+        examples: &[
+            example(
+                "A session's version-control timeline",
+                "SELECT timestamp, kind, gitBranch, subcommand, command FROM git_activity\n\
+                 WHERE sessionId::VARCHAR LIKE 'abc%' ORDER BY timestamp;",
+            ),
+            example(
+                "Most common git subcommands",
+                "SELECT subcommand, count(*) as runs, count(*) FILTER (WHERE is_error) as errors\n\
+                 FROM git_activity WHERE kind = 'command' GROUP BY subcommand ORDER BY runs DESC;",
+            ),
+            example(
+                "Commits per branch",
+                "SELECT gitBranch, count(*) as commits FROM git_activity\n\
+                 WHERE subcommand = 'commit' GROUP BY gitBranch ORDER BY commits DESC;",
+            ),
+        ],
+    },
+    ViewTopic {
+        name: "file_history",
+        summary: "Ordered Read/Edit/Write events per file with op_seq numbering",
+        details: "Built from file_operations, keeping Read, Edit, and Write calls with a \
+                  file_path. op_seq numbers a file's events from 1 across everything loaded; \
+                  session_op_seq restarts in each session. previous_operation and since_previous \
+                  look back one event on the same file.",
+        examples: &[
+            example(
+                "What happened to a file",
+                "SELECT op_seq, timestamp, operation, sessionId FROM file_history\n\
+                 WHERE file_path LIKE '%/src/repl.rs' ORDER BY op_seq;",
+            ),
+            example(
+                "Files edited before being read in a session",
+                "SELECT DISTINCT file_path, sessionId FROM file_history\n\
+                 WHERE session_op_seq = 1 AND operation = 'Edit';",
+            ),
+            example(
+                "Most re-read files",
+                "SELECT file_path, count(*) as reads FROM file_history\n\
+                 WHERE operation = 'Read' AND previous_operation = 'Read'\n\
+                 GROUP BY file_path ORDER BY reads DESC;",
+            ),
+        ],
+    },
+    ViewTopic {
+        name: "notebook_operations",
+        summary: "NotebookEdit calls: notebook path, cell id, edit mode, cell type",
+        details: "The notebook counterpart of file_operations. edit_mode is replace, insert, or \
+                  delete (replace when an edit omits it, NULL for reads); cell_type is code or markdown when \
+                  given. Read calls on .ipynb files still appear in file_operations.",
+        examples: &[
+            example(
+                "Most edited notebooks",
+                "SELECT notebook_path, count(*) as edits FROM notebook_operations\n\
+                 WHERE tool_name = 'NotebookEdit' GROUP BY notebook_path ORDER BY edits DESC;",
+            ),
+            example(
+                "Edit modes and cell types",
+                "SELECT edit_mode, cell_type, count(*) FROM notebook_operations\n\
+                 WHERE tool_name = 'NotebookEdit' GROUP BY ALL;",
+            ),
+            example(
+                "Cells inserted in a session",
+                "SELECT timestamp, notebook_path, left(new_source, 80) FROM notebook_operations\n\
+                 WHERE edit_mode = 'insert' AND sessionId::VARCHAR LIKE 'abc%';",
+            ),
+        ],
+    },
+    ViewTopic {
+        name: "searches",
+        summary: "Grep/Glob calls: pattern, path, match count, duration",
+        details: "match_count is the number of matches for Grep in count mode, matching lines in \
+                  content mode, and matching files otherwise (always files for Glob). \
+                  duration_ms is the tool's reported run time; latency spans call to result \
+                  message, including any wait. Result columns are NULL when the result isn't \
+                  loaded.",
+        examples: &[
+            example(
+                "Time spent searching per session",
+                "SELECT sessionId, count(*) as searches, sum(duration_ms) / 1000 as seconds\n\
+                 FROM searches GROUP BY sessionId ORDER BY seconds DESC;",
+            ),
+            example(
+                "Searches that found nothing",
+                "SELECT tool_name, pattern, path FROM searches WHERE match_count = 0;",
+            ),
+            example(
+                "Most repeated patterns",
+                "SELECT pattern, count(*) as times FROM searches\n\
+                 GROUP BY pattern HAVING count(*) > 1 ORDER BY times DESC;",
+            ),
+        ],
+    },
+    ViewTopic {
+        name: "retries",
+        summary: "API retries: attempt, backoff, request ID, status, and model",
+        details: "One row per system message carrying retry fields. requestId, status, and \
+                  error_type come from the error payload. model is taken from the next assistant \
+                  message in the same conversation, which is the reply the retries eventually \
+                  produced (or '<synthetic>' when they gave up).",
+        examples: &[
+            example(
+                "Retries per day and model",
+                "SELECT timestamp::DATE as day, model, count(*) as retries FROM retries\n\
+                 GROUP BY ALL ORDER BY day;",
+            ),
+            example(
+                "Failures by status code",
+                "SELECT status, error_type, count(*) FROM retries\n\
+                 GROUP BY ALL ORDER BY count(*) DESC;",
+            ),
+            example(
+                "Time lost to backoff per session",
+                "SELECT sessionId, sum(retry_in_ms) / 1000 as backoff_seconds FROM retries\n\
+                 GROUP BY sessionId ORDER BY backoff_seconds DESC;",
+            ),
+        ],
+    },
+    ViewTopic {
+        name: "sidechains",
+        summary: "Sidechain threads: origin message, prompt, length, tokens",
+        details: "A sidechain thread starts at an isSidechain message whose parent isn't a \
+                  loaded sidechain message, and includes every sidechain reply below it along \
+                  parentUuid. start_uuid is that first message, origin_uuid its parent in the \
+                  main conversation (NULL for subagent files, whose sidechains start fresh), and \
+                  prompt its text when it is plain text.",
+        examples: &[
+            example(
+                "Costliest sidechains",
+                "SELECT sessionId, agentId, messages, total_tokens, left(prompt, 60)\n\
+                 FROM sidechains ORDER BY total_tokens DESC LIMIT 10;",
+            ),
+            example(
+                "Sidechains per session",
+                "SELECT sessionId, count(*) as sidechains, sum(total_tokens) as tokens\n\
+                 FROM sidechains GROUP BY sessionId ORDER BY sidechains DESC;",
+            ),
+            example(
+                "Sidechain token share",
+                "SELECT sum(total_tokens) / (SELECT sum(total_tokens) FROM sessions) as share\n\
+                 FROM sidechains;",
+            ),
+        ],
+    },
+    ViewTopic {
+        name: "turns",
+        summary: "One row per human prompt: duration, replies, tool calls, tokens",
+        details: "A turn runs from a human message (see human_messages) until the next one in \
+                  the same session; messages before the first prompt belong to no turn. Subagent \
+                  messages count toward the turn whose time span contains them. ended is the \
+                  last message in the turn, so duration covers all the work the prompt caused.",
+        examples: &[
+            example(
+                "Prompts that caused the most work",
+                "SELECT sessionId, turn_index, tool_calls, total_tokens, left(prompt, 60)\n\
+                 FROM turns ORDER BY total_tokens DESC LIMIT 10;",
+            ),
+            example(
+                "Average turn in each session",
+                "SELECT sessionId, count(*) as turns, avg(duration) as avg_duration,\n\
+                   avg(tool_calls) as avg_tool_calls FROM turns GROUP BY sessionId;",
+            ),
+            example(
+                "Turns with no tool use",
+                "SELECT started, left(prompt, 80) FROM turns\n\
+                 WHERE tool_calls = 0 ORDER BY started DESC;",
+            ),
+        ],
+    },
+    ViewTopic {
+        name: "response_latency",
+        summary: "Per human prompt: time to first reply and first text, with model",
+        details: "Only the main conversation is considered. first_response is the wait until any \
+                  assistant message (often thinking or a tool call); first_text is the wait \
+                  until the first assistant text block. Both are NULL when nothing arrived \
+                  before the next prompt.",
+        examples: &[
+            example(
+                "Median time to first reply by model",
+                "SELECT model, median(epoch(first_response)) as median_seconds, count(*)\n\
+                 FROM response_latency GROUP BY model;",
+            ),
+            example(
+                "Responsiveness by week",
+                "SELECT date_trunc('week', timestamp) as week, avg(first_response) as avg_reply,\n\
+                   avg(first_text) as avg_text FROM response_latency GROUP BY week ORDER BY week;",
+            ),
+            example(
+                "Slowest prompts to answer",
+                "SELECT timestamp, first_response, left(content, 60) FROM response_latency\n\
+                 ORDER BY first_response DESC NULLS LAST LIMIT 10;",
+            ),
+        ],
+    },
+    ViewTopic {
+        name: "context_growth",
+        summary: "Context size per reply, running totals, and % of the context window",
+        details: "context_tokens is everything the request carried: input_tokens + \
+                  cache_read_tokens + cache_creation_tokens. Running totals and message_index \
+                  restart for each conversation (the main thread and each subagent file). \
+                  context_pct assumes a 200K-token window, so 1M-context requests can exceed \
+                  100%; auto-compaction usually triggers somewhere above 75%.",
+        examples: &[
+            example(
+                "Peak context per session",
+                "SELECT sessionId, max(context_pct) as peak_pct, max(message_index) as replies\n\
+                 FROM context_growth WHERE NOT isAgent GROUP BY sessionId ORDER BY peak_pct DESC;",
+            ),
+            example(
+                "A session's context over time",
+                "SELECT message_index, timestamp, context_tokens, context_pct FROM context_growth\n\
+                 WHERE sessionId::VARCHAR LIKE 'abc%' AND NOT isAgent ORDER BY message_index;",
+            ),
+            example(
+                "Replies sent near the limit",
+                "SELECT sessionId, timestamp, context_pct FROM context_growth\n\
+                 WHERE context_pct > 80;",
+            ),
+        ],
+    },
+    ViewTopic {
+        name: "stop_reasons",
+        summary: "Why each response ended, with truncated/refused flags",
+        details: "source 'assistant' rows carry the API stop_reason, lowercased (end_turn, \
+                  tool_use, stop_sequence, max_tokens, refusal, pause_turn, ...). source \
+                  'system' rows are hooks that stopped Claude (stopReason or \
+                  preventedContinuation), normalized to 'hook_stop' with the hook's reason in \
+                  detail. is_abnormal is true for anything but end_turn, tool_use, and \
+                  stop_sequence.",
+        examples: &[
+            example(
+                "Stop reasons by model",
+                "SELECT model, stop_reason, count(*) FROM stop_reasons\n\
+                 WHERE source = 'assistant' GROUP BY ALL ORDER BY model, count(*) DESC;",
+            ),
+            example(
+                "Truncated and refused responses",
+                "SELECT timestamp, sessionId, stop_reason, model FROM stop_reasons\n\
+                 WHERE is_truncated OR is_refusal ORDER BY timestamp DESC;",
+            ),
+            example(
+                "Hook stops",
+                "SELECT timestamp, sessionId, detail FROM stop_reasons\n\
+                 WHERE stop_reason = 'hook_stop';",
+            ),
+        ],
+    },
+    ViewTopic {
+        name: "permission_denials",
+        summary: "Tool calls rejected by the user or blocked by permission rules",
+        details: "Built from errored tool_calls whose result text matches Claude Code's \
+                  permission messages. denial_kind is user_rejected (declined at the prompt), \
+                  not_granted (permission not granted, e.g. in non-interactive runs), or \
+                  rule_denied (a deny rule matched). uuid and timestamp are those of the result \
+                  message.",
+        examples: &[
+            example(
+                "Most denied tools",
+                "SELECT tool_name, denial_kind, count(*) FROM permission_denials\n\
+                 GROUP BY ALL ORDER BY count(*) DESC;",
+            ),
+            example(
+                "Bash commands you keep rejecting",
+                "SELECT tool_input->>'command' as command, count(*) as rejections\n\
+                 FROM permission_denials\n\
+                 WHERE tool_name = 'Bash' AND denial_kind = 'user_rejected'\n\
+                 GROUP BY command ORDER BY rejections DESC;",
+            ),
+            example(
+                "Calls blocked in headless runs",
+                "SELECT timestamp, tool_name, tool_input FROM permission_denials\n\
+                 WHERE denial_kind = 'not_granted' ORDER BY timestamp DESC;",
+            ),
+        ],
+    },
+];
+
+/// Examples spanning views, shown by `.help examples`.
+pub const EXAMPLES: &[Example] = &[
+    example(
+        "Sessions summary",
+        "SELECT sessionId, count(*) as msgs, min(timestamp) as started\n\
+         FROM messages GROUP BY sessionId ORDER BY started DESC;",
+    ),
+    example(
+        "What did the plan look like before things went wrong?",
+        "SELECT * FROM todos_asof('abc123', '2025-01-15 14:30:00');\n\
+         SELECT * FROM files_touched_before('abc123', '2025-01-15 14:30:00');",
+    ),
+    example(
+        "Prompts that led to the most tool calls",
+        "SELECT h.content, count(u.tool_id) as calls\n\
+         FROM human_messages h JOIN tool_uses u ON u.sessionId = h.sessionId AND u.timestamp > h.timestamp\n\
+         GROUP BY ALL ORDER BY calls DESC LIMIT 10;",
+    ),
+    example(
+        "Daily output tokens",
+        "SELECT timestamp::DATE as day, sum(output_tokens) FROM token_usage GROUP BY day ORDER BY day;",
+    ),
+];
+
+const MACROS: &str = "\
+Macros (session ID prefix, timestamp):
+  todos_asof(s, ts)            Todo list as it stood at ts
+  files_touched_before(s, ts)  Files read/modified before ts";
+
+const JSON_SYNTAX: &str = "\
+JSON field access (DuckDB syntax):
+  message->'field'        Access JSON field (returns JSON)
+  message->>'field'       Access JSON field as string
+  message->'a'->'b'       Nested access
+
+Useful functions:
+  arr[n]                 Get nth element (1-indexed)
+  UNNEST(arr)            Expand array into rows
+  json_extract_string()  Extract string from JSON";
+
+/// Help for `name`, if it's a built-in view.
+pub fn topic(name: &str) -> Option<&'static ViewTopic> {
+    VIEWS.iter().find(|v| v.name.eq_ignore_ascii_case(name))
+}
+
+/// The `.help` overview: commands, views, macros, and JSON syntax.
+pub fn overview() -> String {
+    let mut out = String::from("\nCommands:\n");
+    for c in COMMANDS {
+        // Long usages get their own line, descriptions stay aligned
+        if c.usage.len() > 21 {
+            let _ = writeln!(out, "  {}\n  {:21} {}", c.usage, "", c.summary);
+        } else {
+            let _ = writeln!(out, "  {:21} {}", c.usage, c.summary);
+        }
+    }
+    out.push_str("\nViews:\n");
+    for v in VIEWS {
+        let _ = writeln!(out, "  {:19} {}", v.name, v.summary);
+    }
+    let _ = write!(
+        out,
+        "\n{MACROS}\n\n{JSON_SYNTAX}\n\nType .help <view> for its columns and examples, or .help examples.\n"
+    );
+    out
+}
+
+/// A view's name, summary, and details; the REPL follows this with its live
+/// columns and [`format_examples`].
+pub fn describe(topic: &ViewTopic) -> String {
+    format!("{} - {}\n\n{}", topic.name, topic.summary, topic.details)
+}
+
+/// Examples as commented SQL, indented like the rest of the help.
+pub fn format_examples(examples: &[Example]) -> String {
+    let mut out = String::new();
+    write_examples(&mut out, examples);
+    out.trim_end().to_string()
+}
+
+/// `.help examples`: every view's examples plus the cross-view ones.
+pub fn examples() -> String {
+    let mut out = String::new();
+    for topic in VIEWS {
+        let _ = writeln!(out, "=== {} ===", topic.name);
+        write_examples(&mut out, topic.examples);
+    }
+    out.push_str("=== across views ===\n");
+    write_examples(&mut out, EXAMPLES);
+    out
+}
+
+fn write_examples(out: &mut String, examples: &[Example]) {
+    for e in examples {
+        let _ = writeln!(out, "  -- {}", e.title);
+        for line in e.sql.lines() {
+            let _ = writeln!(out, "  {line}");
+        }
+        out.push('\n');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_topic_lookup() {
+        let topic = topic("TOOL_USES").unwrap();
+        assert_eq!(topic.name, "tool_uses");
+        assert!((3..=4).contains(&topic.examples.len()));
+        assert!(describe(topic).starts_with("tool_uses - All tool calls"));
+        assert!(format_examples(topic.examples).starts_with("  -- Most used tools\n  SELECT tool_name"));
+        assert!(super::topic("nope").is_none());
+    }
+
+    #[test]
+    fn test_overview_aligns_commands() {
+        let text = overview();
+        assert!(text.contains("\n  .tables               List built-in views"));
+        assert!(text.contains("\n  .export <fmt> <file> [query]\n                        Write"));
+        assert!(text.contains("\n  assistant_messages  Assistant messages"));
+    }
+}
@@ -0,0 +1,107 @@
+//! SQL syntax highlighting for the interactive prompt.
+
+const KEYWORD: &str = "\x1b[1;34m";
+const STRING: &str = "\x1b[32m";
+const VIEW: &str = "\x1b[36m";
+const NUMBER: &str = "\x1b[33m";
+const DIM: &str = "\x1b[2m";
+const RESET: &str = "\x1b[0m";
+
+/// Colorize keywords, string literals, numbers, comments, and view names.
+/// Dot commands are left as typed.
+pub fn highlight_sql(line: &str, keywords: &[&str], views: &[String]) -> String {
+    if line.trim_start().starts_with('.') {
+        return line.to_string();
+    }
+
+    let mut out = String::with_capacity(line.len() * 2);
+    let mut rest = line;
+    while let Some(c) = rest.chars().next() {
+        let (len, color) = if rest.starts_with("--") {
+            (rest.find('\n').unwrap_or(rest.len()), Some(DIM))
+        } else if rest.starts_with("/*") {
+            (rest.find("*/").map_or(rest.len(), |i| i + 2), Some(DIM))
+        } else if c == '\'' {
+            (quoted_len(rest), Some(STRING))
+        } else if c == '"' {
+            (rest[1..].find('"').map_or(rest.len(), |i| i + 2), None)
+        } else if c.is_ascii_digit() {
+            let len = rest
+                .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+                .unwrap_or(rest.len());
+            (len, Some(NUMBER))
+        } else if c.is_alphabetic() || c == '_' {
+            let len = rest
+                .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            let word = &rest[..len];
+            let color = if keywords.iter().any(|k| k.eq_ignore_ascii_case(word)) {
+                Some(KEYWORD)
+            } else if views.iter().any(|v| v == word) {
+                Some(VIEW)
+            } else {
+                None
+            };
+            (len, color)
+        } else {
+            (c.len_utf8(), None)
+        };
+
+        let (token, tail) = rest.split_at(len);
+        match color {
+            Some(color) => {
+                out.push_str(color);
+                out.push_str(token);
+                out.push_str(RESET);
+            }
+            None => out.push_str(token),
+        }
+        rest = tail;
+    }
+    out
+}
+
+/// Length of a single-quoted literal at the start of `s`, `''` escapes
+/// included; an unterminated literal runs to the end.
+fn quoted_len(s: &str) -> usize {
+    let bytes = s.as_bytes();
+    let mut i = 1;
+    while i < bytes.len() {
+        if bytes[i] == b'\'' {
+            if bytes.get(i + 1) == Some(&b'\'') {
+                i += 2;
+                continue;
+            }
+            return i + 1;
+        }
+        i += 1;
+    }
+    bytes.len()
+}
+
+/// Dim a prompt (used for continuation lines).
+pub fn dim(prompt: &str) -> String {
+    format!("{DIM}{prompt}{RESET}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_highlight_sql_tokens() {
+        let views = vec!["messages".to_string()];
+        let out = highlight_sql("select 'it''s' FROM messages -- note", &["SELECT", "FROM"], &views);
+        assert_eq!(
+            out,
+            "\x1b[1;34mselect\x1b[0m \x1b[32m'it''s'\x1b[0m \x1b[1;34mFROM\x1b[0m \
+             \x1b[36mmessages\x1b[0m \x1b[2m-- note\x1b[0m"
+        );
+    }
+
+    #[test]
+    fn test_highlight_leaves_dot_commands_and_identifiers() {
+        assert_eq!(highlight_sql(".schema messages", &["SELECT"], &[]), ".schema messages");
+        assert_eq!(highlight_sql("\"select\"", &["SELECT"], &[]), "\"select\"");
+    }
+}
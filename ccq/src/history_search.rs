@@ -0,0 +1,198 @@
+//! Ctrl-R fuzzy search over REPL history, with a selectable list of matches.
+
+use std::io;
+use std::sync::{Arc, Mutex};
+
+use ratatui::backend::CrosstermBackend;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::crossterm::execute;
+use ratatui::crossterm::terminal::{EnterAlternateScreen, LeaveAlternateScreen};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Style, Stylize};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, List, ListState, Paragraph};
+use ratatui::{Frame, Terminal};
+use rustyline::{
+    Cmd, ConditionalEventHandler, Event as KeyInput, EventContext, Movement, RepeatCount,
+};
+
+/// Key handler replacing rustyline's reverse-i-search. Entries are shared
+/// with the REPL loop, which refreshes them from the editor's history.
+pub struct HistorySearch {
+    entries: Arc<Mutex<Vec<String>>>,
+}
+
+impl HistorySearch {
+    pub const fn new(entries: Arc<Mutex<Vec<String>>>) -> Self {
+        Self { entries }
+    }
+}
+
+impl ConditionalEventHandler for HistorySearch {
+    fn handle(&self, _: &KeyInput, _: RepeatCount, _: bool, _: &EventContext) -> Option<Cmd> {
+        let entries = self.entries.lock().ok()?.clone();
+        match pick(&entries) {
+            Ok(Some(entry)) => Some(Cmd::Replace(Movement::WholeBuffer, Some(entry))),
+            Ok(None) => Some(Cmd::Repaint),
+            // Fall back to rustyline's own search
+            Err(_) => None,
+        }
+    }
+}
+
+/// Score `candidate` against `query`; `None` if it doesn't match.
+///
+/// Every whitespace-separated term must appear as a case-insensitive
+/// subsequence. Consecutive characters and word starts score higher.
+pub fn score(query: &str, candidate: &str) -> Option<i64> {
+    let haystack: Vec<char> = candidate.to_lowercase().chars().collect();
+    query.split_whitespace().try_fold(0, |total, term| {
+        let term: Vec<char> = term.to_lowercase().chars().collect();
+        // Best of the matches starting at each occurrence of the first character
+        let best = (0..haystack.len())
+            .filter(|&start| haystack[start] == term[0])
+            .filter_map(|start| score_from(&haystack, &term, start))
+            .max()?;
+        Some(total + best)
+    })
+}
+
+/// Score `term` matched greedily in `haystack`, its first character at `start`.
+fn score_from(haystack: &[char], term: &[char], start: usize) -> Option<i64> {
+    let mut total = 0;
+    let mut from = start;
+    let mut previous: Option<usize> = None;
+    for &needle in term {
+        let at = from + haystack[from..].iter().position(|&c| c == needle)?;
+        total += 1;
+        if previous.is_some_and(|p| p + 1 == at) {
+            total += 5;
+        }
+        if at == 0 || !haystack[at - 1].is_alphanumeric() {
+            total += 3;
+        }
+        if previous.is_some() {
+            total -= i64::try_from(at - from).unwrap_or(i64::MAX).min(3);
+        }
+        previous = Some(at);
+        from = at + 1;
+    }
+    Some(total)
+}
+
+/// Distinct `entries` (oldest first, as history stores them) matching
+/// `query`, best first; ties go to the most recent.
+pub fn rank<'a>(query: &str, entries: &'a [String]) -> Vec<&'a str> {
+    let mut seen = std::collections::HashSet::new();
+    let mut scored: Vec<(i64, &str)> = entries
+        .iter()
+        .rev()
+        .filter(|entry| seen.insert(entry.as_str()))
+        .filter_map(|entry| score(query, entry).map(|s| (s, entry.as_str())))
+        .collect();
+    // Stable: equal scores keep newest-first order
+    scored.sort_by_key(|&(score, _)| std::cmp::Reverse(score));
+    scored.into_iter().map(|(_, entry)| entry).collect()
+}
+
+/// Show the search screen and return the chosen entry, `None` if cancelled.
+/// Runs inside rustyline's raw mode, so only the alternate screen is toggled.
+///
+/// # Errors
+/// Returns error if the terminal can't be drawn or read.
+pub fn pick(entries: &[String]) -> io::Result<Option<String>> {
+    execute!(io::stdout(), EnterAlternateScreen)?;
+    let result = Terminal::new(CrosstermBackend::new(io::stdout()))
+        .and_then(|mut terminal| {
+            let picked = search_loop(&mut terminal, entries);
+            terminal.show_cursor()?;
+            picked
+        });
+    execute!(io::stdout(), LeaveAlternateScreen)?;
+    result
+}
+
+fn search_loop(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    entries: &[String],
+) -> io::Result<Option<String>> {
+    let mut query = String::new();
+    let mut state = ListState::default().with_selected(Some(0));
+    loop {
+        let matches = rank(&query, entries);
+        terminal.draw(|frame| draw(frame, &query, &matches, &mut state))?;
+
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc => return Ok(None),
+            KeyCode::Char('c' | 'g') if ctrl => return Ok(None),
+            KeyCode::Enter => {
+                let chosen = state.selected().and_then(|i| matches.get(i));
+                return Ok(chosen.map(|entry| (*entry).to_string()));
+            }
+            KeyCode::Up => state.select_previous(),
+            KeyCode::Char('p') if ctrl => state.select_previous(),
+            KeyCode::Down => state.select_next(),
+            KeyCode::Char('n' | 'r') if ctrl => state.select_next(),
+            KeyCode::Backspace => {
+                query.pop();
+                state.select(Some(0));
+            }
+            KeyCode::Char(c) if !ctrl => {
+                query.push(c);
+                state.select(Some(0));
+            }
+            _ => {}
+        }
+    }
+}
+
+fn draw(frame: &mut Frame, query: &str, matches: &[&str], state: &mut ListState) {
+    let [input, list] =
+        Layout::vertical([Constraint::Length(3), Constraint::Fill(1)]).areas(frame.area());
+
+    let title = format!(" history: {} match(es) | Enter select, Esc cancel ", matches.len());
+    let prompt = Line::from(vec!["> ".bold(), query.into()]);
+    frame.render_widget(Paragraph::new(prompt).block(Block::bordered().title(title)), input);
+    let typed = u16::try_from(query.chars().count()).unwrap_or(0);
+    frame.set_cursor_position((input.x + 3 + typed, input.y + 1));
+
+    // Multi-line queries are shown on one line
+    let items: Vec<String> = matches
+        .iter()
+        .map(|entry| entry.split_whitespace().collect::<Vec<_>>().join(" "))
+        .collect();
+    let list_widget = List::new(items)
+        .block(Block::bordered())
+        .highlight_style(Style::default().fg(Color::Black).bg(Color::Cyan));
+    frame.render_stateful_widget(list_widget, list, state);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_score_subsequence_terms() {
+        assert!(score("tlus", "SELECT * FROM tool_uses").is_some());
+        assert!(score("tool cnt", "SELECT tool_name, count(*) AS cnt FROM tool_uses").is_some());
+        assert!(score("xyz", "SELECT 1").is_none());
+        assert!(score("tool", "SELECT tool_name").unwrap() > score("tool", "t_o_o_l").unwrap());
+    }
+
+    #[test]
+    fn test_rank_dedupes_and_prefers_recent_on_ties() {
+        let entries: Vec<String> = ["SELECT 1", "SELECT 2", "SELECT 1", ".schema"]
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(rank("", &entries), vec![".schema", "SELECT 1", "SELECT 2"]);
+        assert_eq!(rank("sel 2", &entries), vec!["SELECT 2"]);
+    }
+}
@@ -0,0 +1,197 @@
+//! Single-message JSON inspector used by `.show`.
+
+use std::fmt::Write;
+
+use serde_json::Value;
+
+use crate::utils::sql_quote;
+use crate::{Error, QuerySession, Result};
+
+const KEY: &str = "\x1b[34m";
+const STRING: &str = "\x1b[32m";
+const NUMBER: &str = "\x1b[33m";
+const LITERAL: &str = "\x1b[35m";
+const DIM: &str = "\x1b[2m";
+const RESET: &str = "\x1b[0m";
+
+/// A message's raw JSON with its resolved neighbours in the thread.
+#[derive(Debug, Clone)]
+pub struct MessageView {
+    pub uuid: String,
+    pub raw: Value,
+    pub parent: Option<String>,
+    pub children: Vec<String>,
+}
+
+/// Fetch the message whose uuid starts with `prefix`.
+///
+/// # Errors
+/// Returns error if no message or more than one message matches.
+pub fn fetch(session: &QuerySession, prefix: &str) -> Result<MessageView> {
+    let result = session.query(&format!(
+        "SELECT DISTINCT uuid::VARCHAR as uuid, raw FROM raw_messages
+         WHERE starts_with(uuid::VARCHAR, {}) ORDER BY uuid LIMIT 10",
+        sql_quote(&prefix.to_lowercase())
+    ))?;
+
+    let mut uuids: Vec<&str> = result.rows().iter().map(|row| row[0].as_str()).collect();
+    uuids.dedup();
+    match uuids.len() {
+        0 => return Err(Error::MessageNotFound(prefix.to_string())),
+        1 => {}
+        n => {
+            return Err(Error::AmbiguousMessage {
+                prefix: prefix.to_string(),
+                matches: n,
+            });
+        }
+    }
+
+    let row = &result.rows()[0];
+    let uuid = row[0].clone();
+    let raw: Value = serde_json::from_str(&row[1]).unwrap_or(Value::Null);
+    let parent = raw
+        .get("parentUuid")
+        .and_then(Value::as_str)
+        .map(str::to_string);
+
+    let children = session
+        .query(&format!(
+            "SELECT DISTINCT uuid::VARCHAR FROM messages WHERE parentUuid = {} ORDER BY 1",
+            sql_quote(&uuid)
+        ))?
+        .rows()
+        .iter()
+        .map(|row| row[0].clone())
+        .collect();
+
+    Ok(MessageView {
+        uuid,
+        raw,
+        parent,
+        children,
+    })
+}
+
+/// Render the message with its parent/children header.
+pub fn render_message(view: &MessageView, max_depth: Option<usize>, color: bool) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "uuid:     {}", view.uuid);
+    let _ = writeln!(out, "parent:   {}", view.parent.as_deref().unwrap_or("(none)"));
+    if view.children.is_empty() {
+        let _ = writeln!(out, "children: (none)");
+    } else {
+        let _ = writeln!(out, "children: {}", view.children.join(", "));
+    }
+    out.push('\n');
+    out.push_str(&render(&view.raw, max_depth, color));
+    out
+}
+
+/// Pretty-print JSON, folding containers nested deeper than `max_depth`.
+pub fn render(value: &Value, max_depth: Option<usize>, color: bool) -> String {
+    let mut out = String::new();
+    let painter = Painter { color };
+    write_value(&mut out, value, 0, max_depth, painter);
+    out
+}
+
+#[derive(Clone, Copy)]
+struct Painter {
+    color: bool,
+}
+
+impl Painter {
+    fn paint(self, out: &mut String, style: &str, text: &str) {
+        if self.color {
+            let _ = write!(out, "{style}{text}{RESET}");
+        } else {
+            out.push_str(text);
+        }
+    }
+}
+
+fn write_value(
+    out: &mut String,
+    value: &Value,
+    depth: usize,
+    max_depth: Option<usize>,
+    painter: Painter,
+) {
+    let folded = max_depth.is_some_and(|max| depth >= max);
+    match value {
+        Value::Null => painter.paint(out, LITERAL, "null"),
+        Value::Bool(b) => painter.paint(out, LITERAL, &b.to_string()),
+        Value::Number(n) => painter.paint(out, NUMBER, &n.to_string()),
+        Value::String(s) => painter.paint(out, STRING, &Value::String(s.clone()).to_string()),
+        Value::Array(items) if items.is_empty() => out.push_str("[]"),
+        Value::Object(map) if map.is_empty() => out.push_str("{}"),
+        Value::Array(items) if folded => {
+            painter.paint(out, DIM, &format!("[… {} item(s)]", items.len()));
+        }
+        Value::Object(map) if folded => {
+            painter.paint(out, DIM, &format!("{{… {} key(s)}}", map.len()));
+        }
+        Value::Array(items) => {
+            out.push_str("[\n");
+            for (i, item) in items.iter().enumerate() {
+                indent(out, depth + 1);
+                write_value(out, item, depth + 1, max_depth, painter);
+                close_item(out, i + 1 == items.len());
+            }
+            indent(out, depth);
+            out.push(']');
+        }
+        Value::Object(map) => {
+            out.push_str("{\n");
+            for (i, (key, item)) in map.iter().enumerate() {
+                indent(out, depth + 1);
+                painter.paint(out, KEY, &Value::String(key.clone()).to_string());
+                out.push_str(": ");
+                write_value(out, item, depth + 1, max_depth, painter);
+                close_item(out, i + 1 == map.len());
+            }
+            indent(out, depth);
+            out.push('}');
+        }
+    }
+}
+
+fn indent(out: &mut String, depth: usize) {
+    out.push_str(&"  ".repeat(depth));
+}
+
+fn close_item(out: &mut String, last: bool) {
+    out.push_str(if last { "\n" } else { ",\n" });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_render_unfolded_matches_pretty_json() {
+        let value = json!({"a": 1, "b": ["x", null], "c": {"d": true}, "e": {}});
+        assert_eq!(
+            render(&value, None, false),
+            serde_json::to_string_pretty(&value).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_render_folds_below_depth() {
+        let value = json!({"message": {"content": [1, 2, 3]}, "type": "user"});
+        assert_eq!(
+            render(&value, Some(1), false),
+            "{\n  \"message\": {… 1 key(s)},\n  \"type\": \"user\"\n}"
+        );
+    }
+
+    #[test]
+    fn test_render_color_wraps_keys() {
+        let out = render(&json!({"k": "v"}), None, true);
+        assert!(out.contains("\x1b[34m\"k\"\x1b[0m"));
+        assert!(out.contains("\x1b[32m\"v\"\x1b[0m"));
+    }
+}
@@ -1,12 +1,36 @@
 //! cc-query library for querying Claude Code session data with `DuckDB`.
 
+pub mod batch;
+pub mod completion;
+pub mod config;
+pub mod diff;
+pub mod doctor;
 pub mod error;
+pub mod export;
 pub mod formatter;
+pub mod help;
+pub mod highlight;
+pub mod history_search;
+pub mod inspect;
+pub mod mcp;
+pub mod pager;
+pub mod plan;
+pub mod pricing;
 pub mod query_session;
 pub mod repl;
+pub mod replay;
+pub mod saved;
+pub mod secrets;
+pub mod serve;
 pub mod session_loader;
+pub mod source;
+pub mod todos;
+pub mod top;
+pub mod tree;
 pub mod utils;
+pub mod verify;
 
+pub use config::Config;
 pub use error::{Error, Result};
-pub use query_session::QuerySession;
+pub use query_session::{QuerySession, SessionOptions};
 pub use session_loader::SessionInfo;
@@ -1,48 +1,516 @@
 //! CLI entry point for ccq.
 
 use std::io::IsTerminal;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::ExitCode;
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
+
+use ccq::secrets::{self, Detector};
 
 /// SQL REPL for querying Claude Code session data
 #[derive(Debug, Parser)]
 #[command(name = "ccq", version, about)]
+#[allow(clippy::struct_excessive_bools)] // independent CLI flags
 struct Cli {
+    #[command(subcommand)]
+    subcommand: Option<Command>,
+
     /// Path to project (omit for all projects)
     project_path: Option<PathBuf>,
 
     /// Filter to sessions matching ID prefix
-    #[arg(short, long)]
+    #[arg(short, long, global = true)]
     session: Option<String>,
 
-    /// Use directory directly as JSONL data source
-    #[arg(short, long = "data-dir")]
-    data_dir: Option<PathBuf>,
+    /// Use directory directly as JSONL data source (repeatable; files are merged)
+    #[arg(short, long = "data-dir", global = true)]
+    data_dir: Vec<PathBuf>,
+
+    /// Only include messages recorded on this git branch
+    #[arg(short, long, global = true)]
+    branch: Option<String>,
+
+    /// Skip counting session files at startup (faster on large trees; see .info)
+    #[arg(long, global = true)]
+    no_count: bool,
+
+    /// Number style for table output: plain, comma, dot, space, or locale
+    #[arg(long, value_name = "STYLE", global = true, value_parser = parse_number_style)]
+    numbers: Option<ccq::formatter::NumberFormat>,
+
+    /// Color REPL tables: auto (terminal without `NO_COLOR`), always, or never
+    #[arg(
+        long,
+        value_name = "WHEN",
+        global = true,
+        default_value = "auto",
+        value_parser = parse_color
+    )]
+    color: ccq::formatter::ColorChoice,
+
+    /// Currency symbol for cost columns in table output (e.g. "$")
+    #[arg(long, value_name = "SYMBOL", global = true)]
+    currency: Option<String>,
+
+    /// Retry a query once with fewer threads if it runs out of memory
+    #[arg(long, global = true)]
+    retry_oom: bool,
+
+    /// Suppress the startup banner and row-count footers
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+
+    /// Print discovery details and view creation timing to stderr
+    #[arg(short, long, global = true)]
+    verbose: bool,
+
+    /// Show each query's plan instead of its results
+    #[arg(long, global = true, conflicts_with = "profile")]
+    explain: bool,
+
+    /// Run each query under EXPLAIN ANALYZE and show the plan with timings
+    #[arg(long, global = true)]
+    profile: bool,
+
+    /// Parse and plan -c or piped statements without executing them; exits 1 on errors
+    #[arg(long, conflicts_with_all = ["explain", "profile"])]
+    check: bool,
+
+    /// Expand ${VAR} environment variables in -c and piped SQL
+    #[arg(long)]
+    env_subst: bool,
+
+    /// Create an extra view for this run (repeatable)
+    #[arg(long = "view", value_name = "NAME=SELECT", global = true, value_parser = parse_view)]
+    views: Vec<(String, String)>,
+
+    /// After -c or piped SQL, write a JSON summary of statements, rows, timings, and errors ("-" for stderr)
+    #[arg(long, value_name = "PATH")]
+    summary_json: Option<PathBuf>,
+
+    /// Execute SQL and exit instead of reading stdin or starting the REPL
+    #[arg(short, long, value_name = "SQL")]
+    command: Option<String>,
+
+    /// Exit non-zero if the queries return no rows (or any query fails)
+    #[arg(long, conflicts_with = "fail_if_rows")]
+    fail_if_empty: bool,
+
+    /// Exit non-zero if the queries return any rows (or any query fails)
+    #[arg(long)]
+    fail_if_rows: bool,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Step through a session message by message (prints everything when not a terminal)
+    Replay {
+        /// Session ID (or unique prefix)
+        id: String,
+
+        /// Include subagent messages
+        #[arg(long)]
+        agents: bool,
+    },
+
+    /// Scan message content and tool results for leaked secrets
+    ScanSecrets {
+        /// Only run the named built-in detector (repeatable)
+        #[arg(long = "detector", value_name = "NAME", value_parser = parse_builtin_detector)]
+        detectors: Vec<Detector>,
+
+        /// Add a custom detector using RE2 syntax (repeatable)
+        #[arg(long = "pattern", value_name = "NAME=REGEX", value_parser = parse_custom_detector)]
+        patterns: Vec<Detector>,
+    },
+
+    /// Run a SQL script against each project matching a glob, one output file per project
+    Batch {
+        /// Glob of project directories, e.g. '~/code/*'
+        #[arg(long, value_name = "GLOB")]
+        projects_glob: String,
+
+        /// SQL script file to run against each project
+        #[arg(long, value_name = "FILE")]
+        query: PathBuf,
+
+        /// Output path template; {project} is replaced by the directory name, prefixed with
+        /// parent directories where names repeat (.csv for CSV, else TSV)
+        #[arg(long, value_name = "TEMPLATE")]
+        out: String,
+    },
+
+    /// Compare two sessions: messages, tokens, tool mix, files, duration, errors
+    Diff {
+        /// First session ID (or unique prefix)
+        a: String,
+
+        /// Second session ID (or unique prefix)
+        b: String,
+    },
+
+    /// Diagnose missing directories, unreadable files, malformed lines, and unknown schema keys
+    Doctor,
+
+    /// Write a session back out as importable JSONL with content trimmed
+    ExportSession {
+        /// Session ID (or unique prefix)
+        id: String,
+
+        /// Output file (default: stdout)
+        #[arg(short, long)]
+        out: Option<PathBuf>,
+
+        /// Replace tool result content with a placeholder
+        #[arg(long, conflicts_with = "truncate_tool_results")]
+        drop_tool_results: bool,
+
+        /// Truncate tool result text to CHARS characters
+        #[arg(long, value_name = "CHARS")]
+        truncate_tool_results: Option<usize>,
+
+        /// Replace image blocks with a text placeholder
+        #[arg(long)]
+        drop_images: bool,
+
+        /// Remove file-history-snapshot lines
+        #[arg(long)]
+        drop_snapshots: bool,
+    },
+
+    /// Serve an HTTP API: `POST /query` (SQL in, JSON out) and `GET /views`
+    Serve {
+        /// Address to listen on (no authentication; use 0.0.0.0 only on trusted networks)
+        #[arg(long, default_value = "127.0.0.1:7878")]
+        bind: String,
+    },
+
+    /// Serve the data to agents as an MCP server on stdio (`run_query`, `list_views`,
+    /// `describe_view`)
+    Mcp,
+
+    /// Show the latest todo list per session, optionally with status history
+    Todos {
+        /// Session ID (or unique prefix); defaults to the most recently updated sessions
+        id: Option<String>,
+
+        /// Also show each todo's status transitions
+        #[arg(long)]
+        history: bool,
+
+        /// Number of sessions to show when no ID is given
+        #[arg(long, default_value_t = 5)]
+        limit: usize,
+    },
+
+    /// Live dashboard of token burn, the active session, tool calls, and errors
+    Top {
+        /// Seconds between refreshes
+        #[arg(long, default_value_t = 2)]
+        interval: u64,
+    },
+
+    /// Check that every raw field survives the typed schema for a session
+    Verify {
+        /// Session ID (or prefix)
+        id: String,
+    },
+}
+
+fn parse_builtin_detector(name: &str) -> Result<Detector, String> {
+    let builtins = secrets::builtin_detectors();
+    let names = builtins.iter().map(|d| d.name.as_str()).collect::<Vec<_>>().join(", ");
+    builtins
+        .into_iter()
+        .find(|d| d.name == name)
+        .ok_or_else(|| format!("unknown detector (available: {names})"))
+}
+
+fn parse_custom_detector(spec: &str) -> Result<Detector, String> {
+    Detector::parse(spec).ok_or_else(|| "expected NAME=REGEX".to_string())
+}
+
+fn parse_number_style(style: &str) -> Result<ccq::formatter::NumberFormat, String> {
+    ccq::formatter::NumberFormat::from_style(style).ok_or_else(|| {
+        format!("expected one of: {}", ccq::formatter::NUMBER_STYLES.join(", "))
+    })
+}
+
+fn parse_color(when: &str) -> Result<ccq::formatter::ColorChoice, String> {
+    ccq::formatter::ColorChoice::parse(when).ok_or_else(|| {
+        format!("expected one of: {}", ccq::formatter::ColorChoice::NAMES.join(", "))
+    })
+}
+
+fn parse_view(spec: &str) -> Result<(String, String), String> {
+    match spec.split_once('=') {
+        Some((name, select)) if !name.trim().is_empty() && !select.trim().is_empty() => {
+            Ok((name.trim().to_string(), select.trim().to_string()))
+        }
+        _ => Err("expected NAME=SELECT ...".to_string()),
+    }
 }
 
 fn main() -> ExitCode {
-    if let Err(e) = run() {
-        eprintln!("Error: {e}");
-        ExitCode::FAILURE
+    match run() {
+        Ok(code) => code,
+        // Output piped into something like `head` that stopped reading
+        Err(ccq::Error::Io(e)) if e.kind() == std::io::ErrorKind::BrokenPipe => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run() -> ccq::Result<ExitCode> {
+    let mut cli = Cli::parse();
+    // Only the REPL needs a valid config; other commands fall back to defaults
+    let config = ccq::Config::load();
+
+    let options = ccq::SessionOptions {
+        project_dir: cli.project_path.take(),
+        session_filter: cli.session.take(),
+        data_dirs: std::mem::take(&mut cli.data_dir),
+        branch: cli.branch.take(),
+        views: std::mem::take(&mut cli.views),
+        no_count: cli.no_count,
+        retry_oom: cli.retry_oom,
+        pricing: config.as_ref().map(|c| c.pricing.clone()).unwrap_or_default(),
+    };
+
+    match cli.subcommand.take() {
+        // Doctor must run even when discovery finds nothing to load
+        Some(Command::Doctor) => {
+            let checks = ccq::doctor::run(&options)?;
+            Ok(exit_code(ccq::doctor::print_report(&checks)))
+        }
+        Some(Command::Batch {
+            projects_glob,
+            query,
+            out,
+        }) => batch(projects_glob, &query, out, &options),
+        Some(Command::Diff { a, b }) => {
+            let session = open_session(options, &cli)?;
+            println!("{}", ccq::diff::diff(&session, &a, &b)?);
+            Ok(ExitCode::SUCCESS)
+        }
+        Some(Command::Replay { id, agents }) => replay(&open_session(options, &cli)?, &id, agents),
+        Some(Command::ScanSecrets {
+            detectors,
+            patterns,
+        }) => scan_secrets(&open_session(options, &cli)?, detectors, patterns),
+        Some(Command::ExportSession {
+            id,
+            out,
+            drop_tool_results,
+            truncate_tool_results,
+            drop_images,
+            drop_snapshots,
+        }) => {
+            let session = open_session(options, &cli)?;
+            let export = ccq::export::ExportOptions {
+                drop_tool_results,
+                truncate_tool_results,
+                drop_images,
+                drop_snapshots,
+            };
+            export_session(&session, &id, out.as_deref(), &export)
+        }
+        Some(Command::Serve { bind }) => {
+            let session = open_session(options, &cli)?;
+            ccq::serve::serve(&session, &bind)?;
+            Ok(ExitCode::SUCCESS)
+        }
+        Some(Command::Mcp) => {
+            let session = open_session(options, &cli)?;
+            ccq::mcp::serve_stdio(&session)?;
+            Ok(ExitCode::SUCCESS)
+        }
+        Some(Command::Todos { id, history, limit }) => {
+            todos(&open_session(options, &cli)?, id.as_deref(), history, limit)
+        }
+        Some(Command::Top { interval }) => {
+            let session = open_session(options, &cli)?;
+            ccq::top::run(&session, std::time::Duration::from_secs(interval.max(1)))?;
+            Ok(ExitCode::SUCCESS)
+        }
+        Some(Command::Verify { id }) => {
+            let session = open_session(options, &cli)?;
+            let report = ccq::verify::verify(&session, &id)?;
+            Ok(exit_code(ccq::verify::print_report(&report)))
+        }
+        None => {
+            let config = config?;
+            let mut session = open_session(options, &cli)?;
+            ccq::repl::register_macros(&session, &config.macros);
+            ccq::repl::warm_up(&session, &config.warmup, cli.quiet);
+            run_queries(&mut session, &cli, &config.repl)
+        }
+    }
+}
+
+fn batch(
+    projects_glob: String,
+    query: &Path,
+    out_template: String,
+    options: &ccq::SessionOptions,
+) -> ccq::Result<ExitCode> {
+    let batch = ccq::batch::BatchOptions {
+        projects_glob,
+        script: std::fs::read_to_string(query)?,
+        out_template,
+    };
+    let summary = ccq::batch::run(&batch, options)?;
+    eprintln!(
+        "{} written, {} skipped, {} failed",
+        summary.written, summary.skipped, summary.failed
+    );
+    Ok(exit_code(summary.failed == 0))
+}
+
+fn replay(session: &ccq::QuerySession, id: &str, agents: bool) -> ccq::Result<ExitCode> {
+    let session_id = session.resolve_session_id(id)?;
+    let messages = ccq::replay::load(session, &session_id, agents)?;
+    if messages.is_empty() {
+        return Err(ccq::Error::SessionNotFound(id.to_string()));
+    }
+    if std::io::stdout().is_terminal() {
+        ccq::replay::run(&messages)?;
     } else {
-        ExitCode::SUCCESS
+        ccq::replay::print_all(&messages);
     }
+    Ok(ExitCode::SUCCESS)
 }
 
-fn run() -> ccq::Result<()> {
-    let cli = Cli::parse();
+fn scan_secrets(
+    session: &ccq::QuerySession,
+    detectors: Vec<Detector>,
+    patterns: Vec<Detector>,
+) -> ccq::Result<ExitCode> {
+    let mut detectors = if detectors.is_empty() {
+        secrets::builtin_detectors()
+    } else {
+        detectors
+    };
+    detectors.extend(patterns);
+
+    let findings = secrets::scan(session, &detectors)?;
+    secrets::print_report(&findings);
+    Ok(exit_code(findings.is_empty()))
+}
+
+fn export_session(
+    session: &ccq::QuerySession,
+    id: &str,
+    out: Option<&Path>,
+    options: &ccq::export::ExportOptions,
+) -> ccq::Result<ExitCode> {
+    // The file search walks the discovered directories, which `--no-count` skips
+    let info = session.counted_info()?;
+    let stats = ccq::export::export_to(&info, id, out, options)?;
+    eprintln!(
+        "Exported {} line(s): {} trimmed, {} dropped ({} -> {} bytes)",
+        stats.lines, stats.modified, stats.dropped, stats.bytes_in, stats.bytes_out
+    );
+    Ok(ExitCode::SUCCESS)
+}
+
+fn todos(
+    session: &ccq::QuerySession,
+    id: Option<&str>,
+    history: bool,
+    limit: usize,
+) -> ccq::Result<ExitCode> {
+    let scope = match id {
+        Some(id) => ccq::todos::Scope::Session(session.resolve_session_id(id)?),
+        None => ccq::todos::Scope::Recent(limit),
+    };
+    let lists = ccq::todos::latest(session, &scope)?;
+    let transitions = if history {
+        Some(ccq::todos::transitions(session, &scope)?)
+    } else {
+        None
+    };
+    print!("{}", ccq::todos::render(&lists, transitions.as_deref()));
+    Ok(ExitCode::SUCCESS)
+}
+
+fn open_session(options: ccq::SessionOptions, cli: &Cli) -> ccq::Result<ccq::QuerySession> {
+    let session = ccq::QuerySession::with_options(options)?;
+    if cli.verbose {
+        ccq::repl::print_discovery_details(&session);
+    }
+    Ok(session)
+}
 
-    let session = ccq::QuerySession::create(
-        cli.project_path.as_deref(),
-        cli.session.as_deref(),
-        cli.data_dir.as_deref(),
-    )?;
+/// Run `-c` SQL, piped stdin, or the interactive REPL.
+fn run_queries(
+    session: &mut ccq::QuerySession,
+    cli: &Cli,
+    repl: &ccq::config::ReplConfig,
+) -> ccq::Result<ExitCode> {
+    let analyze = if cli.profile {
+        Some(ccq::repl::Analyze::Profile)
+    } else if cli.explain {
+        Some(ccq::repl::Analyze::Explain)
+    } else {
+        None
+    };
+    let options = ccq::repl::ReplOptions {
+        quiet: cli.quiet,
+        analyze,
+        env_subst: cli.env_subst,
+        check: cli.check,
+        numbers: ccq::formatter::NumberFormat {
+            currency: cli.currency.clone(),
+            ..cli.numbers.clone().unwrap_or_default()
+        },
+        pager: true,
+        color: cli.color.enabled(std::io::stdout().is_terminal()),
+        max_rows: Some(ccq::repl::DEFAULT_MAX_ROWS),
+        confirm_rows: Some(ccq::repl::DEFAULT_CONFIRM_ROWS),
+        edit_mode: repl.edit_mode,
+        prompt: repl.prompt.clone(),
+        max_width: Some(ccq::repl::DEFAULT_MAX_WIDTH),
+        ..ccq::repl::ReplOptions::default()
+    };
 
-    if std::io::stdin().is_terminal() {
-        ccq::repl::start_interactive(&session)
+    let summary = if let Some(sql) = &cli.command {
+        ccq::repl::run_script(session, sql, &options)?
+    } else if std::io::stdin().is_terminal() {
+        ccq::repl::start_interactive(session, &options)?;
+        return Ok(ExitCode::SUCCESS);
     } else {
-        ccq::repl::run_piped(&session)
+        ccq::repl::run_piped(session, &options)?
+    };
+
+    if let Some(path) = &cli.summary_json {
+        if path.as_os_str() == "-" {
+            eprintln!("{}", summary.to_json());
+        } else {
+            std::fs::write(path, summary.to_json() + "\n")?;
+        }
+    }
+
+    if summary.terminated {
+        return Ok(ExitCode::from(143));
+    }
+    if cli.check {
+        return Ok(exit_code(summary.errors == 0));
+    }
+    let checked = cli.fail_if_empty || cli.fail_if_rows;
+    let failed = (checked && summary.errors > 0)
+        || (cli.fail_if_empty && summary.rows == 0)
+        || (cli.fail_if_rows && summary.rows > 0);
+    Ok(exit_code(!failed))
+}
+
+const fn exit_code(ok: bool) -> ExitCode {
+    if ok {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
     }
 }
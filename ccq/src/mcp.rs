@@ -0,0 +1,268 @@
+//! Model Context Protocol server over stdio, so agents can query past sessions.
+//!
+//! Speaks newline-delimited JSON-RPC 2.0 and offers three tools:
+//! - `run_query` — run SQL, returns `{"columns", "row_count", "rows"}`
+//! - `list_views` — the available views, with summaries for built-in ones
+//! - `describe_view` — a view's columns and, for built-in views, its help
+//!
+//! Register with Claude Code via `claude mcp add ccq -- ccq mcp`.
+
+use std::fmt::Write as _;
+use std::io::{self, BufRead, Write};
+
+use serde_json::{json, Value};
+
+use crate::utils::sql_ident;
+use crate::{help, QuerySession, Result};
+
+/// Protocol revision answered when the client doesn't name one.
+const PROTOCOL_VERSION: &str = "2024-11-05";
+
+/// Rows returned by `run_query` unless the call asks for more.
+const DEFAULT_MAX_ROWS: usize = 200;
+
+/// JSON-RPC error codes.
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+/// Text returned by a tool call, and whether it reports a failure.
+type ToolOutput = std::result::Result<String, String>;
+
+/// Answer requests on stdin until it closes.
+///
+/// Requests are handled one at a time on the session's connection; nothing
+/// but protocol messages is written to stdout.
+///
+/// # Errors
+/// Returns error if stdin can't be read or stdout can't be written.
+pub fn serve_stdio(session: &QuerySession) -> Result<()> {
+    let mut stdout = io::stdout().lock();
+    for line in io::stdin().lock().lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(response) = handle(&line, |name, args| call_tool(session, name, args)) {
+            writeln!(stdout, "{response}")?;
+            stdout.flush()?;
+        }
+    }
+    Ok(())
+}
+
+/// Response to one JSON-RPC message; `None` for notifications.
+fn handle(line: &str, call: impl FnOnce(&str, &Value) -> Option<ToolOutput>) -> Option<Value> {
+    let Ok(message) = serde_json::from_str::<Value>(line) else {
+        return Some(rpc_error(&Value::Null, PARSE_ERROR, "parse error"));
+    };
+    let id = message.get("id")?.clone();
+    let Some(method) = message.get("method").and_then(Value::as_str) else {
+        return Some(rpc_error(&id, INVALID_REQUEST, "missing method"));
+    };
+    let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+    let result = match method {
+        "initialize" => json!({
+            "protocolVersion": params
+                .get("protocolVersion")
+                .and_then(Value::as_str)
+                .unwrap_or(PROTOCOL_VERSION),
+            "capabilities": { "tools": {} },
+            "serverInfo": { "name": "ccq", "version": env!("CARGO_PKG_VERSION") },
+        }),
+        "ping" => json!({}),
+        "tools/list" => json!({ "tools": tools() }),
+        "tools/call" => {
+            let name = params.get("name").and_then(Value::as_str).unwrap_or_default();
+            let args = params.get("arguments").cloned().unwrap_or_else(|| json!({}));
+            let Some(output) = call(name, &args) else {
+                return Some(rpc_error(&id, INVALID_PARAMS, &format!("unknown tool: {name}")));
+            };
+            let (text, is_error) = match output {
+                Ok(text) => (text, false),
+                Err(text) => (text, true),
+            };
+            json!({ "content": [{ "type": "text", "text": text }], "isError": is_error })
+        }
+        _ => return Some(rpc_error(&id, METHOD_NOT_FOUND, &format!("unknown method: {method}"))),
+    };
+    Some(json!({ "jsonrpc": "2.0", "id": id, "result": result }))
+}
+
+fn rpc_error(id: &Value, code: i64, message: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+/// Tool definitions for `tools/list`.
+fn tools() -> Value {
+    json!([
+        {
+            "name": "run_query",
+            "description": "Run a DuckDB SQL query over Claude Code session history. \
+                            Use list_views and describe_view to find tables and columns.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "sql": { "type": "string", "description": "SQL to run" },
+                    "max_rows": {
+                        "type": "integer",
+                        "description": format!("Rows to return (default {DEFAULT_MAX_ROWS})"),
+                    },
+                },
+                "required": ["sql"],
+            },
+        },
+        {
+            "name": "list_views",
+            "description": "List the views available to run_query, with a summary of each.",
+            "inputSchema": { "type": "object", "properties": {} },
+        },
+        {
+            "name": "describe_view",
+            "description": "Show a view's columns, what a row means, and example queries.",
+            "inputSchema": {
+                "type": "object",
+                "properties": { "name": { "type": "string", "description": "View name" } },
+                "required": ["name"],
+            },
+        },
+    ])
+}
+
+/// Run tool `name`; `None` if there's no such tool.
+fn call_tool(session: &QuerySession, name: &str, args: &Value) -> Option<ToolOutput> {
+    let text_arg = |key: &str| {
+        args.get(key)
+            .and_then(Value::as_str)
+            .filter(|s| !s.trim().is_empty())
+            .ok_or_else(|| format!("missing \"{key}\" argument"))
+    };
+    let output = match name {
+        "run_query" => text_arg("sql").and_then(|sql| {
+            let max_rows = args
+                .get("max_rows")
+                .and_then(Value::as_u64)
+                .and_then(|n| usize::try_from(n).ok())
+                .unwrap_or(DEFAULT_MAX_ROWS);
+            run_query(session, sql, max_rows)
+        }),
+        "list_views" => list_views(session),
+        "describe_view" => text_arg("name").and_then(|view| describe_view(session, view)),
+        _ => return None,
+    };
+    Some(output)
+}
+
+fn run_query(session: &QuerySession, sql: &str, max_rows: usize) -> ToolOutput {
+    let mut result = session.query_json(sql).map_err(|e| e.to_string())?;
+    truncate_rows(&mut result, max_rows);
+    Ok(result.to_string())
+}
+
+/// Keep the first `max_rows` rows of a `query_json` result, noting the cut.
+fn truncate_rows(result: &mut Value, max_rows: usize) {
+    let Some(rows) = result.get_mut("rows").and_then(Value::as_array_mut) else {
+        return;
+    };
+    if rows.len() > max_rows {
+        rows.truncate(max_rows);
+        result["truncated"] = json!(true);
+    }
+}
+
+fn list_views(session: &QuerySession) -> ToolOutput {
+    let sql = "SELECT view_name FROM duckdb_views() WHERE NOT internal ORDER BY view_name";
+    let result = session.query(sql).map_err(|e| e.to_string())?;
+    let views: Vec<Value> = result
+        .rows()
+        .iter()
+        .map(|row| {
+            let summary = help::topic(&row[0]).map(|t| t.summary);
+            json!({ "name": row[0], "summary": summary })
+        })
+        .collect();
+    Ok(json!({ "views": views }).to_string())
+}
+
+fn describe_view(session: &QuerySession, name: &str) -> ToolOutput {
+    let result = session
+        .query(&format!("DESCRIBE {}", sql_ident(name)))
+        .map_err(|e| e.to_string())?;
+    let mut text = help::topic(name).map_or_else(String::new, |t| help::describe(t) + "\n\n");
+    text.push_str("Columns:\n");
+    for row in result.rows() {
+        let _ = writeln!(text, "  {} {}", row[0], row[1]);
+    }
+    if let Some(topic) = help::topic(name) {
+        let _ = write!(text, "\nExamples:\n{}", help::format_examples(topic.examples));
+    }
+    Ok(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn no_tools(_: &str, _: &Value) -> Option<ToolOutput> {
+        None
+    }
+
+    #[test]
+    fn test_handle_initialize_and_list() {
+        let init = concat!(
+            r#"{"jsonrpc":"2.0","id":1,"method":"initialize","#,
+            r#""params":{"protocolVersion":"2025-03-26"}}"#
+        );
+        let response = handle(init, no_tools).unwrap();
+        assert_eq!(response["id"], 1);
+        assert_eq!(response["result"]["protocolVersion"], "2025-03-26");
+        assert_eq!(response["result"]["serverInfo"]["name"], "ccq");
+
+        let list = handle(r#"{"jsonrpc":"2.0","id":"a","method":"tools/list"}"#, no_tools).unwrap();
+        let names: Vec<&str> = list["result"]["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|t| t["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, ["run_query", "list_views", "describe_view"]);
+    }
+
+    #[test]
+    fn test_handle_notifications_and_errors() {
+        let initialized = r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#;
+        assert!(handle(initialized, no_tools).is_none());
+        assert_eq!(handle("{not json", no_tools).unwrap()["error"]["code"], PARSE_ERROR);
+        let unknown = handle(r#"{"jsonrpc":"2.0","id":2,"method":"nope"}"#, no_tools).unwrap();
+        assert_eq!(unknown["error"]["code"], METHOD_NOT_FOUND);
+        let call = r#"{"jsonrpc":"2.0","id":3,"method":"tools/call","params":{"name":"nope"}}"#;
+        assert_eq!(handle(call, no_tools).unwrap()["error"]["code"], INVALID_PARAMS);
+    }
+
+    #[test]
+    fn test_handle_tool_call_result() {
+        let call = r#"{"jsonrpc":"2.0","id":4,"method":"tools/call",
+                       "params":{"name":"run_query","arguments":{"sql":"SELECT 1"}}}"#
+            .replace('\n', "");
+        let response = handle(&call, |name, args| {
+            assert_eq!(name, "run_query");
+            assert_eq!(args["sql"], "SELECT 1");
+            Some(Err("boom".to_string()))
+        })
+        .unwrap();
+        assert_eq!(response["result"]["content"][0]["text"], "boom");
+        assert_eq!(response["result"]["isError"], true);
+    }
+
+    #[test]
+    fn test_truncate_rows() {
+        let mut result = json!({ "columns": ["n"], "row_count": 3, "rows": [[1], [2], [3]] });
+        truncate_rows(&mut result, 5);
+        assert!(result.get("truncated").is_none());
+        truncate_rows(&mut result, 2);
+        assert_eq!(result["rows"], json!([[1], [2]]));
+        assert_eq!(result["truncated"], true);
+    }
+}
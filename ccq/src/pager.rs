@@ -0,0 +1,61 @@
+//! Page long REPL output through `$PAGER`.
+
+use std::io::{self, IsTerminal, Write};
+use std::process::{Command, Stdio};
+
+/// Used when `$PAGER` is unset or empty; `-S` chops wide tables instead of
+/// wrapping and `-R` passes colors through.
+pub const DEFAULT_PAGER: &str = "less -SR";
+
+/// Whether `text` is taller than a terminal of `height` rows (leaving a
+/// row for the prompt).
+pub fn needs_paging(text: &str, height: u16) -> bool {
+    text.lines().count() >= usize::from(height)
+}
+
+/// Print `text`, through the pager when stdout is a terminal and the text
+/// doesn't fit on screen.
+///
+/// # Errors
+/// Returns error if the pager can't be started or written to.
+pub fn print(text: &str) -> io::Result<()> {
+    let fits = ratatui::crossterm::terminal::size()
+        .map_or(true, |(_, height)| !needs_paging(text, height));
+    if fits || !io::stdout().is_terminal() {
+        println!("{text}");
+        return Ok(());
+    }
+
+    let command = std::env::var("PAGER")
+        .ok()
+        .filter(|p| !p.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_PAGER.to_string());
+    let mut parts = command.split_whitespace();
+    let program = parts.next().unwrap_or("less");
+
+    let mut child = Command::new(program)
+        .args(parts)
+        .stdin(Stdio::piped())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // Quitting the pager early closes its stdin; that's not an error
+        match writeln!(stdin, "{text}") {
+            Err(e) if e.kind() != io::ErrorKind::BrokenPipe => return Err(e),
+            _ => {}
+        }
+    }
+    child.wait()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_needs_paging() {
+        assert!(!needs_paging("a\nb", 24));
+        assert!(needs_paging(&"row\n".repeat(30), 24));
+        assert!(needs_paging(&"row\n".repeat(24), 24));
+    }
+}
@@ -0,0 +1,118 @@
+//! Indented rendering of `DuckDB`'s JSON query plans (`.explain`).
+
+use std::fmt::Write;
+
+use serde_json::Value;
+
+use crate::formatter::truncate_cell;
+
+/// Longest operator detail shown before it's cut with `…`.
+const DETAIL_CHARS: usize = 80;
+
+/// Render the JSON from `EXPLAIN (FORMAT JSON)` or `EXPLAIN (ANALYZE, FORMAT
+/// JSON)` as an indented operator tree; `None` if it isn't a JSON plan.
+pub fn render(json: &str) -> Option<String> {
+    let value: Value = serde_json::from_str(json).ok()?;
+    let mut out = String::new();
+    match &value {
+        Value::Array(nodes) => {
+            for node in nodes {
+                write_node(&mut out, node, 0);
+            }
+        }
+        Value::Object(_) => write_node(&mut out, &value, 0),
+        _ => return None,
+    }
+    Some(out.trim_end().to_string())
+}
+
+fn write_node(out: &mut String, node: &Value, depth: usize) {
+    let name = ["name", "operator_name", "operator_type"]
+        .iter()
+        .find_map(|key| node.get(*key).and_then(Value::as_str))
+        .map(str::trim)
+        .filter(|name| !name.is_empty());
+    let children = node.get("children").and_then(Value::as_array);
+
+    let Some(name) = name else {
+        // The ANALYZE root is a query summary rather than an operator
+        if let Some(latency) = node.get("latency").and_then(Value::as_f64) {
+            let _ = writeln!(out, "Total: {latency:.3}s");
+        }
+        for child in children.into_iter().flatten() {
+            write_node(out, child, depth);
+        }
+        return;
+    };
+
+    let _ = write!(out, "{}{name}", "  ".repeat(depth));
+    let timing = node.get("operator_timing").and_then(Value::as_f64);
+    let rows = node.get("operator_cardinality").and_then(Value::as_u64);
+    match (timing, rows) {
+        (Some(timing), Some(rows)) => {
+            let _ = write!(out, " ({timing:.3}s, {rows} rows)");
+        }
+        (None, Some(rows)) => {
+            let _ = write!(out, " ({rows} rows)");
+        }
+        _ => {}
+    }
+    if let Some(extra) = node.get("extra_info").map(details).filter(|d| !d.is_empty()) {
+        let _ = write!(out, "  [{}]", truncate_cell(&extra, DETAIL_CHARS));
+    }
+    out.push('\n');
+    for child in children.into_iter().flatten() {
+        write_node(out, child, depth + 1);
+    }
+}
+
+/// `extra_info` on one line: `key: value; ...`, list values comma-separated.
+fn details(extra: &Value) -> String {
+    let text = |value: &Value| match value {
+        Value::String(s) => s.clone(),
+        Value::Array(items) => items
+            .iter()
+            .map(|i| i.as_str().map_or_else(|| i.to_string(), str::to_string))
+            .collect::<Vec<_>>()
+            .join(", "),
+        other => other.to_string(),
+    };
+    let joined = match extra {
+        Value::Object(fields) => fields
+            .iter()
+            .map(|(key, value)| format!("{key}: {}", text(value)))
+            .collect::<Vec<_>>()
+            .join("; "),
+        other => text(other),
+    };
+    joined.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_explain_tree() {
+        let json = r##"[{"name": "PROJECTION", "children": [
+            {"name": "HASH_GROUP_BY", "extra_info": {"Groups": "#0", "Aggregates": ["count_star()"]},
+             "children": [{"name": "READ_JSON", "children": [], "extra_info": {}}]}
+        ], "extra_info": {"Projections": "type"}}]"##;
+        assert_eq!(
+            render(json).unwrap(),
+            "PROJECTION  [Projections: type]\n  \
+             HASH_GROUP_BY  [Groups: #0; Aggregates: count_star()]\n    \
+             READ_JSON"
+        );
+    }
+
+    #[test]
+    fn test_render_analyze_tree() {
+        let json = r#"{"latency": 0.5, "children": [
+            {"operator_name": "PROJECTION ", "operator_timing": 0.0012, "operator_cardinality": 3,
+             "children": []}
+        ]}"#;
+        assert_eq!(render(json).unwrap(), "Total: 0.500s\nPROJECTION (0.001s, 3 rows)");
+        assert!(render("┌─────┐").is_none());
+    }
+}
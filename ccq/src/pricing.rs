@@ -0,0 +1,145 @@
+//! Estimated API prices per model, for turning token counts into dollars.
+
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+
+use crate::utils::sql_quote;
+
+/// USD per million tokens for models whose name starts with `prefix`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ModelPrice {
+    prefix: &'static str,
+    input: f64,
+    output: f64,
+    cache_write: f64,
+    cache_read: f64,
+}
+
+const fn price(
+    prefix: &'static str,
+    input: f64,
+    output: f64,
+    cache_write: f64,
+    cache_read: f64,
+) -> ModelPrice {
+    ModelPrice {
+        prefix,
+        input,
+        output,
+        cache_write,
+        cache_read,
+    }
+}
+
+/// Published list prices (input, output, 5-minute cache write, cache read).
+/// The costs view prices each model by the longest prefix it starts with.
+const PRICES: &[ModelPrice] = &[
+    price("claude-opus-4-5", 5.0, 25.0, 6.25, 0.5),
+    price("claude-opus-4", 15.0, 75.0, 18.75, 1.5),
+    price("claude-3-opus", 15.0, 75.0, 18.75, 1.5),
+    price("claude-sonnet-4", 3.0, 15.0, 3.75, 0.3),
+    price("claude-3-7-sonnet", 3.0, 15.0, 3.75, 0.3),
+    price("claude-3-5-sonnet", 3.0, 15.0, 3.75, 0.3),
+    price("claude-haiku-4-5", 1.0, 5.0, 1.25, 0.1),
+    price("claude-3-5-haiku", 0.8, 4.0, 1.0, 0.08),
+    price("claude-3-haiku", 0.25, 1.25, 0.3, 0.03),
+];
+
+/// A `[pricing]` config entry: USD per million tokens for models whose name
+/// starts with its key. Cache rates default to 1.25x (write) and 0.1x (read)
+/// the input rate, as in the published prices.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct ModelRates {
+    pub input: f64,
+    pub output: f64,
+    pub cache_write: Option<f64>,
+    pub cache_read: Option<f64>,
+}
+
+/// SQL creating the `model_pricing` table from the list prices, with `overrides`
+/// replacing built-in prefixes of the same name or adding new ones.
+pub fn table_sql(overrides: &BTreeMap<String, ModelRates>) -> String {
+    let built_in = PRICES
+        .iter()
+        .filter(|p| !overrides.contains_key(p.prefix))
+        .map(|p| (p.prefix, p.input, p.output, p.cache_write, p.cache_read));
+    let configured = overrides.iter().map(|(prefix, r)| {
+        let cache_write = r.cache_write.unwrap_or(r.input * 1.25);
+        let cache_read = r.cache_read.unwrap_or(r.input * 0.1);
+        (prefix.as_str(), r.input, r.output, cache_write, cache_read)
+    });
+    let rows = built_in
+        .chain(configured)
+        .map(|(prefix, input, output, cache_write, cache_read)| {
+            format!(
+                "      ({}, {input}, {output}, {cache_write}, {cache_read})",
+                sql_quote(prefix)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(",\n");
+    format!(
+        "    -- Model pricing: USD per million tokens by model-name prefix\n    \
+         CREATE OR REPLACE TABLE model_pricing (\n      \
+         prefix VARCHAR, input DOUBLE, output DOUBLE, cache_write DOUBLE, cache_read DOUBLE\n    \
+         );\n    \
+         INSERT INTO model_pricing VALUES\n{rows};"
+    )
+}
+
+/// Tokens and estimated cost per model over everything loaded, costliest first.
+pub const SUMMARY_SQL: &str = "SELECT coalesce(model, 'unknown') AS model,
+       count(*) AS messages,
+       sum(input_tokens) AS input_tokens,
+       sum(output_tokens) AS output_tokens,
+       sum(cache_creation_tokens) AS cache_write_tokens,
+       sum(cache_read_tokens) AS cache_read_tokens,
+       round(sum(cost_usd), 2) AS cost_usd
+FROM costs
+WHERE model IS DISTINCT FROM '<synthetic>'
+GROUP BY ALL
+ORDER BY cost_usd DESC NULLS LAST, model";
+
+/// Tokens and estimated cost per account type and model, for `.cost account`.
+///
+/// Session logs carry no account or API key identity; `userType` (normally
+/// `external`) is the only account-level field, so this is as fine as the
+/// split gets without matching `requestId` against provider billing exports.
+pub const ACCOUNT_SUMMARY_SQL: &str = "SELECT coalesce(userType, 'unknown') AS userType,
+       coalesce(model, 'unknown') AS model,
+       count(*) AS messages,
+       count(DISTINCT sessionId) AS sessions,
+       sum(input_tokens) AS input_tokens,
+       sum(output_tokens) AS output_tokens,
+       round(sum(cost_usd), 2) AS cost_usd
+FROM costs
+WHERE model IS DISTINCT FROM '<synthetic>'
+GROUP BY ALL
+ORDER BY userType, cost_usd DESC NULLS LAST, model";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_table_sql_applies_overrides() {
+        let rates = |input, cache_read| ModelRates {
+            input,
+            output: 10.0,
+            cache_write: None,
+            cache_read,
+        };
+        let overrides = BTreeMap::from([
+            ("claude-opus-4-5".to_string(), rates(4.0, Some(0.25))),
+            ("my-proxy".to_string(), rates(2.0, None)),
+        ]);
+        let sql = table_sql(&overrides);
+        assert!(sql.contains("CREATE OR REPLACE TABLE model_pricing"));
+        assert!(sql.contains("('claude-opus-4', 15, 75, 18.75, 1.5)"));
+        assert!(sql.contains("('claude-opus-4-5', 4, 10, 5, 0.25)"));
+        assert!(!sql.contains("('claude-opus-4-5', 5,"));
+        assert!(sql.contains("('my-proxy', 2, 10, 2.5, 0.2)"));
+        assert!(sql.ends_with(");"));
+    }
+}
@@ -1,18 +1,72 @@
 //! `DuckDB` query session management.
 
+use std::cell::Cell;
+use std::fmt::Write as _;
+use std::collections::BTreeMap;
 use std::io::Write;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-use duckdb::Connection;
+use chrono_tz::Tz;
+use duckdb::{Connection, InterruptHandle};
 
+use crate::pricing::{self, ModelRates};
 use crate::session_loader::{self, FilePattern, SessionInfo};
+use crate::utils::{sql_ident, sql_quote};
 use crate::{formatter, Error, Result};
 
+/// Explicit column schema applied when reading JSONL: (JSON key, `DuckDB` type).
+pub const SCHEMA_COLUMNS: &[(&str, &str)] = &[
+    ("uuid", "UUID"),
+    ("type", "VARCHAR"),
+    ("subtype", "VARCHAR"),
+    ("parentUuid", "UUID"),
+    ("timestamp", "TIMESTAMP"),
+    ("sessionId", "UUID"),
+    ("cwd", "VARCHAR"),
+    ("gitBranch", "VARCHAR"),
+    ("slug", "VARCHAR"),
+    ("version", "VARCHAR"),
+    ("isSidechain", "BOOLEAN"),
+    ("userType", "VARCHAR"),
+    ("message", "JSON"),
+    ("isCompactSummary", "BOOLEAN"),
+    ("isMeta", "BOOLEAN"),
+    ("isVisibleInTranscriptOnly", "BOOLEAN"),
+    ("sourceToolUseID", "VARCHAR"),
+    ("thinkingMetadata", "JSON"),
+    ("todos", "JSON"),
+    ("toolUseResult", "JSON"),
+    ("error", "JSON"),
+    ("isApiErrorMessage", "BOOLEAN"),
+    ("requestId", "VARCHAR"),
+    ("sourceToolAssistantUUID", "UUID"),
+    ("content", "VARCHAR"),
+    ("compactMetadata", "JSON"),
+    ("hasOutput", "BOOLEAN"),
+    ("hookCount", "INTEGER"),
+    ("hookErrors", "JSON"),
+    ("hookInfos", "JSON"),
+    ("level", "VARCHAR"),
+    ("logicalParentUuid", "UUID"),
+    ("maxRetries", "INTEGER"),
+    ("preventedContinuation", "BOOLEAN"),
+    ("retryAttempt", "INTEGER"),
+    ("retryInMs", "INTEGER"),
+    ("stopReason", "VARCHAR"),
+    ("toolUseID", "VARCHAR"),
+];
+
 /// Query result with column names and row data.
 #[derive(Debug, Clone)]
 pub struct QueryResult {
     columns: Vec<String>,
     rows: Vec<Vec<String>>,
+    /// Per row: which values are NULL, as their text can't tell
+    nulls: Vec<Vec<bool>>,
+    /// Per column: whether its values are numbers
+    numeric: Vec<bool>,
 }
 
 impl QueryResult {
@@ -31,21 +85,105 @@ impl QueryResult {
         self.rows.len()
     }
 
+    /// A result from rows gathered elsewhere (see [`QuerySession::query_each`]).
+    pub const fn from_parts(
+        columns: Vec<String>,
+        rows: Vec<Vec<String>>,
+        nulls: Vec<Vec<bool>>,
+        numeric: Vec<bool>,
+    ) -> Self {
+        Self {
+            columns,
+            rows,
+            nulls,
+            numeric,
+        }
+    }
+
+    /// The first `n` rows, with the same columns.
+    #[must_use]
+    pub fn head(&self, n: usize) -> Self {
+        Self {
+            columns: self.columns.clone(),
+            rows: self.rows.iter().take(n).cloned().collect(),
+            nulls: self.nulls.iter().take(n).cloned().collect(),
+            numeric: self.numeric.clone(),
+        }
+    }
+
     /// Format as a table with Unicode box-drawing characters.
     pub fn to_table(&self) -> String {
         formatter::format_table(&self.columns, &self.rows)
     }
 
+    /// Format as a table with explicit rendering options.
+    pub fn to_table_with(&self, options: &formatter::TableOptions) -> String {
+        let rows = options.display_rows(&self.columns, &self.rows, &self.nulls, &self.numeric);
+        formatter::format_table_typed(&self.columns, &rows, &self.nulls, &self.numeric, options)
+    }
+
+    /// Format one record per row (`.x`), with number formatting applied.
+    pub fn to_expanded(&self, options: &formatter::TableOptions) -> String {
+        let rows = options.display_rows(&self.columns, &self.rows, &self.nulls, &self.numeric);
+        formatter::format_expanded(&self.columns, &rows, options)
+    }
+
     /// Format as tab-separated values.
     pub fn to_tsv(&self) -> String {
         formatter::format_tsv(&self.columns, &self.rows)
     }
+
+    /// Format in any output mode. Number formatting and the NULL text apply
+    /// to the human-oriented modes (table, markdown, line) only.
+    pub fn render(&self, mode: formatter::OutputMode, options: &formatter::TableOptions) -> String {
+        use formatter::OutputMode;
+
+        let display_rows =
+            || options.display_rows(&self.columns, &self.rows, &self.nulls, &self.numeric);
+        match mode {
+            OutputMode::Table => self.to_table_with(options),
+            OutputMode::Tsv => self.to_tsv(),
+            OutputMode::Csv => formatter::format_csv(&self.columns, &self.rows),
+            OutputMode::Json => {
+                formatter::format_json(&self.columns, &self.rows, &self.nulls, &self.numeric)
+            }
+            OutputMode::Markdown => {
+                formatter::format_markdown(&self.columns, &display_rows(), &self.numeric)
+            }
+            OutputMode::Line => formatter::format_line(&self.columns, &display_rows()),
+        }
+    }
+}
+
+/// Options controlling which session data is loaded into the views.
+#[derive(Debug, Clone, Default)]
+pub struct SessionOptions {
+    /// Project path (omit for all projects)
+    pub project_dir: Option<PathBuf>,
+    /// Session ID prefix filter
+    pub session_filter: Option<String>,
+    /// Directories used directly as the JSONL data source, merged
+    pub data_dirs: Vec<PathBuf>,
+    /// Only include messages recorded on this git branch
+    pub branch: Option<String>,
+    /// Extra `(name, SELECT)` views created after the built-in ones
+    pub views: Vec<(String, String)>,
+    /// Skip the upfront file walk; counts are computed on demand
+    pub no_count: bool,
+    /// Retry a query once with fewer threads when `DuckDB` runs out of memory
+    pub retry_oom: bool,
+    /// `model_pricing` entries added to or replacing the built-in prices
+    pub pricing: BTreeMap<String, ModelRates>,
 }
 
 /// `DuckDB` session with pre-configured views over JSONL session data.
 pub struct QuerySession {
     conn: Connection,
     info: SessionInfo,
+    options: SessionOptions,
+    view_setup_time: Duration,
+    /// Zone timestamps are shown in; stored timestamps are UTC
+    time_zone: Cell<Option<Tz>>,
 }
 
 impl QuerySession {
@@ -58,22 +196,171 @@ impl QuerySession {
         session_filter: Option<&str>,
         data_dir: Option<&Path>,
     ) -> Result<Self> {
-        let info = session_loader::get_session_files(project_dir, session_filter, data_dir)?;
+        Self::with_options(SessionOptions {
+            project_dir: project_dir.map(Path::to_path_buf),
+            session_filter: session_filter.map(str::to_string),
+            data_dirs: data_dir.map(Path::to_path_buf).into_iter().collect(),
+            ..SessionOptions::default()
+        })
+    }
+
+    /// Create a new query session from options.
+    ///
+    /// # Errors
+    /// Returns error if no sessions are found or database setup fails.
+    pub fn with_options(options: SessionOptions) -> Result<Self> {
+        let info = Self::discover(&options)?;
+        let conn = Connection::open_in_memory()?;
+        let started = Instant::now();
+        let sql = Self::build_create_views_sql(info.file_pattern(), &options);
+        conn.execute_batch(&sql)?;
+        let view_setup_time = started.elapsed();
 
-        if info.session_count() == 0 {
+        Ok(Self {
+            conn,
+            info,
+            options,
+            view_setup_time,
+            time_zone: Cell::new(None),
+        })
+    }
+
+    /// Narrow (or widen, with `None`) the session filter: re-run discovery
+    /// and recreate the views on the same connection, so temp tables and
+    /// user-defined views survive. On error the current scope is kept.
+    ///
+    /// # Errors
+    /// Returns error if no sessions match or the views can't be rebuilt.
+    pub fn rescope(&mut self, session_filter: Option<String>) -> Result<()> {
+        let options = SessionOptions {
+            session_filter,
+            ..self.options.clone()
+        };
+        let info = Self::discover(&options).map_err(|e| match (e, &options.session_filter) {
+            (Error::NoSessions { .. }, Some(prefix)) => Error::SessionNotFound(prefix.clone()),
+            (e, _) => e,
+        })?;
+        let started = Instant::now();
+        let sql = Self::build_create_views_sql(info.file_pattern(), &options);
+        // One transaction, so a view that fails part-way leaves the old ones in place
+        self.conn.execute_batch("BEGIN TRANSACTION")?;
+        if let Err(e) = self.conn.execute_batch(&sql) {
+            let _ = self.conn.execute_batch("ROLLBACK");
+            return Err(e.into());
+        }
+        self.conn.execute_batch("COMMIT")?;
+        self.view_setup_time = started.elapsed();
+        self.info = info;
+        self.options = options;
+        Ok(())
+    }
+
+    /// Re-run discovery with the current scope and recreate the views, picking
+    /// up session files written since the session was opened.
+    ///
+    /// # Errors
+    /// Returns error if discovery or view creation fails.
+    pub fn reload(&mut self) -> Result<()> {
+        self.rescope(self.options.session_filter.clone())
+    }
+
+    /// Find the session files `options` select.
+    fn discover(options: &SessionOptions) -> Result<SessionInfo> {
+        let info = if options.no_count {
+            session_loader::get_session_files_uncounted(
+                options.project_dir.as_deref(),
+                options.session_filter.as_deref(),
+                &options.data_dirs,
+            )
+        } else {
+            session_loader::get_session_files(
+                options.project_dir.as_deref(),
+                options.session_filter.as_deref(),
+                &options.data_dirs,
+            )?
+        };
+
+        let empty = if info.is_counted() {
+            info.session_count() == 0
+        } else {
+            info.file_pattern().patterns().is_empty()
+        };
+        if empty {
             return Err(Error::NoSessions {
-                path: data_dir.map_or_else(
-                    || project_dir.map(Path::to_path_buf).unwrap_or_default(),
-                    Path::to_path_buf,
-                ),
+                path: options
+                    .data_dirs
+                    .first()
+                    .cloned()
+                    .or_else(|| options.project_dir.clone())
+                    .unwrap_or_default(),
             });
         }
+        Ok(info)
+    }
+
+    /// Time spent creating the views.
+    pub const fn view_setup_time(&self) -> Duration {
+        self.view_setup_time
+    }
+
+    /// The zone query results show timestamps in; `None` for UTC.
+    pub const fn time_zone(&self) -> Option<Tz> {
+        self.time_zone.get()
+    }
+
+    /// Show timestamps in query results in `zone` (`None` for UTC). Only
+    /// the text changes: SQL still sees the stored UTC values.
+    pub fn set_time_zone(&self, zone: Option<Tz>) {
+        self.time_zone.set(zone);
+    }
+
+    /// Session information with real counts, walking the data directories
+    /// now if the session was opened with `no_count`.
+    ///
+    /// # Errors
+    /// Returns error if discovery fails.
+    pub fn counted_info(&self) -> Result<SessionInfo> {
+        if self.info.is_counted() {
+            return Ok(self.info.clone());
+        }
+        session_loader::get_session_files(
+            self.options.project_dir.as_deref(),
+            self.options.session_filter.as_deref(),
+            &self.options.data_dirs,
+        )
+    }
 
-        let conn = Connection::open_in_memory()?;
-        let sql = Self::build_create_views_sql(info.file_pattern());
-        conn.execute_batch(&sql)?;
+    /// Confine file access to the directories the session data is read from,
+    /// so queries can't read or write other files or install extensions.
+    /// `DuckDB` refuses to re-enable external access once it's off.
+    ///
+    /// # Errors
+    /// Returns error if the settings can't be applied.
+    pub fn restrict_file_access(&self) -> Result<()> {
+        let dirs: Vec<String> = self
+            .info
+            .file_pattern()
+            .root_dirs()
+            .into_iter()
+            .map(sql_quote)
+            .collect();
+        self.conn.execute_batch(&format!(
+            "SET allowed_directories = [{}];
+             SET enable_external_access = false;",
+            dirs.join(", ")
+        ))?;
+        Ok(())
+    }
 
-        Ok(Self { conn, info })
+    /// Handle that cancels the statement currently running on this session,
+    /// usable from another thread.
+    pub fn interrupt_handle(&self) -> Arc<InterruptHandle> {
+        self.conn.interrupt_handle()
+    }
+
+    /// Options this session was created with.
+    pub const fn options(&self) -> &SessionOptions {
+        &self.options
     }
 
     /// Session information (counts, patterns).
@@ -81,11 +368,121 @@ impl QuerySession {
         &self.info
     }
 
+    /// Resolve a session ID prefix to the full session ID.
+    ///
+    /// # Errors
+    /// Returns error if no session or more than one session matches.
+    pub fn resolve_session_id(&self, prefix: &str) -> Result<String> {
+        let result = self.query(&format!(
+            "SELECT DISTINCT sessionId::VARCHAR FROM messages
+             WHERE starts_with(sessionId::VARCHAR, {}) LIMIT 10",
+            sql_quote(&prefix.to_lowercase())
+        ))?;
+        match result.rows() {
+            [] => Err(Error::SessionNotFound(prefix.to_string())),
+            [row] => Ok(row[0].clone()),
+            rows => Err(Error::AmbiguousSession {
+                prefix: prefix.to_string(),
+                matches: rows.len(),
+            }),
+        }
+    }
+
+    /// Parse, bind, and plan a single statement without executing it.
+    ///
+    /// # Errors
+    /// Returns error if the statement doesn't parse or bind.
+    pub fn check(&self, sql: &str) -> Result<()> {
+        self.conn.prepare(sql)?;
+        Ok(())
+    }
+
+    fn should_retry(&self, error: &Error) -> bool {
+        self.options.retry_oom && is_out_of_memory(error)
+    }
+
+    /// Run `f` with a quarter of the threads and insertion order relaxed,
+    /// which lets most aggregations fit in memory, then restore the settings.
+    fn with_reduced_memory<T>(&self, f: impl FnOnce() -> Result<T>) -> Result<T> {
+        let current = self.query_once(
+            "SELECT current_setting('threads'), current_setting('preserve_insertion_order')",
+        )?;
+        let (threads, order) = current.rows().first().map_or_else(
+            || (1, "true".to_string()),
+            |row| (row[0].parse::<usize>().unwrap_or(1), row[1].clone()),
+        );
+        let reduced = (threads / 4).max(1);
+
+        eprintln!("Out of memory; retrying with {reduced} thread(s)");
+        self.conn.execute_batch(&format!(
+            "SET threads = {reduced}; SET preserve_insertion_order = false;"
+        ))?;
+        let result = f();
+        self.conn.execute_batch(&format!(
+            "SET threads = {threads}; SET preserve_insertion_order = {order};"
+        ))?;
+        result
+    }
+
     /// Execute a SQL query and return results.
     ///
     /// # Errors
     /// Returns error if the query fails.
     pub fn query(&self, sql: &str) -> Result<QueryResult> {
+        match self.query_once(sql) {
+            Err(e) if self.should_retry(&e) => self.with_reduced_memory(|| self.query_once(sql)),
+            other => other,
+        }
+    }
+
+    fn query_once(&self, sql: &str) -> Result<QueryResult> {
+        let mut rows = Vec::new();
+        let mut nulls = Vec::new();
+        let (columns, numeric) = self.each_row(sql, |_, _, row, row_nulls| {
+            rows.push(row);
+            nulls.push(row_nulls);
+            Ok(())
+        })?;
+        Ok(QueryResult {
+            columns,
+            rows,
+            nulls,
+            numeric,
+        })
+    }
+
+    /// Execute a SQL query, handing each row and which of its values are
+    /// NULL to `on_row` as it is read rather than collecting them. `on_row`
+    /// also gets the column names and which columns have held numbers so
+    /// far. Returns the column names and final numeric flags, which are
+    /// available even when no rows match.
+    ///
+    /// # Errors
+    /// Returns error if the query fails or `on_row` does.
+    pub fn query_each(
+        &self,
+        sql: &str,
+        mut on_row: impl FnMut(&[String], &[bool], Vec<String>, Vec<bool>) -> Result<()>,
+    ) -> Result<(Vec<String>, Vec<bool>)> {
+        // Only retry if no rows were handed out, so none are seen twice
+        let delivered = std::cell::Cell::new(false);
+        let mut tracked = |columns: &[String], numeric: &[bool], row, nulls| {
+            delivered.set(true);
+            on_row(columns, numeric, row, nulls)
+        };
+        match self.each_row(sql, &mut tracked) {
+            Err(e) if !delivered.get() && self.should_retry(&e) => {
+                self.with_reduced_memory(|| self.each_row(sql, &mut tracked))
+            }
+            other => other,
+        }
+    }
+
+    fn each_row(
+        &self,
+        sql: &str,
+        mut on_row: impl FnMut(&[String], &[bool], Vec<String>, Vec<bool>) -> Result<()>,
+    ) -> Result<(Vec<String>, Vec<bool>)> {
         let mut stmt = self.conn.prepare(sql)?;
 
         // Execute query first
@@ -106,19 +503,69 @@ impl QuerySession {
             })
             .collect();
 
-        // Collect rows
-        let mut rows = Vec::new();
-
+        let zone = self.time_zone.get();
+        let mut numeric = vec![false; column_count];
         while let Some(row) = rows_iter.next()? {
             let mut row_data = Vec::with_capacity(column_count);
-            for i in 0..column_count {
+            let mut row_nulls = Vec::with_capacity(column_count);
+            for (i, is_numeric) in numeric.iter_mut().enumerate() {
                 // Use DisplayValueRef to avoid intermediate Value allocation
-                row_data.push(formatter::DisplayValueRef(&row.get_ref(i)?).to_string());
+                let value = row.get_ref(i)?;
+                *is_numeric |= formatter::is_numeric(&value);
+                row_nulls.push(matches!(value, duckdb::types::ValueRef::Null));
+                row_data.push(zone.map_or_else(
+                    || formatter::DisplayValueRef(&value).to_string(),
+                    |zone| formatter::display_in_zone(&value, zone),
+                ));
             }
-            rows.push(row_data);
+            on_row(&columns, &numeric, row_data, row_nulls)?;
+        }
+        Ok((columns, numeric))
+    }
+
+    /// Execute a SQL query and return `{"columns": [...], "rows": [[...]]}`
+    /// with each value converted to its natural JSON type.
+    ///
+    /// # Errors
+    /// Returns error if the query fails.
+    pub fn query_json(&self, sql: &str) -> Result<serde_json::Value> {
+        match self.query_json_once(sql) {
+            Err(e) if self.should_retry(&e) => {
+                self.with_reduced_memory(|| self.query_json_once(sql))
+            }
+            other => other,
+        }
+    }
+
+    fn query_json_once(&self, sql: &str) -> Result<serde_json::Value> {
+        let mut stmt = self.conn.prepare(sql)?;
+        let mut rows_iter = stmt.query([])?;
+        let column_count = rows_iter
+            .as_ref()
+            .map_or(0, duckdb::Statement::column_count);
+
+        let columns: Vec<String> = (0..column_count)
+            .map(|i| {
+                rows_iter
+                    .as_ref()
+                    .and_then(|s| s.column_name(i).ok())
+                    .map_or_else(|| "?".to_string(), String::clone)
+            })
+            .collect();
+
+        let mut rows = Vec::new();
+        while let Some(row) = rows_iter.next()? {
+            let values = (0..column_count)
+                .map(|i| Ok(formatter::json_value(&row.get_ref(i)?)))
+                .collect::<Result<Vec<_>>>()?;
+            rows.push(serde_json::Value::Array(values));
         }
 
-        Ok(QueryResult { columns, rows })
+        Ok(serde_json::json!({
+            "columns": columns,
+            "row_count": rows.len(),
+            "rows": rows,
+        }))
     }
 
     /// Execute a SQL query and stream TSV results directly to a writer.
@@ -129,6 +576,17 @@ impl QuerySession {
     /// # Errors
     /// Returns error if the query fails or writing fails.
     pub fn query_tsv_streaming<W: Write>(&self, sql: &str, mut writer: W) -> Result<usize> {
+        // Only retry if nothing was written, so output is never duplicated
+        let mut wrote = false;
+        match self.stream_tsv(sql, &mut writer, &mut wrote) {
+            Err(e) if !wrote && self.should_retry(&e) => {
+                self.with_reduced_memory(|| self.stream_tsv(sql, &mut writer, &mut wrote))
+            }
+            other => other,
+        }
+    }
+
+    fn stream_tsv<W: Write>(&self, sql: &str, mut writer: W, wrote: &mut bool) -> Result<usize> {
         let mut stmt = self.conn.prepare(sql)?;
         let mut rows_iter = stmt.query([])?;
         let column_count = rows_iter
@@ -145,6 +603,7 @@ impl QuerySession {
             })
             .collect();
         writeln!(writer, "{}", columns.join("\t"))?;
+        *wrote = true;
 
         // Stream rows - no per-cell allocations!
         let mut row_count = 0;
@@ -162,55 +621,34 @@ impl QuerySession {
         Ok(row_count)
     }
 
-    /// Generate SQL to create all 11 views.
+    /// Generate SQL to create all 42 views.
     #[allow(clippy::too_many_lines)]
-    fn build_create_views_sql(pattern: &FilePattern) -> String {
+    fn build_create_views_sql(pattern: &FilePattern, options: &SessionOptions) -> String {
         let pattern_sql = pattern.to_string();
 
+        // Optional scoping applied to the base views; everything else derives from them
+        let (branch_filter, raw_branch_filter) = options.branch.as_deref().map_or_else(
+            || (String::new(), String::new()),
+            |branch| {
+                let branch = sql_quote(branch);
+                (
+                    format!("\n      AND gitBranch = {branch}"),
+                    format!("\n      AND json->>'gitBranch' = {branch}"),
+                )
+            },
+        );
+
         // Explicit column schema for type safety
-        let columns_def = [
-            "'uuid': 'UUID'",
-            "'type': 'VARCHAR'",
-            "'subtype': 'VARCHAR'",
-            "'parentUuid': 'UUID'",
-            "'timestamp': 'TIMESTAMP'",
-            "'sessionId': 'UUID'",
-            "'cwd': 'VARCHAR'",
-            "'gitBranch': 'VARCHAR'",
-            "'slug': 'VARCHAR'",
-            "'version': 'VARCHAR'",
-            "'isSidechain': 'BOOLEAN'",
-            "'userType': 'VARCHAR'",
-            "'message': 'JSON'",
-            "'isCompactSummary': 'BOOLEAN'",
-            "'isMeta': 'BOOLEAN'",
-            "'isVisibleInTranscriptOnly': 'BOOLEAN'",
-            "'sourceToolUseID': 'VARCHAR'",
-            "'thinkingMetadata': 'JSON'",
-            "'todos': 'JSON'",
-            "'toolUseResult': 'JSON'",
-            "'error': 'JSON'",
-            "'isApiErrorMessage': 'BOOLEAN'",
-            "'requestId': 'VARCHAR'",
-            "'sourceToolAssistantUUID': 'UUID'",
-            "'content': 'VARCHAR'",
-            "'compactMetadata': 'JSON'",
-            "'hasOutput': 'BOOLEAN'",
-            "'hookCount': 'INTEGER'",
-            "'hookErrors': 'JSON'",
-            "'hookInfos': 'JSON'",
-            "'level': 'VARCHAR'",
-            "'logicalParentUuid': 'UUID'",
-            "'maxRetries': 'INTEGER'",
-            "'preventedContinuation': 'BOOLEAN'",
-            "'retryAttempt': 'INTEGER'",
-            "'retryInMs': 'INTEGER'",
-            "'stopReason': 'VARCHAR'",
-            "'toolUseID': 'VARCHAR'",
-        ]
-        .join(", ");
-
-        format!(
+        let columns_def = SCHEMA_COLUMNS
+            .iter()
+            .map(|(key, ty)| format!("'{key}': '{ty}'"))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        // Table behind the costs view; created before the view so it binds
+        let pricing_sql = pricing::table_sql(&options.pricing);
+
+        let mut sql = format!(
             r"
     -- Base messages view with explicit schema for type safety
     CREATE OR REPLACE VIEW messages AS
@@ -269,7 +707,7 @@ impl QuerySession {
       ignore_errors=true,
       columns={{{columns_def}}}
     ) WITH ORDINALITY
-    WHERE type IN ('user', 'assistant', 'system');
+    WHERE type IN ('user', 'assistant', 'system'){branch_filter};
 
     -- User messages view
     CREATE OR REPLACE VIEW user_messages AS
@@ -317,7 +755,7 @@ impl QuerySession {
       (json->>'uuid')::UUID as uuid,
       json as raw
     FROM read_ndjson_objects({pattern_sql}, ignore_errors=true)
-    WHERE json->>'uuid' IS NOT NULL AND length(json->>'uuid') > 0;
+    WHERE json->>'uuid' IS NOT NULL AND length(json->>'uuid') > 0{raw_branch_filter};
 
     -- Tool uses: All tool calls with unnested content blocks
     CREATE OR REPLACE VIEW tool_uses AS
@@ -369,15 +807,33 @@ impl QuerySession {
       isAgent,
       agentId,
       project,
+      -- userType is the only account-level field Claude Code records (no account
+      -- or API key); requestId correlates with provider-side billing logs
+      userType,
+      requestId,
+      message->>'id' as message_id,
       message->>'model' as model,
       message->>'stop_reason' as stop_reason,
       CAST(message->'usage'->>'input_tokens' AS BIGINT) as input_tokens,
       CAST(message->'usage'->>'output_tokens' AS BIGINT) as output_tokens,
       CAST(message->'usage'->>'cache_read_input_tokens' AS BIGINT) as cache_read_tokens,
-      CAST(message->'usage'->>'cache_creation_input_tokens' AS BIGINT) as cache_creation_tokens
+      CAST(message->'usage'->>'cache_creation_input_tokens' AS BIGINT) as cache_creation_tokens,
+      file,
+      rownum
     FROM assistant_messages
     WHERE (message->'usage') IS NOT NULL;
 
+    -- Response usage: token_usage with one row per API response. Claude Code writes a
+    -- line per content block, each repeating the response's id, requestId, and usage,
+    -- so the last line stands in for the response
+    CREATE OR REPLACE VIEW response_usage AS
+    SELECT *
+    FROM token_usage
+    QUALIFY row_number() OVER (
+      PARTITION BY coalesce(message_id, uuid::VARCHAR), requestId
+      ORDER BY rownum DESC
+    ) = 1;
+
     -- Bash commands: Bash tool uses with extracted command
     CREATE OR REPLACE VIEW bash_commands AS
     SELECT
@@ -415,11 +871,1115 @@ impl QuerySession {
       tool_input->>'pattern' as pattern
     FROM tool_uses
     WHERE tool_name IN ('Read', 'Write', 'Edit', 'Glob', 'Grep');
-  "
+
+    -- Thinking blocks: extended-thinking content from assistant messages
+    CREATE OR REPLACE VIEW thinking_blocks AS
+    SELECT
+      m.uuid,
+      m.parentUuid,
+      m.timestamp,
+      m.sessionId,
+      m.isAgent,
+      m.agentId,
+      m.project,
+      m.rownum,
+      m.requestId,
+      m.message->>'model' as model,
+      block->>'thinking' as thinking,
+      length(block->>'thinking') as thinking_chars,
+      -- Rough estimate at ~4 characters per token; usage doesn't split thinking out
+      CAST(ceil(length(block->>'thinking') / 4) AS BIGINT) as estimated_tokens,
+      coalesce(length(block->>'signature'), 0) > 0 as has_signature,
+      row_number() OVER (PARTITION BY m.uuid ORDER BY (SELECT NULL)) - 1 as block_index
+    FROM assistant_messages m,
+    LATERAL UNNEST(CAST(message->'content' AS JSON[])) as t(block)
+    WHERE block->>'type' = 'thinking';
+
+    -- Text blocks: assistant prose, one row per text content block
+    CREATE OR REPLACE VIEW text_blocks AS
+    SELECT
+      m.uuid,
+      m.parentUuid,
+      m.timestamp,
+      m.sessionId,
+      m.isAgent,
+      m.agentId,
+      m.project,
+      m.rownum,
+      m.message->>'model' as model,
+      block->>'text' as text,
+      length(block->>'text') as text_chars,
+      row_number() OVER (PARTITION BY m.uuid ORDER BY (SELECT NULL)) - 1 as block_index
+    FROM assistant_messages m,
+    LATERAL UNNEST(CAST(message->'content' AS JSON[])) as t(block)
+    WHERE block->>'type' = 'text';
+
+    -- Attachments: images and documents sent to the model, including those
+    -- returned inside tool results (e.g. Read on an image or PDF)
+    CREATE OR REPLACE VIEW attachments AS
+    WITH array_messages AS (
+      SELECT * FROM user_messages
+      WHERE json_type(message->'content') = 'ARRAY'
+    ),
+    blocks AS (
+      SELECT m.*, block, NULL as tool_use_id
+      FROM array_messages m,
+      LATERAL UNNEST(CAST(message->'content' AS JSON[])) as t(block)
+      UNION ALL
+      SELECT m.*, nested, block->>'tool_use_id'
+      FROM array_messages m,
+      LATERAL UNNEST(CAST(message->'content' AS JSON[])) as t(block),
+      LATERAL UNNEST(CAST(
+        CASE WHEN json_type(block->'content') = 'ARRAY' THEN block->'content' ELSE '[]'::JSON END
+        AS JSON[])) as u(nested)
+      WHERE block->>'type' = 'tool_result'
+    )
+    SELECT
+      uuid,
+      timestamp,
+      sessionId,
+      isAgent,
+      agentId,
+      project,
+      rownum,
+      tool_use_id,
+      block->>'type' as attachment_type,
+      block->'source'->>'media_type' as media_type,
+      block->'source'->>'type' as source_type,
+      -- Decoded size: base64 carries 3 bytes per 4 characters
+      CASE block->'source'->>'type'
+        WHEN 'base64' THEN CAST(length(block->'source'->>'data') * 3 // 4 AS BIGINT)
+        WHEN 'text' THEN CAST(strlen(block->'source'->>'data') AS BIGINT)
+      END as estimated_bytes,
+      block->'source'->>'url' as url,
+      block->>'title' as title
+    FROM blocks
+    WHERE block->>'type' IN ('image', 'document');
+
+    -- Todo events: the todo list attached to each user message, one row per item
+    CREATE OR REPLACE VIEW todo_events AS
+    SELECT
+      uuid,
+      timestamp,
+      sessionId,
+      isAgent,
+      agentId,
+      project,
+      rownum,
+      i + 1 as position,
+      json_array_length(todos) as todo_count,
+      todos->i->>'content' as content,
+      todos->i->>'status' as status,
+      todos->i->>'activeForm' as activeForm
+    FROM (
+      SELECT *, unnest(range(json_array_length(todos)::BIGINT)) as i
+      FROM user_messages
+      WHERE json_type(todos) = 'ARRAY'
+    );
+
+    -- Hooks: hooks run and hook errors reported by system messages, one row each
+    CREATE OR REPLACE VIEW hooks AS
+    WITH entries AS (
+      SELECT *, hookInfos->i as entry, false as is_error
+      FROM (
+        SELECT *, unnest(range(json_array_length(hookInfos)::BIGINT)) as i
+        FROM system_messages
+        WHERE json_type(hookInfos) = 'ARRAY'
+      )
+      UNION ALL
+      SELECT *, hookErrors->i, true
+      FROM (
+        SELECT *, unnest(range(json_array_length(hookErrors)::BIGINT)) as i
+        FROM system_messages
+        WHERE json_type(hookErrors) = 'ARRAY'
+      )
+    )
+    SELECT
+      uuid,
+      timestamp,
+      sessionId,
+      isAgent,
+      agentId,
+      project,
+      rownum,
+      toolUseID,
+      -- stop_hook_summary -> stop, subagent_stop_hook_summary -> subagent_stop
+      regexp_replace(subtype, '_hook_summary$', '') as event,
+      i as hook_index,
+      is_error,
+      CASE WHEN json_type(entry) = 'OBJECT'
+           THEN coalesce(entry->>'command', entry->>'name')
+      END as command,
+      CASE WHEN json_type(entry) = 'OBJECT'
+           THEN TRY_CAST(coalesce(entry->>'exitCode', entry->>'exit_code') AS INTEGER)
+      END as exit_code,
+      CASE
+        WHEN NOT is_error THEN NULL
+        WHEN json_type(entry) = 'VARCHAR' THEN entry->>'$'
+        ELSE coalesce(entry->>'error', entry->>'message', CAST(entry AS VARCHAR))
+      END as error,
+      preventedContinuation
+    FROM entries;
+
+    -- Errors: API error replies, system error payloads, and failed tool calls
+    CREATE OR REPLACE VIEW errors AS
+    SELECT
+      uuid, timestamp, sessionId, isAgent, agentId, project,
+      'api' as error_kind,
+      coalesce(message->'content'->0->>'text', CAST(error AS VARCHAR)) as error_message,
+      CASE WHEN json_type(error) = 'VARCHAR' THEN error->>'$' ELSE CAST(error AS VARCHAR) END
+        as detail
+    FROM assistant_messages
+    WHERE isApiErrorMessage
+    UNION ALL
+    SELECT
+      uuid, timestamp, sessionId, isAgent, agentId, project,
+      'system',
+      coalesce(
+        error->'error'->'error'->>'message',
+        error->'error'->>'message',
+        error->>'message',
+        content,
+        CAST(error AS VARCHAR)
+      ),
+      subtype
+    FROM system_messages
+    WHERE error IS NOT NULL AND json_type(error) <> 'NULL'
+    UNION ALL
+    SELECT
+      uuid, timestamp, sessionId, isAgent, agentId, project,
+      'tool',
+      result_content,
+      tool_use_id
+    FROM tool_results
+    WHERE is_error;
+
+    -- Sessions: one row per session with its span, activity, and token totals
+    CREATE OR REPLACE VIEW sessions AS
+    WITH message_stats AS (
+      SELECT
+        sessionId,
+        any_value(project) as project,
+        min(timestamp) as first,
+        max(timestamp) as last,
+        count(*) as messages,
+        count(*) FILTER (WHERE type = 'user') as user_messages,
+        count(*) FILTER (WHERE type = 'assistant') as assistant_messages,
+        count(*) FILTER (WHERE type = 'system') as system_messages,
+        count(DISTINCT agentId) FILTER (WHERE isAgent) as agents
+      FROM messages
+      WHERE sessionId IS NOT NULL
+      GROUP BY sessionId
+    ),
+    tool_stats AS (
+      SELECT sessionId, count(*) as tool_calls, list(DISTINCT tool_name ORDER BY tool_name) as tools
+      FROM tool_uses
+      GROUP BY sessionId
+    ),
+    token_stats AS (
+      SELECT
+        sessionId,
+        sum(input_tokens) as input_tokens,
+        sum(output_tokens) as output_tokens,
+        sum(cache_read_tokens) as cache_read_tokens,
+        sum(cache_creation_tokens) as cache_creation_tokens
+      FROM response_usage
+      GROUP BY sessionId
+    )
+    SELECT
+      m.sessionId,
+      m.project,
+      m.first,
+      m.last,
+      m.last - m.first as duration,
+      m.messages,
+      m.user_messages,
+      m.assistant_messages,
+      m.system_messages,
+      coalesce(t.tool_calls, 0) as tool_calls,
+      coalesce(t.tools, []) as tools,
+      coalesce(k.input_tokens, 0) as input_tokens,
+      coalesce(k.output_tokens, 0) as output_tokens,
+      coalesce(k.cache_read_tokens, 0) as cache_read_tokens,
+      coalesce(k.cache_creation_tokens, 0) as cache_creation_tokens,
+      coalesce(k.input_tokens, 0) + coalesce(k.output_tokens, 0)
+        + coalesce(k.cache_read_tokens, 0) + coalesce(k.cache_creation_tokens, 0) as total_tokens,
+      m.agents
+    FROM message_stats m
+    LEFT JOIN tool_stats t USING (sessionId)
+    LEFT JOIN token_stats k USING (sessionId);
+
+    -- Threads: each message with its thread root, depth, and uuid path along parentUuid
+    CREATE OR REPLACE VIEW threads AS
+    WITH RECURSIVE nodes AS (
+      SELECT DISTINCT ON (sessionId, uuid) sessionId, uuid, parentUuid, isSidechain, agentId
+      FROM messages
+      WHERE uuid IS NOT NULL
+    ),
+    walk(sessionId, uuid, parentUuid, root_uuid, depth, path) AS (
+      -- Roots: no parent, or a parent that isn't in the loaded data
+      SELECT n.sessionId, n.uuid, n.parentUuid, n.uuid, 0, [n.uuid]
+      FROM nodes n
+      WHERE n.parentUuid IS NULL
+        OR NOT EXISTS (
+          SELECT 1 FROM nodes p WHERE p.sessionId = n.sessionId AND p.uuid = n.parentUuid
+        )
+      UNION ALL
+      SELECT
+        n.sessionId, n.uuid, n.parentUuid, w.root_uuid, w.depth + 1, list_append(w.path, n.uuid)
+      FROM nodes n
+      JOIN walk w ON n.sessionId = w.sessionId AND n.parentUuid = w.uuid
+    )
+    SELECT
+      w.sessionId,
+      w.uuid,
+      w.parentUuid,
+      w.root_uuid,
+      w.depth,
+      w.path,
+      n.isSidechain,
+      n.agentId
+    FROM walk w
+    JOIN nodes n USING (sessionId, uuid);
+
+    -- Agents: one row per subagent file, linked to the Task call that spawned it
+    CREATE OR REPLACE VIEW agents AS
+    WITH agent_messages AS (
+      SELECT
+        file,
+        any_value(agentId) as agentId,
+        any_value(sessionId) as sessionId,
+        any_value(project) as project,
+        any_value(sourceToolUseID) FILTER (WHERE sourceToolUseID IS NOT NULL) as tool_use_id,
+        min(timestamp) as first,
+        max(timestamp) as last,
+        count(*) as messages
+      FROM messages
+      WHERE isAgent
+      GROUP BY file
+    ),
+    agent_tokens AS (
+      SELECT
+        file,
+        sum(input_tokens) as input_tokens,
+        sum(output_tokens) as output_tokens,
+        sum(cache_read_tokens) as cache_read_tokens,
+        sum(cache_creation_tokens) as cache_creation_tokens
+      FROM response_usage
+      WHERE isAgent
+      GROUP BY file
+    )
+    SELECT
+      a.agentId,
+      a.sessionId,
+      a.project,
+      a.file,
+      a.tool_use_id,
+      u.uuid as parent_uuid,
+      u.tool_input->>'subagent_type' as subagent_type,
+      u.tool_input->>'description' as description,
+      a.first,
+      a.last,
+      a.last - a.first as duration,
+      a.messages,
+      coalesce(k.input_tokens, 0) as input_tokens,
+      coalesce(k.output_tokens, 0) as output_tokens,
+      coalesce(k.cache_read_tokens, 0) as cache_read_tokens,
+      coalesce(k.cache_creation_tokens, 0) as cache_creation_tokens,
+      coalesce(k.input_tokens, 0) + coalesce(k.output_tokens, 0)
+        + coalesce(k.cache_read_tokens, 0) + coalesce(k.cache_creation_tokens, 0) as total_tokens
+    FROM agent_messages a
+    LEFT JOIN agent_tokens k USING (file)
+    LEFT JOIN tool_uses u ON u.tool_id = a.tool_use_id AND NOT u.isAgent;
+
+    -- Edits: Edit and MultiEdit calls, one row per old_string/new_string replacement
+    CREATE OR REPLACE VIEW edits AS
+    WITH replacements AS (
+      SELECT *, 0 as edit_index, tool_input as edit
+      FROM tool_uses
+      WHERE tool_name = 'Edit'
+      UNION ALL
+      SELECT * EXCLUDE (i), i as edit_index, tool_input->'edits'->i as edit
+      FROM (
+        SELECT *, unnest(range(json_array_length(tool_input->'edits')::BIGINT)) as i
+        FROM tool_uses
+        WHERE tool_name = 'MultiEdit' AND json_type(tool_input->'edits') = 'ARRAY'
+      )
+    )
+    SELECT
+      uuid,
+      timestamp,
+      sessionId,
+      isAgent,
+      agentId,
+      project,
+      rownum,
+      tool_id,
+      tool_name,
+      tool_input->>'file_path' as file_path,
+      edit_index,
+      edit->>'old_string' as old_string,
+      edit->>'new_string' as new_string,
+      coalesce(CAST(edit->>'replace_all' AS BOOLEAN), false) as replace_all,
+      length(coalesce(edit->>'new_string', ''))
+        - length(coalesce(edit->>'old_string', '')) as char_delta
+    FROM replacements;
+
+    -- Web requests: WebFetch and WebSearch calls with their result size and duration
+    CREATE OR REPLACE VIEW web_requests AS
+    SELECT
+      u.uuid,
+      u.timestamp,
+      u.sessionId,
+      u.isAgent,
+      u.agentId,
+      u.project,
+      u.rownum,
+      u.tool_id,
+      u.tool_name,
+      u.tool_input->>'url' as url,
+      regexp_extract(u.tool_input->>'url', '^[a-zA-Z]+://([^/:?#]+)', 1) as domain,
+      u.tool_input->>'query' as query,
+      u.tool_input->>'prompt' as prompt,
+      r.is_error,
+      length(r.result_content) as result_chars,
+      r.duration_ms
+    FROM tool_uses u
+    LEFT JOIN tool_results r ON r.tool_use_id = u.tool_id
+    WHERE u.tool_name IN ('WebFetch', 'WebSearch');
+
+    -- Task launches: Task tool calls with their inputs and the agent run they started
+    CREATE OR REPLACE VIEW task_launches AS
+    SELECT
+      u.uuid,
+      u.timestamp,
+      u.sessionId,
+      u.isAgent,
+      u.agentId,
+      u.project,
+      u.rownum,
+      u.tool_id,
+      u.tool_input->>'description' as description,
+      u.tool_input->>'prompt' as prompt,
+      u.tool_input->>'subagent_type' as subagent_type,
+      u.tool_input->>'model' as model,
+      a.agentId as launched_agentId,
+      a.file as agent_file,
+      a.messages as agent_messages,
+      a.total_tokens as agent_tokens,
+      a.duration as agent_duration
+    FROM tool_uses u
+    LEFT JOIN agents a ON a.tool_use_id = u.tool_id
+    WHERE u.tool_name = 'Task';
+
+    -- MCP tool uses: mcp__<server>__<tool> names split into server and tool
+    CREATE OR REPLACE VIEW mcp_tool_uses AS
+    SELECT
+      uuid,
+      timestamp,
+      sessionId,
+      isAgent,
+      agentId,
+      project,
+      rownum,
+      tool_id,
+      tool_name,
+      regexp_extract(tool_name, '^mcp__(.+?)__(.+)$', 1) as server,
+      regexp_extract(tool_name, '^mcp__(.+?)__(.+)$', 2) as tool,
+      tool_input
+    FROM tool_uses
+    WHERE starts_with(tool_name, 'mcp__');
+
+    -- Tool calls: each tool use joined to its result, with latency
+    CREATE OR REPLACE VIEW tool_calls AS
+    SELECT
+      u.uuid,
+      u.timestamp,
+      u.sessionId,
+      u.isAgent,
+      u.agentId,
+      u.project,
+      u.rownum,
+      u.tool_id,
+      u.tool_name,
+      u.tool_input,
+      r.uuid as result_uuid,
+      r.timestamp as result_timestamp,
+      r.timestamp - u.timestamp as latency,
+      r.duration_ms,
+      r.is_error,
+      r.result_content,
+      length(r.result_content) as result_chars
+    FROM tool_uses u
+    LEFT JOIN tool_results r ON r.tool_use_id = u.tool_id;
+
+    -- Compactions: compact boundaries with their metadata and the summary that follows
+    CREATE OR REPLACE VIEW compactions AS
+    WITH boundaries AS (
+      SELECT * FROM system_messages WHERE subtype = 'compact_boundary'
+    ),
+    summaries AS (
+      SELECT * FROM user_messages WHERE isCompactSummary
+    )
+    SELECT
+      coalesce(b.uuid, s.uuid) as uuid,
+      coalesce(b.timestamp, s.timestamp) as timestamp,
+      coalesce(b.sessionId, s.sessionId) as sessionId,
+      coalesce(b.isAgent, s.isAgent) as isAgent,
+      coalesce(b.agentId, s.agentId) as agentId,
+      coalesce(b.project, s.project) as project,
+      b.compactMetadata->>'trigger' as trigger,
+      CAST(b.compactMetadata->>'preTokens' AS BIGINT) as pre_tokens,
+      CAST(b.compactMetadata->>'postTokens' AS BIGINT) as post_tokens,
+      b.logicalParentUuid,
+      s.uuid as summary_uuid,
+      CASE WHEN json_type(s.message->'content') = 'VARCHAR'
+           THEN s.message->>'content'
+           ELSE s.message->'content'->0->>'text'
+      END as summary,
+      b.compactMetadata
+    FROM boundaries b
+    FULL OUTER JOIN summaries s
+      ON s.parentUuid = b.uuid AND s.sessionId = b.sessionId;
+
+    -- Interruptions: the user stopping a response, a running tool, or a tool request
+    CREATE OR REPLACE VIEW interruptions AS
+    WITH tool_interrupts AS (
+      SELECT
+        result_uuid as uuid,
+        result_timestamp as timestamp,
+        sessionId, isAgent, agentId, project,
+        CASE WHEN starts_with(result_content, '[Request interrupted by user')
+             THEN 'tool' ELSE 'rejected'
+        END as kind,
+        tool_name,
+        tool_id,
+        result_content as text
+      FROM tool_calls
+      WHERE starts_with(result_content, '[Request interrupted by user')
+        OR starts_with(result_content, 'The user doesn''t want to proceed with this tool use')
+    ),
+    texts AS (
+      SELECT uuid, timestamp, sessionId, isAgent, agentId, project, message->>'content' as text
+      FROM user_messages
+      WHERE json_type(message->'content') = 'VARCHAR'
+      UNION ALL
+      SELECT m.uuid, m.timestamp, m.sessionId, m.isAgent, m.agentId, m.project, block->>'text'
+      FROM (
+        SELECT * FROM user_messages WHERE json_type(message->'content') = 'ARRAY'
+      ) m,
+      LATERAL UNNEST(CAST(message->'content' AS JSON[])) as t(block)
+      WHERE block->>'type' = 'text'
+    )
+    SELECT * FROM tool_interrupts
+    UNION ALL
+    -- Text notices not already covered by an interrupted tool result in the same message
+    SELECT uuid, timestamp, sessionId, isAgent, agentId, project, 'prompt', NULL, NULL, text
+    FROM texts
+    WHERE starts_with(text, '[Request interrupted by user')
+      AND uuid NOT IN (SELECT uuid FROM tool_interrupts);
+
+{pricing_sql}
+
+    -- Costs: estimated USD per API response, priced by the longest matching prefix
+    CREATE OR REPLACE VIEW costs AS
+    SELECT
+      t.uuid,
+      t.timestamp,
+      t.sessionId,
+      t.isAgent,
+      t.agentId,
+      t.project,
+      t.userType,
+      t.requestId,
+      t.model,
+      t.input_tokens,
+      t.output_tokens,
+      t.cache_read_tokens,
+      t.cache_creation_tokens,
+      p.prefix as price_prefix,
+      coalesce(t.input_tokens, 0) * p.input / 1e6 as input_cost,
+      coalesce(t.output_tokens, 0) * p.output / 1e6 as output_cost,
+      coalesce(t.cache_creation_tokens, 0) * p.cache_write / 1e6 as cache_write_cost,
+      coalesce(t.cache_read_tokens, 0) * p.cache_read / 1e6 as cache_read_cost,
+      input_cost + output_cost + cache_write_cost + cache_read_cost as cost_usd
+    FROM response_usage t
+    LEFT JOIN model_pricing p ON starts_with(t.model, p.prefix)
+    QUALIFY row_number() OVER (PARTITION BY t.rownum ORDER BY length(p.prefix) DESC) = 1;
+
+    -- Daily usage: messages, tool calls, tokens, and estimated cost per day and project
+    CREATE OR REPLACE VIEW daily_usage AS
+    WITH message_days AS (
+      SELECT
+        timestamp::DATE as day,
+        project,
+        count(*) as messages,
+        count(DISTINCT sessionId) as sessions
+      FROM messages
+      WHERE timestamp IS NOT NULL
+      GROUP BY ALL
+    ),
+    tool_days AS (
+      SELECT timestamp::DATE as day, project, count(*) as tool_calls
+      FROM tool_uses
+      GROUP BY ALL
+    ),
+    cost_days AS (
+      SELECT
+        timestamp::DATE as day,
+        project,
+        sum(input_tokens) as input_tokens,
+        sum(output_tokens) as output_tokens,
+        sum(cache_read_tokens) as cache_read_tokens,
+        sum(cache_creation_tokens) as cache_creation_tokens,
+        sum(cost_usd) as cost_usd
+      FROM costs
+      GROUP BY ALL
+    )
+    SELECT
+      m.day,
+      m.project,
+      m.sessions,
+      m.messages,
+      coalesce(t.tool_calls, 0) as tool_calls,
+      coalesce(c.input_tokens, 0) as input_tokens,
+      coalesce(c.output_tokens, 0) as output_tokens,
+      coalesce(c.cache_read_tokens, 0) as cache_read_tokens,
+      coalesce(c.cache_creation_tokens, 0) as cache_creation_tokens,
+      coalesce(c.cost_usd, 0) as cost_usd
+    FROM message_days m
+    LEFT JOIN tool_days t USING (day, project)
+    LEFT JOIN cost_days c USING (day, project);
+
+    -- Model usage: model per assistant message and where it changes within a conversation
+    CREATE OR REPLACE VIEW model_usage AS
+    SELECT
+      *,
+      coalesce(model <> previous_model, false) as model_changed,
+      model <> primary_model as off_primary
+    FROM (
+      SELECT
+        uuid,
+        timestamp,
+        sessionId,
+        isAgent,
+        agentId,
+        project,
+        rownum,
+        requestId,
+        message->>'model' as model,
+        lag(message->>'model') OVER conversation as previous_model,
+        mode(message->>'model') OVER (PARTITION BY file) as primary_model
+      FROM assistant_messages
+      WHERE message->>'model' IS NOT NULL
+        AND message->>'model' <> '<synthetic>'
+      WINDOW conversation AS (PARTITION BY file ORDER BY timestamp, rownum)
+    );
+
+    -- Slash commands: user messages that invoke a /command, with name and arguments
+    CREATE OR REPLACE VIEW slash_commands AS
+    WITH texts AS (
+      SELECT *, message->>'content' as text
+      FROM user_messages
+      WHERE json_type(message->'content') = 'VARCHAR'
+        AND (agentId IS NULL OR agentId = '')
+    )
+    SELECT
+      uuid,
+      timestamp,
+      sessionId,
+      project,
+      rownum,
+      CASE WHEN text LIKE '%<command-name>%'
+           THEN regexp_extract(text, '<command-name>\s*/?([^<\s]+)', 1)
+           ELSE regexp_extract(text, '^\s*/([^\s]+)', 1)
+      END as command,
+      CASE WHEN text LIKE '%<command-name>%'
+           THEN trim(regexp_extract(text, '(?s)<command-args>(.*?)</command-args>', 1))
+           ELSE trim(regexp_extract(text, '(?s)^\s*/[^\s]+(.*)$', 1))
+      END as args,
+      text LIKE '%<command-name>%' as is_wrapped,
+      text
+    FROM texts
+    WHERE text LIKE '%<command-name>%'
+      OR regexp_matches(text, '^\s*/[A-Za-z][\w:-]*(\s|$)');
+
+    -- Git activity: branch changes and git commands as one timeline per session
+    CREATE OR REPLACE VIEW git_activity AS
+    WITH branch_changes AS (
+      SELECT *
+      FROM (
+        SELECT
+          uuid, timestamp, sessionId, isAgent, agentId, project, rownum, gitBranch,
+          lag(gitBranch) OVER (PARTITION BY file ORDER BY timestamp, rownum) as previous_branch
+        FROM messages
+        WHERE gitBranch IS NOT NULL AND gitBranch <> ''
+      )
+      WHERE previous_branch IS DISTINCT FROM gitBranch
+    ),
+    git_commands AS (
+      SELECT
+        b.*,
+        a.gitBranch,
+        regexp_extract(
+          b.command, '(?:^|[;&|(]\s*)git\s+(?:-\S+\s+)*([a-z][a-z-]*)', 1
+        ) as subcommand
+      FROM bash_commands b
+      JOIN assistant_messages a ON a.uuid = b.uuid
+      WHERE regexp_matches(b.command, '(?:^|[;&|(]\s*)git\s')
+    )
+    SELECT
+      uuid, timestamp, sessionId, isAgent, agentId, project, rownum,
+      'branch' as kind, gitBranch, previous_branch,
+      NULL as subcommand, NULL as command, NULL as tool_id, NULL::BOOLEAN as is_error
+    FROM branch_changes
+    UNION ALL
+    SELECT
+      c.uuid, c.timestamp, c.sessionId, c.isAgent, c.agentId, c.project, c.rownum,
+      'command', c.gitBranch, NULL,
+      c.subcommand, c.command, c.tool_id, r.is_error
+    FROM git_commands c
+    LEFT JOIN tool_results r ON r.tool_use_id = c.tool_id;
+
+    -- File history: Read/Edit/Write events per file in order, numbered with op_seq
+    CREATE OR REPLACE VIEW file_history AS
+    SELECT
+      file_path,
+      row_number() OVER by_file as op_seq,
+      row_number() OVER (
+        PARTITION BY file_path, sessionId ORDER BY timestamp, rownum
+      ) as session_op_seq,
+      tool_name as operation,
+      timestamp,
+      sessionId,
+      isAgent,
+      agentId,
+      project,
+      uuid,
+      tool_id,
+      lag(tool_name) OVER by_file as previous_operation,
+      timestamp - lag(timestamp) OVER by_file as since_previous
+    FROM file_operations
+    WHERE tool_name IN ('Read', 'Edit', 'Write') AND file_path IS NOT NULL
+    WINDOW by_file AS (PARTITION BY file_path ORDER BY timestamp, rownum);
+
+    -- Notebook operations: NotebookEdit (and legacy NotebookRead) calls with cell details
+    CREATE OR REPLACE VIEW notebook_operations AS
+    SELECT
+      uuid,
+      timestamp,
+      sessionId,
+      isAgent,
+      agentId,
+      project,
+      rownum,
+      tool_id,
+      tool_name,
+      tool_input->>'notebook_path' as notebook_path,
+      tool_input->>'cell_id' as cell_id,
+      CASE WHEN tool_name = 'NotebookEdit'
+           THEN coalesce(tool_input->>'edit_mode', 'replace')
+      END as edit_mode,
+      tool_input->>'cell_type' as cell_type,
+      tool_input->>'new_source' as new_source
+    FROM tool_uses
+    WHERE tool_name IN ('NotebookEdit', 'NotebookRead');
+
+    -- Searches: Grep and Glob calls with match counts and timing from their results
+    CREATE OR REPLACE VIEW searches AS
+    SELECT
+      u.uuid,
+      u.timestamp,
+      u.sessionId,
+      u.isAgent,
+      u.agentId,
+      u.project,
+      u.rownum,
+      u.tool_id,
+      u.tool_name,
+      u.tool_input->>'pattern' as pattern,
+      u.tool_input->>'path' as path,
+      u.tool_input->>'glob' as glob,
+      u.tool_input->>'output_mode' as output_mode,
+      CAST(m.toolUseResult->>'numFiles' AS BIGINT) as num_files,
+      coalesce(
+        CAST(m.toolUseResult->>'numMatches' AS BIGINT),
+        CASE WHEN m.toolUseResult->>'mode' = 'content'
+             THEN CAST(m.toolUseResult->>'numLines' AS BIGINT)
+        END,
+        CAST(m.toolUseResult->>'numFiles' AS BIGINT)
+      ) as match_count,
+      CAST(m.toolUseResult->>'truncated' AS BOOLEAN) as truncated,
+      r.is_error,
+      r.duration_ms,
+      r.timestamp - u.timestamp as latency
+    FROM tool_uses u
+    LEFT JOIN tool_results r ON r.tool_use_id = u.tool_id
+    LEFT JOIN user_messages m ON m.uuid = r.uuid
+    WHERE u.tool_name IN ('Grep', 'Glob');
+
+    -- Retries: API retry notices from system messages, with the request and model involved
+    CREATE OR REPLACE VIEW retries AS
+    SELECT
+      s.uuid,
+      s.timestamp,
+      s.sessionId,
+      s.isAgent,
+      s.agentId,
+      s.project,
+      s.rownum,
+      s.retryAttempt as retry_attempt,
+      s.maxRetries as max_retries,
+      s.retryInMs as retry_in_ms,
+      coalesce(s.error->>'requestID', s.error->>'request_id') as requestId,
+      CAST(s.error->>'status' AS INTEGER) as status,
+      coalesce(s.error->'error'->'error'->>'type', s.error->'error'->>'type') as error_type,
+      coalesce(
+        s.error->'error'->'error'->>'message',
+        s.error->'error'->>'message',
+        s.error->>'message',
+        s.content
+      ) as error_message,
+      a.message->>'model' as model
+    FROM system_messages s
+    -- The model comes from the conversation's next assistant reply
+    ASOF LEFT JOIN assistant_messages a
+      ON a.file = s.file AND a.timestamp >= s.timestamp
+    WHERE s.retryAttempt IS NOT NULL
+      OR s.maxRetries IS NOT NULL
+      OR s.retryInMs IS NOT NULL;
+
+    -- Sidechains: isSidechain messages grouped into threads with origin, length, and tokens
+    CREATE OR REPLACE VIEW sidechains AS
+    WITH RECURSIVE side AS (
+      SELECT DISTINCT ON (sessionId, uuid)
+        sessionId, uuid, parentUuid, timestamp, isAgent, agentId, project, message
+      FROM messages
+      WHERE isSidechain AND uuid IS NOT NULL
+    ),
+    walk(sessionId, uuid, start_uuid) AS (
+      -- A sidechain starts where its parent isn't itself a loaded sidechain message
+      SELECT s.sessionId, s.uuid, s.uuid
+      FROM side s
+      WHERE NOT EXISTS (
+        SELECT 1 FROM side p WHERE p.sessionId = s.sessionId AND p.uuid = s.parentUuid
+      )
+      UNION ALL
+      SELECT s.sessionId, s.uuid, w.start_uuid
+      FROM side s
+      JOIN walk w ON s.sessionId = w.sessionId AND s.parentUuid = w.uuid
+    ),
+    members AS (
+      SELECT w.start_uuid, s.*
+      FROM walk w
+      JOIN side s USING (sessionId, uuid)
+    ),
+    tokens AS (
+      SELECT
+        m.sessionId,
+        m.start_uuid,
+        sum(
+          coalesce(t.input_tokens, 0) + coalesce(t.output_tokens, 0)
+          + coalesce(t.cache_read_tokens, 0) + coalesce(t.cache_creation_tokens, 0)
+        ) as total_tokens,
+        sum(t.output_tokens) as output_tokens
+      FROM members m
+      JOIN response_usage t ON t.sessionId = m.sessionId AND t.uuid = m.uuid
+      GROUP BY ALL
+    )
+    SELECT
+      m.sessionId,
+      m.start_uuid,
+      any_value(s.parentUuid) as origin_uuid,
+      any_value(m.agentId) as agentId,
+      any_value(m.isAgent) as isAgent,
+      any_value(m.project) as project,
+      any_value(
+        CASE WHEN json_type(s.message->'content') = 'VARCHAR' THEN s.message->>'content' END
+      ) as prompt,
+      min(m.timestamp) as first,
+      max(m.timestamp) as last,
+      max(m.timestamp) - min(m.timestamp) as duration,
+      count(*) as messages,
+      coalesce(any_value(k.output_tokens), 0) as output_tokens,
+      coalesce(any_value(k.total_tokens), 0) as total_tokens
+    FROM members m
+    JOIN side s ON s.sessionId = m.sessionId AND s.uuid = m.start_uuid
+    LEFT JOIN tokens k ON k.sessionId = m.sessionId AND k.start_uuid = m.start_uuid
+    GROUP BY m.sessionId, m.start_uuid;
+
+    -- Turns: each session split at every human message, with the work each prompt caused
+    CREATE OR REPLACE VIEW turns AS
+    WITH starts AS (
+      SELECT
+        sessionId,
+        project,
+        uuid,
+        content as prompt,
+        timestamp as started,
+        row_number() OVER by_session as turn_index,
+        lead(timestamp) OVER by_session as next_started
+      FROM human_messages
+      WINDOW by_session AS (PARTITION BY sessionId ORDER BY timestamp, rownum)
+    ),
+    -- Subagent messages fall in the turn whose time span contains them
+    in_turn AS (
+      SELECT s.sessionId, s.turn_index, m.timestamp, m.type, m.uuid
+      FROM starts s
+      JOIN messages m
+        ON m.sessionId = s.sessionId
+        AND m.timestamp >= s.started
+        AND (s.next_started IS NULL OR m.timestamp < s.next_started)
+    ),
+    activity AS (
+      SELECT
+        sessionId,
+        turn_index,
+        max(timestamp) as ended,
+        count(*) as messages,
+        count(*) FILTER (WHERE type = 'assistant') as assistant_messages
+      FROM in_turn
+      GROUP BY ALL
+    ),
+    tools AS (
+      SELECT i.sessionId, i.turn_index, count(*) as tool_calls
+      FROM in_turn i
+      JOIN tool_uses u ON u.sessionId = i.sessionId AND u.uuid = i.uuid
+      GROUP BY ALL
+    ),
+    tokens AS (
+      SELECT
+        i.sessionId,
+        i.turn_index,
+        sum(t.input_tokens) as input_tokens,
+        sum(t.output_tokens) as output_tokens,
+        sum(
+          coalesce(t.input_tokens, 0) + coalesce(t.output_tokens, 0)
+          + coalesce(t.cache_read_tokens, 0) + coalesce(t.cache_creation_tokens, 0)
+        ) as total_tokens
+      FROM in_turn i
+      JOIN response_usage t ON t.sessionId = i.sessionId AND t.uuid = i.uuid
+      GROUP BY ALL
+    )
+    SELECT
+      s.sessionId,
+      s.project,
+      s.turn_index,
+      s.uuid,
+      s.prompt,
+      s.started,
+      a.ended,
+      a.ended - s.started as duration,
+      a.messages,
+      a.assistant_messages,
+      coalesce(t.tool_calls, 0) as tool_calls,
+      coalesce(k.input_tokens, 0) as input_tokens,
+      coalesce(k.output_tokens, 0) as output_tokens,
+      coalesce(k.total_tokens, 0) as total_tokens
+    FROM starts s
+    JOIN activity a USING (sessionId, turn_index)
+    LEFT JOIN tools t USING (sessionId, turn_index)
+    LEFT JOIN tokens k USING (sessionId, turn_index);
+
+    -- Response latency: time from each human message to the first reply and first text
+    CREATE OR REPLACE VIEW response_latency AS
+    WITH prompts AS (
+      SELECT
+        uuid, timestamp, sessionId, project, rownum, content,
+        lead(timestamp) OVER (PARTITION BY sessionId ORDER BY timestamp, rownum) as next_prompt
+      FROM human_messages
+    ),
+    replies AS (
+      SELECT sessionId, timestamp, uuid, message->>'model' as model
+      FROM assistant_messages
+      WHERE NOT isAgent
+    ),
+    text_replies AS (
+      SELECT DISTINCT sessionId, timestamp FROM text_blocks WHERE NOT isAgent
+    ),
+    first_reply AS (
+      SELECT p.*, r.timestamp as replied, r.uuid as reply_uuid, r.model
+      FROM prompts p
+      ASOF LEFT JOIN replies r ON r.sessionId = p.sessionId AND r.timestamp > p.timestamp
+    ),
+    first_text AS (
+      SELECT f.*, t.timestamp as texted
+      FROM first_reply f
+      ASOF LEFT JOIN text_replies t ON t.sessionId = f.sessionId AND t.timestamp > f.timestamp
+    )
+    SELECT
+      uuid,
+      timestamp,
+      sessionId,
+      project,
+      rownum,
+      content,
+      -- Replies after the next prompt belong to that prompt instead
+      CASE WHEN next_prompt IS NULL OR replied < next_prompt THEN reply_uuid END as reply_uuid,
+      CASE WHEN next_prompt IS NULL OR replied < next_prompt THEN model END as model,
+      CASE WHEN next_prompt IS NULL OR replied < next_prompt THEN replied - timestamp END
+        as first_response,
+      CASE WHEN next_prompt IS NULL OR texted < next_prompt THEN texted - timestamp END
+        as first_text
+    FROM first_text;
+
+    -- Context growth: prompt size per API response and running totals per conversation
+    CREATE OR REPLACE VIEW context_growth AS
+    SELECT
+      *,
+      round(100.0 * context_tokens / context_window, 1) as context_pct
+    FROM (
+      SELECT
+        t.uuid,
+        t.timestamp,
+        t.sessionId,
+        t.isAgent,
+        t.agentId,
+        t.project,
+        t.rownum,
+        t.model,
+        row_number() OVER conversation as message_index,
+        t.input_tokens,
+        t.cache_read_tokens,
+        t.cache_creation_tokens,
+        -- Everything sent with the request: fresh input plus cached prefix
+        coalesce(t.input_tokens, 0) + coalesce(t.cache_read_tokens, 0)
+          + coalesce(t.cache_creation_tokens, 0) as context_tokens,
+        sum(coalesce(t.input_tokens, 0)) OVER conversation as cumulative_input_tokens,
+        sum(coalesce(t.cache_read_tokens, 0)) OVER conversation as cumulative_cache_read_tokens,
+        -- Standard window; 1M-context beta requests will read above 100%
+        200000 as context_window
+      FROM response_usage t
+      WHERE t.model IS DISTINCT FROM '<synthetic>'
+      WINDOW conversation AS (PARTITION BY t.file ORDER BY t.timestamp, t.rownum)
+    );
+
+    -- Stop reasons: why each response ended, from assistant messages and hook stops
+    CREATE OR REPLACE VIEW stop_reasons AS
+    WITH reasons AS (
+      SELECT
+        uuid, timestamp, sessionId, isAgent, agentId, project, rownum,
+        'assistant' as source,
+        lower(message->>'stop_reason') as stop_reason,
+        message->>'stop_reason' as detail,
+        message->>'model' as model
+      FROM assistant_messages
+      WHERE message->>'stop_reason' IS NOT NULL
+      UNION ALL
+      SELECT
+        uuid, timestamp, sessionId, isAgent, agentId, project, rownum,
+        'system',
+        'hook_stop',
+        stopReason,
+        NULL
+      FROM system_messages
+      WHERE stopReason IS NOT NULL OR preventedContinuation
+    )
+    SELECT
+      *,
+      stop_reason = 'max_tokens' as is_truncated,
+      stop_reason = 'refusal' as is_refusal,
+      stop_reason NOT IN ('end_turn', 'tool_use', 'stop_sequence') as is_abnormal
+    FROM reasons;
+
+    -- Permission denials: tool calls the user rejected or a permission rule blocked
+    CREATE OR REPLACE VIEW permission_denials AS
+    SELECT *
+    FROM (
+      SELECT
+        result_uuid as uuid,
+        result_timestamp as timestamp,
+        sessionId,
+        isAgent,
+        agentId,
+        project,
+        tool_id,
+        tool_name,
+        tool_input,
+        CASE
+          WHEN starts_with(result_content, 'The user doesn''t want to proceed') THEN 'user_rejected'
+          WHEN result_content LIKE '%haven''t granted it yet%' THEN 'not_granted'
+          WHEN regexp_matches(result_content, 'Permission to use \S+ .*has been denied')
+            THEN 'rule_denied'
+        END as denial_kind,
+        result_content
+      FROM tool_calls
+      WHERE is_error
+    )
+    WHERE denial_kind IS NOT NULL;
+
+    -- Time travel: the todo list as it stood at a point within a session.
+    -- Snapshots come from TodoWrite calls and the todos column; latest wins.
+    CREATE OR REPLACE MACRO todos_asof(session_prefix, ts) AS TABLE
+    SELECT
+      as_of,
+      sessionId,
+      i + 1 as position,
+      todos->i->>'content' as content,
+      todos->i->>'status' as status,
+      todos->i->>'activeForm' as activeForm
+    FROM (
+      SELECT as_of, sessionId, todos, unnest(range(json_array_length(todos)::BIGINT)) as i
+      FROM (
+        SELECT timestamp as as_of, sessionId, todos
+        FROM (
+          SELECT timestamp, sessionId, tool_input->'todos' as todos, 1 as source_rank
+          FROM tool_uses
+          WHERE tool_name = 'TodoWrite'
+          UNION ALL
+          SELECT timestamp, sessionId, todos, 0 as source_rank
+          FROM user_messages
+          WHERE json_type(todos) = 'ARRAY'
         )
+        WHERE starts_with(sessionId::VARCHAR, session_prefix)
+          AND timestamp <= CAST(ts AS TIMESTAMP)
+          AND json_type(todos) = 'ARRAY'
+        ORDER BY timestamp DESC, source_rank DESC
+        LIMIT 1
+      )
+    )
+    ORDER BY position;
+
+    -- Time travel: files read or modified before a point within a session
+    CREATE OR REPLACE MACRO files_touched_before(session_prefix, ts) AS TABLE
+    SELECT
+      file_path,
+      count(*) as operations,
+      count(*) FILTER (WHERE tool_name IN ('Write', 'Edit')) as modifications,
+      min(timestamp) as first_touched,
+      max(timestamp) as last_touched,
+      arg_max(tool_name, timestamp) as last_operation
+    FROM file_operations
+    WHERE starts_with(sessionId::VARCHAR, session_prefix)
+      AND timestamp < CAST(ts AS TIMESTAMP)
+      AND file_path IS NOT NULL
+    GROUP BY file_path
+    ORDER BY last_touched;
+  "
+        );
+
+        for (name, select) in &options.views {
+            let _ = writeln!(
+                sql,
+                "    CREATE OR REPLACE VIEW {} AS {};",
+                sql_ident(name),
+                select.trim().trim_end_matches(';')
+            );
+        }
+        sql
     }
 }
 
+fn is_out_of_memory(error: &Error) -> bool {
+    matches!(error, Error::Database(e) if e.to_string().contains("Out of Memory"))
+}
+
+/// Whether `error` is `DuckDB` reporting a statement cancelled through an
+/// [`InterruptHandle`].
+pub fn is_interrupted(error: &Error) -> bool {
+    matches!(error, Error::Database(e) if e.to_string().contains("Interrupted"))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -429,18 +1989,36 @@ mod tests {
         let result = QueryResult {
             columns: vec!["a".to_string(), "b".to_string()],
             rows: vec![vec!["1".to_string(), "2".to_string()]],
+            nulls: vec![vec![false, false]],
+            numeric: vec![true, true],
         };
         assert!(result.to_table().contains("(1 row)"));
         assert_eq!(result.to_tsv(), "a\tb\n1\t2");
     }
 
+    #[test]
+    fn test_query_result_head() {
+        let result = QueryResult {
+            columns: vec!["n".to_string()],
+            rows: (1..=5).map(|n| vec![n.to_string()]).collect(),
+            nulls: vec![vec![false]; 5],
+            numeric: vec![true],
+        };
+        let head = result.head(2);
+        assert_eq!(head.row_count(), 2);
+        assert_eq!(head.to_tsv(), "n\n1\n2");
+        assert_eq!(result.head(10).row_count(), 5);
+    }
+
     #[test]
     fn test_build_create_views_sql_single_pattern() {
         let pattern = FilePattern::Single("/path/to/*.jsonl".to_string());
-        let sql = QuerySession::build_create_views_sql(&pattern);
+        let sql = QuerySession::build_create_views_sql(&pattern, &SessionOptions::default());
         assert!(sql.contains("'/path/to/*.jsonl'"));
         assert!(sql.contains("CREATE OR REPLACE VIEW messages"));
         assert!(sql.contains("CREATE OR REPLACE VIEW tool_uses"));
+        assert!(sql.contains("CREATE OR REPLACE MACRO todos_asof(session_prefix, ts)"));
+        assert!(sql.contains("CREATE OR REPLACE MACRO files_touched_before(session_prefix, ts)"));
     }
 
     #[test]
@@ -449,7 +2027,31 @@ mod tests {
             "/path/a*.jsonl".to_string(),
             "/path/b*.jsonl".to_string(),
         ]);
-        let sql = QuerySession::build_create_views_sql(&pattern);
+        let sql = QuerySession::build_create_views_sql(&pattern, &SessionOptions::default());
         assert!(sql.contains("['/path/a*.jsonl', '/path/b*.jsonl']"));
+        assert!(!sql.contains("gitBranch ="));
+    }
+
+    #[test]
+    fn test_build_create_views_sql_branch_filter() {
+        let pattern = FilePattern::Single("/path/to/*.jsonl".to_string());
+        let options = SessionOptions {
+            branch: Some("feat/o'neil".to_string()),
+            ..SessionOptions::default()
+        };
+        let sql = QuerySession::build_create_views_sql(&pattern, &options);
+        assert!(sql.contains("AND gitBranch = 'feat/o''neil';"));
+        assert!(sql.contains("AND json->>'gitBranch' = 'feat/o''neil';"));
+    }
+
+    #[test]
+    fn test_build_create_views_sql_extra_views() {
+        let pattern = FilePattern::Single("/path/to/*.jsonl".to_string());
+        let options = SessionOptions {
+            views: vec![("reads".to_string(), "SELECT * FROM tool_uses;".to_string())],
+            ..SessionOptions::default()
+        };
+        let sql = QuerySession::build_create_views_sql(&pattern, &options);
+        assert!(sql.ends_with("CREATE OR REPLACE VIEW \"reads\" AS SELECT * FROM tool_uses;\n"));
     }
 }
@@ -1,15 +1,36 @@
 //! Interactive REPL and piped query execution.
 
-use std::io::{self, BufWriter, Read, Write};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, BufWriter, IsTerminal, Read, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant, SystemTime};
 
 use rustyline::error::ReadlineError;
-use rustyline::DefaultEditor;
+use rustyline::history::DefaultHistory;
+use rustyline::{Editor, EventHandler, KeyEvent};
+use serde::Serialize;
+use signal_hook::consts::{SIGINT, SIGTERM};
+
+use crate::completion::ReplHelper;
+use crate::config::{EditMode, MacroDef, PromptContext, WarmupQuery, expand_prompt};
+use crate::history_search::HistorySearch;
+use crate::formatter::{NumberFormat, OutputMode, TableOptions, TableStream};
+use crate::query_session::{QueryResult, is_interrupted};
+use crate::utils::{ccq_config_dir, get_project_slug, resolve_project_dir, sql_ident, sql_quote};
+use crate::{Error, QuerySession, Result, SessionInfo, SessionOptions};
 
-use crate::{QuerySession, Result};
-
-const HISTORY_FILE: &str = ".cc_query_history";
 const PROMPT: &str = "ccq> ";
 const CONTINUATION_PROMPT: &str = "  -> ";
+const PASTE_PROMPT: &str = "paste> ";
+
+/// Single history file used before history was kept per dataset; seeds each
+/// new per-dataset file so earlier queries stay recallable.
+const LEGACY_HISTORY_FILE: &str = ".cc_query_history";
 
 /// All available views
 const VIEWS: &[&str] = &[
@@ -22,10 +43,169 @@ const VIEWS: &[&str] = &[
     "tool_uses",
     "tool_results",
     "token_usage",
+    "response_usage",
     "bash_commands",
     "file_operations",
+    "thinking_blocks",
+    "text_blocks",
+    "attachments",
+    "todo_events",
+    "hooks",
+    "errors",
+    "sessions",
+    "threads",
+    "agents",
+    "edits",
+    "web_requests",
+    "task_launches",
+    "mcp_tool_uses",
+    "tool_calls",
+    "compactions",
+    "interruptions",
+    "costs",
+    "daily_usage",
+    "model_usage",
+    "slash_commands",
+    "git_activity",
+    "file_history",
+    "notebook_operations",
+    "searches",
+    "retries",
+    "sidechains",
+    "turns",
+    "response_latency",
+    "context_growth",
+    "stop_reasons",
+    "permission_denials",
+];
+
+/// Ancestors and reply levels `.transcript` shows around a message.
+const TRANSCRIPT_DEPTH: usize = 10;
+
+/// Default `.maxrows` for interactive sessions.
+pub const DEFAULT_MAX_ROWS: usize = 40;
+
+/// Default `.set confirmrows`: results showing more rows ask first.
+pub const DEFAULT_CONFIRM_ROWS: usize = 10_000;
+
+/// Default `.width` for interactive sessions, enough for a timestamp or UUID
+/// with room to spare but not a whole message blob.
+pub const DEFAULT_MAX_WIDTH: usize = 60;
+
+/// Dot command names offered by tab completion.
+const DOT_COMMANDS: &[&str] = &[
+    ".help", ".h", ".schema", ".s", ".tables", ".last", ".clip", ".mode", ".output", ".once",
+    ".pager", ".timer", ".maxrows", ".width", ".x", ".set", ".info", ".grep", ".cost", ".watch",
+    ".sessions", ".check", ".explain", ".open", ".reload", ".export", ".store", ".read", ".save",
+    ".saved", ".run", ".transcript", ".tree", ".source", ".show", ".paste", ".edit", ".quit", ".q",
+    ".exit",
 ];
 
+type ReplEditor = Editor<ReplHelper, DefaultHistory>;
+
+/// Query analysis applied instead of returning results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Analyze {
+    /// Show the physical plan without running the query (`EXPLAIN`)
+    Explain,
+    /// Run the query and show the plan with per-operator timing (`EXPLAIN ANALYZE`)
+    Profile,
+}
+
+impl Analyze {
+    fn wrap(self, sql: &str) -> String {
+        let sql = sql.trim().trim_end_matches(';');
+        match self {
+            Self::Explain => format!("EXPLAIN {sql}"),
+            Self::Profile => format!("EXPLAIN ANALYZE {sql}"),
+        }
+    }
+}
+
+/// When table results are shown one record per row instead (`.x`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Expanded {
+    /// Always a table
+    #[default]
+    Off,
+    /// Always expanded
+    On,
+    /// Expanded when the table is wider than the terminal
+    Auto,
+}
+
+impl Expanded {
+    const fn name(self) -> &'static str {
+        match self {
+            Self::Off => "off",
+            Self::On => "on",
+            Self::Auto => "auto",
+        }
+    }
+}
+
+/// Output options shared by the REPL and script execution.
+#[derive(Debug, Clone, Default)]
+#[allow(clippy::struct_excessive_bools)] // independent settings
+pub struct ReplOptions {
+    /// Suppress the startup banner and row-count footers
+    pub quiet: bool,
+    /// Show query plans instead of results
+    pub analyze: Option<Analyze>,
+    /// Expand `${VAR}` from the environment in scripts
+    pub env_subst: bool,
+    /// Validate script statements without executing them
+    pub check: bool,
+    /// Number formatting for table output
+    pub numbers: NumberFormat,
+    /// Result format in the REPL (`.mode`)
+    pub mode: OutputMode,
+    /// File receiving all results (`.output`)
+    pub output: Option<PathBuf>,
+    /// File receiving only the next result (`.once`)
+    pub once: Option<PathBuf>,
+    /// Page results taller than the terminal (`.pager`)
+    pub pager: bool,
+    /// Report elapsed time and row count after each query (`.timer`)
+    pub timer: bool,
+    /// Rows shown before truncating results printed to the terminal (`.maxrows`)
+    pub max_rows: Option<usize>,
+    /// Ask before printing more rows than this to the terminal (`.set confirmrows`)
+    pub confirm_rows: Option<usize>,
+    /// Characters shown per table column (`.width N`)
+    pub max_width: Option<usize>,
+    /// Per-column overrides of `max_width` (`.width COLUMN N`)
+    pub column_widths: Vec<(String, usize)>,
+    /// Show table results one record per row (`.x`)
+    pub expanded: Expanded,
+    /// Text shown for NULL in table, markdown, and line output (`.set nullvalue`)
+    pub null_value: Option<String>,
+    /// Color tables and `.show` output on the terminal (`--color`)
+    pub color: bool,
+    /// Key bindings at the prompt (`[repl] edit_mode` in the config)
+    pub edit_mode: EditMode,
+    /// Prompt template (`[repl] prompt` in the config); `ccq> ` when unset
+    pub prompt: Option<String>,
+    /// Last SQL entered at the prompt, saved by a bare `.save NAME`
+    pub last_sql: Option<String>,
+    /// Most recent result, re-rendered by `.last`
+    pub last_result: Option<QueryResult>,
+}
+
+impl ReplOptions {
+    fn table_options(&self) -> TableOptions {
+        TableOptions {
+            row_count: !self.quiet,
+            numbers: self.numbers.clone(),
+            max_width: self.max_width,
+            column_widths: self.column_widths.clone(),
+            null_value: self.null_value.clone(),
+            // Files written with .output/.once stay plain
+            color: self.color && self.output.is_none() && self.once.is_none(),
+        }
+    }
+}
+
 /// Dot command result.
 enum DotCommandResult {
     /// Continue REPL
@@ -38,273 +218,2079 @@ enum DotCommandResult {
 ///
 /// # Errors
 /// Returns error if REPL initialization or I/O fails.
-pub fn start_interactive(session: &QuerySession) -> Result<()> {
-    let history_path = dirs::home_dir()
+pub fn start_interactive(session: &mut QuerySession, options: &ReplOptions) -> Result<()> {
+    let history_path = ccq_config_dir()
         .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "No home directory"))?
-        .join(HISTORY_FILE);
+        .join("history")
+        .join(history_slug(session.options()));
+    if let Some(dir) = history_path.parent() {
+        let _ = fs::create_dir_all(dir);
+    }
+    if !history_path.exists()
+        && let Some(legacy) = dirs::home_dir().map(|home| home.join(LEGACY_HISTORY_FILE))
+    {
+        let _ = fs::copy(legacy, &history_path); // Ignore missing file
+    }
 
-    let mut editor = DefaultEditor::new()?;
+    let edit_mode = match options.edit_mode {
+        EditMode::Emacs => rustyline::EditMode::Emacs,
+        EditMode::Vi => rustyline::EditMode::Vi,
+    };
+    let config = rustyline::Config::builder().edit_mode(edit_mode).build();
+    let mut editor = ReplEditor::with_config(config)?;
+    editor.set_helper(Some(
+        ReplHelper::load(session, DOT_COMMANDS, VIEWS).with_continuation_prompt(CONTINUATION_PROMPT),
+    ));
     let _ = editor.load_history(&history_path); // Ignore missing file
+    let history = Arc::new(Mutex::new(Vec::new()));
+    editor.bind_sequence(
+        KeyEvent::ctrl('R'),
+        EventHandler::Conditional(Box::new(HistorySearch::new(Arc::clone(&history)))),
+    );
 
-    print_banner(session);
+    if !options.quiet {
+        print_banner(session);
+    }
 
-    let result = run_repl_loop(&mut editor, session);
+    let result = run_repl_loop(&mut editor, session, options, &history);
 
     // Always try to save history, ignore errors
     let _ = editor.save_history(&history_path);
 
-    result
+    result
+}
+
+/// History file name for the data being queried, so recalled queries match
+/// the dataset: the project slug, the data directories' slugs, or `all`.
+fn history_slug(options: &SessionOptions) -> String {
+    if !options.data_dirs.is_empty() {
+        return options
+            .data_dirs
+            .iter()
+            .map(|dir| get_project_slug(&fs::canonicalize(dir).unwrap_or_else(|_| dir.clone())))
+            .collect::<Vec<_>>()
+            .join("+");
+    }
+    options.project_dir.as_ref().map_or_else(
+        || "all".to_string(),
+        |dir| {
+            let resolved = resolve_project_dir(&dir.to_string_lossy()).claude_data_dir;
+            resolved.file_name().map_or_else(String::new, |name| name.to_string_lossy().into_owned())
+        },
+    )
+}
+
+fn print_banner(session: &QuerySession) {
+    print_loaded(session.info());
+    if let Some(modified) = session.info().newest_modified() {
+        println!("{}", written_line(modified, SystemTime::now()));
+    }
+    println!("Type \".help\" for usage hints.\n");
+}
+
+/// The loaded messages' time span. This scans every message, so it runs on
+/// `.info` rather than at startup, and Ctrl-C cancels it.
+fn message_span(session: &QuerySession) -> Option<String> {
+    let span = interruptible(session, || {
+        session.query("SELECT min(timestamp), max(timestamp) FROM messages")
+    })
+    .ok()?;
+    let row = span.rows().first()?;
+    if row[0] == "NULL" {
+        return None;
+    }
+    Some(span_line(&row[0], &row[1]))
+}
+
+fn span_line(first: &str, last: &str) -> String {
+    let minutes = |ts: &str| ts.get(..16).unwrap_or(ts).to_string();
+    format!("Messages from {} to {} UTC", minutes(first), minutes(last))
+}
+
+/// When the newest file was written, from discovery, so it costs no scan.
+fn written_line(modified: SystemTime, now: SystemTime) -> String {
+    let written = chrono::DateTime::<chrono::Utc>::from(modified).format("%Y-%m-%d %H:%M");
+    let age = now.duration_since(modified).unwrap_or_default();
+    format!("Newest file written {written} UTC ({})", age_text(age))
+}
+
+/// Rough age like "5 minutes ago", for the banner.
+fn age_text(age: Duration) -> String {
+    let plural = |n: u64, unit: &str| format!("{n} {unit}{} ago", if n == 1 { "" } else { "s" });
+    match age.as_secs() {
+        0..60 => "just now".to_string(),
+        s @ 60..3600 => plural(s / 60, "minute"),
+        s @ 3600..86_400 => plural(s / 3600, "hour"),
+        s => plural(s / 86_400, "day"),
+    }
+}
+
+fn print_loaded(info: &SessionInfo) {
+    if info.is_counted() {
+        println!("{}", loaded_summary(info));
+    } else {
+        println!(
+            "Loaded {} file pattern(s), counting skipped (.info to count)",
+            info.file_pattern().patterns().len()
+        );
+    }
+}
+
+fn loaded_summary(info: &SessionInfo) -> String {
+    if info.project_count() > 1 {
+        format!(
+            "Loaded {} project(s), {} session(s), {} agent file(s)",
+            info.project_count(),
+            info.session_count(),
+            info.agent_count()
+        )
+    } else {
+        format!(
+            "Loaded {} session(s), {} agent file(s)",
+            info.session_count(),
+            info.agent_count()
+        )
+    }
+}
+
+/// `.tables`: every view and table in the session, built-in or user-created.
+fn tables_sql() -> String {
+    let builtin = VIEWS.iter().map(|v| sql_quote(v)).collect::<Vec<_>>().join(", ");
+    format!(
+        "SELECT name, type, CASE WHEN name IN ({builtin}) THEN 'built-in' ELSE 'user' END as source,
+                temporary
+         FROM (
+           SELECT view_name as name, 'view' as type, temporary FROM duckdb_views() WHERE NOT internal
+           UNION ALL
+           SELECT table_name, 'table', temporary FROM duckdb_tables() WHERE NOT internal
+         )
+         ORDER BY source, name"
+    )
+}
+
+/// `.sessions [prefix]`: one row per session, most recently active first.
+fn sessions_sql(prefix: Option<&str>) -> String {
+    let filter = prefix.map_or_else(String::new, |p| {
+        format!(
+            " WHERE starts_with(sessionId::VARCHAR, {})",
+            sql_quote(&p.to_lowercase())
+        )
+    });
+    format!(
+        "SELECT sessionId, project, first, last, messages
+         FROM sessions{filter}
+         ORDER BY last DESC"
+    )
+}
+
+/// `.grep`: case-insensitive regex search over human prompts and assistant
+/// text blocks, newest first, with the match shown in context.
+fn grep_sql(pattern: &str) -> String {
+    let pattern = sql_quote(pattern);
+    format!(
+        "SELECT timestamp, sessionId, role, uuid,
+                regexp_replace(
+                  regexp_extract(text, '(?is).{{0,40}}(?:' || {pattern} || ').{{0,40}}'),
+                  '\\s+', ' ', 'g') as context
+         FROM (
+           SELECT timestamp, sessionId, 'user' as role, uuid, content as text
+           FROM human_messages
+           UNION ALL
+           SELECT timestamp, sessionId, 'assistant', uuid, text
+           FROM text_blocks
+         )
+         WHERE regexp_matches(text, {pattern}, 'i')
+         ORDER BY timestamp DESC"
+    )
+}
+
+/// `.watch SECONDS QUERY`: clear the screen and re-run QUERY every SECONDS
+/// until Ctrl-C, to keep an eye on a live session.
+fn watch_query(session: &QuerySession, args: &str, options: &ReplOptions) {
+    let (seconds, sql) = args.split_once(char::is_whitespace).unwrap_or((args, ""));
+    let sql = sql.trim();
+    let Ok(seconds) = seconds.parse::<u64>() else {
+        println!("Usage: .watch <seconds> <query>");
+        return;
+    };
+    if sql.is_empty() {
+        println!("Usage: .watch <seconds> <query>");
+        return;
+    }
+    let interval = Duration::from_secs(seconds.max(1));
+    let Some(ctrl_c) = CtrlC::get() else {
+        eprintln!("Error: can't watch for Ctrl-C");
+        return;
+    };
+    let _armed = ctrl_c.arm();
+    loop {
+        let result = interruptible(session, || session.query(sql));
+        if matches!(&result, Err(e) if is_interrupted(e)) {
+            break;
+        }
+        // Clear the screen and home the cursor
+        print!("\x1b[2J\x1b[H");
+        let now = chrono::Local::now().format("%H:%M:%S");
+        println!("Every {}s: {sql}  ({now}; Ctrl-C to stop)\n", interval.as_secs());
+        match result {
+            Ok(result) => println!("{}", render_result(&result, options)),
+            Err(e) => eprintln!("Error: {e}"),
+        }
+        let _ = io::stdout().flush();
+
+        let next = Instant::now() + interval;
+        let mut stop = false;
+        while !stop && Instant::now() < next {
+            std::thread::sleep(Duration::from_millis(50));
+            stop = ctrl_c.take_press();
+        }
+        if stop {
+            break;
+        }
+    }
+}
+
+/// `.info`: discovery counts (walking the tree now under `--no-count`), the
+/// messages' time span, and the matched file patterns.
+fn print_info(session: &QuerySession) {
+    match session.counted_info() {
+        Ok(info) => {
+            println!("{}", loaded_summary(&info));
+            if let Some(span) = message_span(session) {
+                println!("{span}");
+            }
+            if let Some(modified) = info.newest_modified() {
+                println!("{}", written_line(modified, SystemTime::now()));
+            }
+            println!("Matched files: {}", session.info().file_pattern());
+        }
+        Err(e) => eprintln!("Error: {e}"),
+    }
+}
+
+/// Create the config file's `[macros]`. Failures are reported and skipped.
+pub fn register_macros(session: &QuerySession, macros: &BTreeMap<String, MacroDef>) {
+    for (name, def) in macros {
+        if let Err(e) = session.query(&def.create_sql(name)) {
+            eprintln!("Macro {name} failed: {e}");
+        }
+    }
+}
+
+/// Run configured warm-up queries, reporting progress on stderr unless quiet.
+/// Failures are reported and skipped so one bad query doesn't block startup.
+pub fn warm_up(session: &QuerySession, queries: &[WarmupQuery], quiet: bool) {
+    let total = queries.len();
+    for (i, query) in queries.iter().enumerate() {
+        let start = Instant::now();
+        let result = session.query(query.sql());
+        let elapsed_ms = start.elapsed().as_secs_f64() * 1000.0;
+        match result {
+            Ok(_) if !quiet => {
+                eprintln!("[{}/{total}] {} ({elapsed_ms:.1}ms)", i + 1, query.label());
+            }
+            Ok(_) => {}
+            Err(e) => eprintln!("[{}/{total}] {} failed: {e}", i + 1, query.label()),
+        }
+    }
+}
+
+/// Print discovery details to stderr: directories scanned, files matched,
+/// per-directory counts, and view creation timing.
+pub fn print_discovery_details(session: &QuerySession) {
+    let info = session.info();
+    eprintln!("Scanned {} director(ies):", info.directories().len());
+    for dir in info.directories() {
+        eprintln!(
+            "  {}: {} session(s), {} agent file(s), {} JSONL file(s)",
+            dir.path.display(),
+            dir.sessions,
+            dir.agents,
+            dir.jsonl_files
+        );
+    }
+    eprintln!("Matched files: {}", info.file_pattern());
+    eprintln!(
+        "Created views in {:.1}ms",
+        session.view_setup_time().as_secs_f64() * 1000.0
+    );
+}
+
+fn run_repl_loop(
+    editor: &mut ReplEditor,
+    session: &mut QuerySession,
+    options: &ReplOptions,
+    history: &Mutex<Vec<String>>,
+) -> Result<()> {
+    // Dot commands like `.mode` change these for the rest of the session
+    let options = &mut options.clone();
+    let mut multiline_buffer = String::new();
+
+    loop {
+        let prompt = if multiline_buffer.is_empty() {
+            prompt_text(session, options)
+        } else {
+            Cow::Borrowed(CONTINUATION_PROMPT)
+        };
+
+        // What Ctrl-R searches
+        if let Ok(mut entries) = history.lock() {
+            *entries = editor.history().iter().cloned().collect();
+        }
+
+        match editor.readline(&prompt) {
+            Ok(line) => {
+                let trimmed = line.trim();
+
+                // `.edit` also works mid-statement, taking over what was typed so far
+                if trimmed == ".edit" {
+                    let _ = editor.add_history_entry(trimmed);
+                    let draft = if multiline_buffer.is_empty() {
+                        options.last_sql.clone().unwrap_or_default()
+                    } else {
+                        std::mem::take(&mut multiline_buffer)
+                    };
+                    if matches!(
+                        edit_and_run(&draft, session, options, editor),
+                        DotCommandResult::Exit
+                    ) {
+                        break;
+                    }
+                    continue;
+                }
+
+                // Handle dot commands
+                if multiline_buffer.is_empty() && trimmed.starts_with('.') {
+                    let _ = editor.add_history_entry(trimmed);
+                    if matches!(
+                        handle_dot_command(trimmed, session, options, editor),
+                        DotCommandResult::Exit
+                    ) {
+                        break;
+                    }
+                    continue;
+                }
+                if multiline_buffer.is_empty() && trimmed.is_empty() {
+                    continue;
+                }
+
+                if !multiline_buffer.is_empty() {
+                    multiline_buffer.push('\n');
+                }
+                multiline_buffer.push_str(&line);
+
+                // `\g` runs the buffer as is, for a statement missing its `;`
+                let forced = trimmed.ends_with("\\g");
+                if forced {
+                    let end = multiline_buffer.trim_end().len() - 2;
+                    multiline_buffer.truncate(end);
+                }
+                if forced || is_complete_statement(&multiline_buffer) {
+                    let sql = std::mem::take(&mut multiline_buffer);
+                    let sql = sql.trim();
+                    if !sql.is_empty() {
+                        let _ = editor.add_history_entry(sql);
+                        execute_query(session, sql, options);
+                        options.last_sql = Some(sql.to_string());
+                    }
+                }
+            }
+            // Ctrl-C / Ctrl-D discard a partly typed statement; at an empty prompt they exit
+            Err(ReadlineError::Interrupted | ReadlineError::Eof) if !multiline_buffer.is_empty() => {
+                multiline_buffer.clear();
+                println!("(statement discarded)");
+            }
+            Err(ReadlineError::Interrupted | ReadlineError::Eof) => break,
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    println!("Goodbye!");
+    Ok(())
+}
+
+/// The main prompt: the configured template filled in, or `ccq> `.
+fn prompt_text<'a>(session: &QuerySession, options: &'a ReplOptions) -> Cow<'a, str> {
+    let Some(template) = &options.prompt else {
+        return Cow::Borrowed(PROMPT);
+    };
+    let info = session.info();
+    let context = PromptContext {
+        project: &prompt_project(session.options()),
+        sessions: info.is_counted().then(|| info.session_count()),
+        mode: options.mode.name(),
+    };
+    Cow::Owned(expand_prompt(template, &context))
+}
+
+/// Short name of the data being queried: the project or data directories'
+/// names, or `all`.
+fn prompt_project(options: &SessionOptions) -> String {
+    let name = |dir: &PathBuf| {
+        let dir = fs::canonicalize(dir).unwrap_or_else(|_| dir.clone());
+        dir.file_name().map_or_else(String::new, |n| n.to_string_lossy().into_owned())
+    };
+    if !options.data_dirs.is_empty() {
+        return options.data_dirs.iter().map(name).collect::<Vec<_>>().join("+");
+    }
+    options.project_dir.as_ref().map_or_else(|| "all".to_string(), name)
+}
+
+/// Whether `sql` ends with a `;` that terminates the statement: outside
+/// string literals, quoted identifiers, and comments, with parentheses closed.
+/// Uses the same scanner as [`split_statements`], so the REPL waits for input
+/// exactly where piped mode would keep reading the same statement.
+fn is_complete_statement(sql: &str) -> bool {
+    let mut depth = 0i32;
+    let mut terminated = false;
+    let mut open = false;
+    scan_sql(sql, |_, lexeme| match lexeme {
+        // A comment doesn't change whether a `;` came last
+        SqlLexeme::Comment { closed } => open |= !closed,
+        SqlLexeme::Quoted { closed } => {
+            open |= !closed;
+            terminated = false;
+        }
+        SqlLexeme::Byte(b'(') => {
+            depth += 1;
+            terminated = false;
+        }
+        SqlLexeme::Byte(b')') => {
+            depth -= 1;
+            terminated = false;
+        }
+        SqlLexeme::Byte(b';') => terminated = depth <= 0,
+        SqlLexeme::Byte(c) if c.is_ascii_whitespace() => {}
+        SqlLexeme::Byte(_) => terminated = false,
+    });
+    terminated && !open
+}
+
+fn execute_query(session: &QuerySession, sql: &str, options: &mut ReplOptions) {
+    if let Some(analyze) = options.analyze {
+        match interruptible(session, || explain_query(session, sql, analyze)) {
+            Ok(plan) => emit(options, &plan),
+            Err(e) if is_interrupted(&e) => eprintln!("Query cancelled"),
+            Err(e) => eprintln!("Error: {e}"),
+        }
+        return;
+    }
+    if can_stream(options) {
+        stream_query(session, sql, options);
+        return;
+    }
+    let start = Instant::now();
+    match interruptible(session, || session.query(sql)) {
+        Ok(result) => {
+            let elapsed = start.elapsed();
+            if let Some(text) = render_confirmed(&result, options) {
+                emit(options, &text);
+            }
+            if options.timer {
+                println!("{}", timer_line(elapsed, result.rows().len()));
+            }
+            options.last_result = Some(result);
+        }
+        Err(e) if is_interrupted(&e) => eprintln!("Query cancelled"),
+        Err(e) => {
+            eprintln!("Error: {e}");
+        }
+    }
+}
+
+/// Rows gathered before a long table result starts printing as it arrives;
+/// they fix the column widths for the rest.
+const STREAM_SAMPLE: usize = 100;
+
+/// Whether table results can be printed as they arrive: boxed tables headed
+/// for the terminal, unless the pager is on and needs the whole result or
+/// so many rows could be shown that `.set confirmrows` would ask first.
+fn can_stream(options: &ReplOptions) -> bool {
+    let shown = options.max_rows.unwrap_or(usize::MAX);
+    options.mode == OutputMode::Table
+        && options.expanded == Expanded::Off
+        && options.output.is_none()
+        && options.once.is_none()
+        && (options.max_rows.is_some() || !options.pager)
+        && options.confirm_rows.is_none_or(|confirm| shown <= confirm)
+}
+
+/// Run `sql`, printing its table row by row once it outgrows the sample (or
+/// `.maxrows`), so long scans show progress; shorter results render as usual.
+/// Either way the whole result is kept for `.last`.
+fn stream_query(session: &QuerySession, sql: &str, options: &mut ReplOptions) {
+    let start = Instant::now();
+    let table_options = TableOptions {
+        row_count: false,
+        ..options.table_options()
+    };
+    let limit = options.max_rows.unwrap_or(usize::MAX);
+    let threshold = STREAM_SAMPLE.min(limit.saturating_add(1));
+    let mut rows = Vec::new();
+    let mut nulls = Vec::new();
+    let mut stream: Option<TableStream> = None;
+    let outcome = interruptible(session, || {
+        session.query_each(sql, |columns, numeric, row, row_nulls| {
+            rows.push(row);
+            nulls.push(row_nulls);
+            let total = rows.len();
+            if let Some(table) = &stream {
+                if total <= limit {
+                    println!("{}", table.row(&rows[total - 1], &nulls[total - 1]));
+                }
+                return Ok(());
+            }
+            if total == threshold {
+                let shown = &rows[..threshold.min(limit)];
+                let table = TableStream::new(columns, shown, &nulls, numeric, &table_options);
+                println!("{}", table.header());
+                for (row, row_nulls) in shown.iter().zip(&nulls) {
+                    println!("{}", table.row(row, row_nulls));
+                }
+                stream = Some(table);
+            }
+            Ok(())
+        })
+    });
+    let total = rows.len();
+    if let Some(table) = &stream {
+        println!("{}", table.footer());
+        if total > limit {
+            println!("{}", truncation_note(limit, total));
+        } else if options.table_options().row_count {
+            println!("({total} row{})", if total == 1 { "" } else { "s" });
+        }
+    }
+    match outcome {
+        Ok((columns, numeric)) => {
+            let result = QueryResult::from_parts(columns, rows, nulls, numeric);
+            if stream.is_none()
+                && let Some(text) = render_confirmed(&result, options)
+            {
+                emit(options, &text);
+            }
+            if options.timer {
+                println!("{}", timer_line(start.elapsed(), total));
+            }
+            options.last_result = Some(result);
+        }
+        Err(e) if is_interrupted(&e) => eprintln!("Query cancelled"),
+        Err(e) => eprintln!("Error: {e}"),
+    }
+}
+
+/// Ctrl-C routing for running statements and `.watch`. The SIGINT hooks are
+/// installed once: while nothing is armed they fall through to the default
+/// action, and while a [`CtrlCGuard`] is alive a press only sets `pressed`.
+struct CtrlC {
+    idle: Arc<AtomicBool>,
+    pressed: Arc<AtomicBool>,
+}
+
+impl CtrlC {
+    /// The process-wide hooks, installed on first use. `None` if SIGINT
+    /// can't be hooked.
+    fn get() -> Option<&'static Self> {
+        static CTRL_C: OnceLock<Option<CtrlC>> = OnceLock::new();
+        CTRL_C
+            .get_or_init(|| {
+                let idle = Arc::new(AtomicBool::new(true));
+                let pressed = Arc::new(AtomicBool::new(false));
+                signal_hook::flag::register_conditional_default(SIGINT, Arc::clone(&idle)).ok()?;
+                signal_hook::flag::register(SIGINT, Arc::clone(&pressed)).ok()?;
+                Some(Self { idle, pressed })
+            })
+            .as_ref()
+    }
+
+    /// Catch Ctrl-C until the guard drops. Arming from idle forgets any
+    /// earlier press; nested guards share the outer one's.
+    fn arm(&self) -> CtrlCGuard<'_> {
+        let was_idle = self.idle.swap(false, Ordering::SeqCst);
+        if was_idle {
+            self.pressed.store(false, Ordering::SeqCst);
+        }
+        CtrlCGuard {
+            ctrl_c: self,
+            was_idle,
+        }
+    }
+
+    /// Whether Ctrl-C was pressed since the last call.
+    fn take_press(&self) -> bool {
+        self.pressed.swap(false, Ordering::SeqCst)
+    }
+}
+
+struct CtrlCGuard<'a> {
+    ctrl_c: &'a CtrlC,
+    was_idle: bool,
+}
+
+impl Drop for CtrlCGuard<'_> {
+    fn drop(&mut self) {
+        self.ctrl_c.idle.store(self.was_idle, Ordering::SeqCst);
+    }
+}
+
+/// Run `f` with Ctrl-C cancelling the statement instead of the REPL: SIGINT
+/// is caught for the duration and a watcher thread forwards it to `DuckDB`'s
+/// interrupt handle, so the query returns an "Interrupted" error.
+fn interruptible<T>(session: &QuerySession, f: impl FnOnce() -> T) -> T {
+    let Some(ctrl_c) = CtrlC::get() else {
+        return f();
+    };
+    let _armed = ctrl_c.arm();
+    let handle = session.interrupt_handle();
+    let (done, finished) = mpsc::channel::<()>();
+
+    std::thread::scope(|scope| {
+        scope.spawn(move || {
+            // Polls for presses; dropping `done` wakes it at once when `f` returns
+            let tick = Duration::from_millis(20);
+            while finished.recv_timeout(tick) == Err(RecvTimeoutError::Timeout) {
+                if ctrl_c.take_press() {
+                    handle.interrupt();
+                }
+            }
+        });
+        let result = f();
+        drop(done);
+        result
+    })
+}
+
+/// Render a query result, cut to `.maxrows` when it is headed for the
+/// terminal rather than a `.output`/`.once` file.
+fn render_result(result: &QueryResult, options: &ReplOptions) -> String {
+    let total = result.row_count();
+    let limit = options
+        .max_rows
+        .filter(|&max| total > max && options.once.is_none() && options.output.is_none());
+    let Some(max) = limit else {
+        return render_rows(result, options, &options.table_options());
+    };
+
+    let table_options = TableOptions {
+        row_count: false,
+        ..options.table_options()
+    };
+    let shown = result.head(max);
+    format!(
+        "{}\n{}",
+        render_rows(&shown, options, &table_options),
+        truncation_note(max, total)
+    )
+}
+
+/// [`render_result`], asking first when more than `.set confirmrows` rows
+/// would be printed to the terminal. `None` when the user declines.
+fn render_confirmed(result: &QueryResult, options: &mut ReplOptions) -> Option<String> {
+    let total = result.row_count();
+    let shown = options.max_rows.map_or(total, |max| total.min(max));
+    let ask = options.confirm_rows.is_some_and(|confirm| shown > confirm)
+        && options.output.is_none()
+        && options.once.is_none()
+        && io::stdin().is_terminal();
+    if !ask {
+        return Some(render_result(result, options));
+    }
+
+    let count = NumberFormat::from_style("comma")
+        .unwrap_or_default()
+        .format(&shown.to_string(), false);
+    print!("Display all {count} rows? [y/N/limit] ");
+    let _ = io::stdout().flush();
+    let mut answer = String::new();
+    let rows = io::stdin()
+        .read_line(&mut answer)
+        .ok()
+        .and_then(|_| confirmed_rows(answer.trim(), shown));
+    let Some(rows) = rows else {
+        println!("Result not shown; .last or .clip can still show it");
+        return None;
+    };
+    let max_rows = options.max_rows.replace(rows);
+    let text = render_result(result, options);
+    options.max_rows = max_rows;
+    Some(text)
+}
+
+/// Rows to show for an answer to the `[y/N/limit]` prompt: all `shown` for
+/// yes, at most N for a number, `None` for anything else.
+fn confirmed_rows(answer: &str, shown: usize) -> Option<usize> {
+    match answer.to_lowercase().as_str() {
+        "y" | "yes" => Some(shown),
+        n => n.parse::<usize>().ok().filter(|&n| n > 0).map(|n| n.min(shown)),
+    }
+}
+
+/// Footer for a result cut to `.maxrows`.
+fn truncation_note(shown: usize, total: usize) -> String {
+    let total = NumberFormat::from_style("comma")
+        .unwrap_or_default()
+        .format(&total.to_string(), false);
+    format!("… (showing {shown} of {total} rows)")
+}
+
+/// Render in the current `.mode`, switching tables to expanded records per `.x`.
+fn render_rows(result: &QueryResult, options: &ReplOptions, table_options: &TableOptions) -> String {
+    if options.mode != OutputMode::Table || options.expanded == Expanded::Off {
+        return result.render(options.mode, table_options);
+    }
+    if options.expanded == Expanded::Auto {
+        let table = result.to_table_with(table_options);
+        let fits = ratatui::crossterm::terminal::size()
+            .map_or(true, |(width, _)| !is_wider_than(&table, width));
+        if fits {
+            return table;
+        }
+    }
+    result.to_expanded(table_options)
+}
+
+/// Whether any line of `text` is longer than `width` columns.
+fn is_wider_than(text: &str, width: u16) -> bool {
+    text.lines().any(|line| crate::formatter::display_width(line) > usize::from(width))
+}
+
+/// `.last [MODE]`: render the previous result again, in MODE if given.
+fn show_last(command: &str, options: &mut ReplOptions) {
+    let mode = command
+        .split_whitespace()
+        .nth(1)
+        .map_or(Some(options.mode), OutputMode::parse);
+    let Some(mode) = mode else {
+        println!("Usage: .last [{}]", OutputMode::NAMES.join("|"));
+        return;
+    };
+    let Some(result) = options.last_result.take() else {
+        println!("No previous result");
+        return;
+    };
+    let current = std::mem::replace(&mut options.mode, mode);
+    let text = render_confirmed(&result, options);
+    options.mode = current;
+    options.last_result = Some(result);
+    if let Some(text) = text {
+        emit(options, &text);
+    }
+}
+
+/// `.clip [MODE]`: copy the previous result to the clipboard, in MODE or the
+/// current `.mode`. Tables are copied as TSV, which pastes into spreadsheets.
+fn copy_last(command: &str, options: &ReplOptions) {
+    let current = match options.mode {
+        OutputMode::Table => OutputMode::Tsv,
+        mode => mode,
+    };
+    let mode = command
+        .split_whitespace()
+        .nth(1)
+        .map_or(Some(current), OutputMode::parse);
+    let Some(mode) = mode else {
+        println!("Usage: .clip [{}]", OutputMode::NAMES.join("|"));
+        return;
+    };
+    let Some(result) = &options.last_result else {
+        println!("No previous result");
+        return;
+    };
+    let table_options = TableOptions {
+        color: false,
+        ..options.table_options()
+    };
+    let text = result.render(mode, &table_options);
+    match arboard::Clipboard::new().and_then(|mut clipboard| clipboard.set_text(text)) {
+        Ok(()) => {
+            let rows = result.row_count();
+            let plural = if rows == 1 { "" } else { "s" };
+            println!("Copied {rows} row{plural} as {}", mode.name());
+        }
+        Err(e) => eprintln!("Error: clipboard unavailable: {e}"),
+    }
+}
+
+/// Names accepted by `.set`.
+const SETTINGS: &[&str] =
+    &["confirmrows", "maxrows", "maxwidth", "nullvalue", "pager", "timer", "timezone"];
+
+/// `.set NAME VALUE`: change a setting; `.set` alone lists them all.
+fn set_command(session: &QuerySession, options: &mut ReplOptions, args: &str) {
+    let (name, value) = args.split_once(char::is_whitespace).unwrap_or((args, ""));
+    let name = name.to_lowercase();
+    let value = unquote(value.trim());
+    if name.is_empty() {
+        for setting in SETTINGS {
+            println!("{setting:<10} {}", setting_value(session, options, setting));
+        }
+        return;
+    }
+    if !SETTINGS.contains(&name.as_str()) {
+        println!("Unknown setting: {name}. Settings: {}", SETTINGS.join(", "));
+        return;
+    }
+    if value.is_empty() {
+        println!("{}", setting_value(session, options, &name));
+        return;
+    }
+    let applied = if name == "timezone" {
+        // Stored timestamps are UTC; results show them in this zone
+        value
+            .parse::<chrono_tz::Tz>()
+            .map(|zone| session.set_time_zone((zone != chrono_tz::UTC).then_some(zone)))
+            .map_err(|_| format!("Unknown time zone: {value} (e.g. America/Los_Angeles, UTC)"))
+    } else {
+        apply_setting(options, &name, value)
+    };
+    if let Err(message) = applied {
+        println!("{message}");
+    }
+}
+
+/// Set one of the display settings `.set` shares with the formatter.
+fn apply_setting(
+    options: &mut ReplOptions,
+    name: &str,
+    value: &str,
+) -> std::result::Result<(), String> {
+    let limit = |value: &str| match value {
+        "off" | "0" => Ok(None),
+        n => n.parse::<usize>().map(Some).map_err(|_| format!("Usage: .set {name} N|off")),
+    };
+    let switch = |value: &str| match value {
+        "on" => Ok(true),
+        "off" => Ok(false),
+        _ => Err(format!("Usage: .set {name} on|off")),
+    };
+    match name {
+        "confirmrows" => options.confirm_rows = limit(value)?,
+        "maxrows" => options.max_rows = limit(value)?,
+        "maxwidth" => options.max_width = limit(value)?,
+        "nullvalue" => options.null_value = (value != "NULL").then(|| value.to_string()),
+        "pager" => options.pager = switch(value)?,
+        "timer" => options.timer = switch(value)?,
+        _ => return Err(format!("Unknown setting: {name}")),
+    }
+    Ok(())
+}
+
+fn setting_value(session: &QuerySession, options: &ReplOptions, name: &str) -> String {
+    let limit = |value: Option<usize>| value.map_or_else(|| "off".to_string(), |n| n.to_string());
+    let switch = |on: bool| if on { "on" } else { "off" }.to_string();
+    match name {
+        "confirmrows" => limit(options.confirm_rows),
+        "maxrows" => limit(options.max_rows),
+        "maxwidth" => limit(options.max_width),
+        "nullvalue" => options.null_value.clone().unwrap_or_else(|| "NULL".to_string()),
+        "pager" => switch(options.pager),
+        "timer" => switch(options.timer),
+        "timezone" => session
+            .time_zone()
+            .map_or("UTC", |zone| zone.name())
+            .to_string(),
+        _ => String::new(),
+    }
+}
+
+/// Strip one pair of matching surrounding quotes.
+fn unquote(value: &str) -> &str {
+    ['\'', '"']
+        .iter()
+        .find_map(|&q| value.strip_prefix(q).and_then(|v| v.strip_suffix(q)))
+        .unwrap_or(value)
+}
+
+/// `.timer` report: wall-clock query time (rendering excluded) and rows returned.
+fn timer_line(elapsed: Duration, rows: usize) -> String {
+    let plural = if rows == 1 { "" } else { "s" };
+    format!("Run Time: {:.1}ms ({rows} row{plural})", elapsed.as_secs_f64() * 1000.0)
+}
+
+/// `.mode`, `.output`, `.once`, `.pager`, `.timer`, `.maxrows`, `.width`,
+/// `.set`, and `.x`; returns whether `cmd` was one of them.
+fn handle_output_setting(
+    cmd: &str,
+    command: &str,
+    session: &QuerySession,
+    options: &mut ReplOptions,
+) -> bool {
+    if cmd == ".mode" || cmd.starts_with(".mode ") {
+        match command.split_whitespace().nth(1) {
+            None => println!("{}", options.mode.name()),
+            Some(name) => match OutputMode::parse(name) {
+                Some(mode) => options.mode = mode,
+                None => println!("Usage: .mode {}", OutputMode::NAMES.join("|")),
+            },
+        }
+        return true;
+    }
+
+    if cmd == ".output" || cmd.starts_with(".output ") {
+        options.output = None;
+        if let Some(path) = redirect_target(command) {
+            // Start the file fresh; results are appended from here on
+            match fs::File::create(&path) {
+                Ok(_) => options.output = Some(path),
+                Err(e) => eprintln!("Error: {}: {e}", path.display()),
+            }
+        }
+        return true;
+    }
+
+    if cmd == ".once" || cmd.starts_with(".once ") {
+        match redirect_target(command) {
+            Some(path) => options.once = Some(path),
+            None => println!("Usage: .once <file>"),
+        }
+        return true;
+    }
+
+    if cmd == ".pager" || cmd.starts_with(".pager ") {
+        match command.split_whitespace().nth(1) {
+            Some("on") => options.pager = true,
+            Some("off") => options.pager = false,
+            None => println!("{}", if options.pager { "on" } else { "off" }),
+            Some(_) => println!("Usage: .pager on|off"),
+        }
+        return true;
+    }
+
+    if cmd == ".timer" || cmd.starts_with(".timer ") {
+        match command.split_whitespace().nth(1) {
+            Some("on") => options.timer = true,
+            Some("off") => options.timer = false,
+            None => println!("{}", if options.timer { "on" } else { "off" }),
+            Some(_) => println!("Usage: .timer on|off"),
+        }
+        return true;
+    }
+
+    if cmd == ".maxrows" || cmd.starts_with(".maxrows ") {
+        match command.split_whitespace().nth(1) {
+            None => match options.max_rows {
+                Some(max) => println!("{max}"),
+                None => println!("off"),
+            },
+            Some("off" | "0") => options.max_rows = None,
+            Some(n) => match n.parse::<usize>() {
+                Ok(max) => options.max_rows = Some(max),
+                Err(_) => println!("Usage: .maxrows N|off"),
+            },
+        }
+        return true;
+    }
+
+    if cmd == ".set" || cmd.starts_with(".set ") {
+        set_command(session, options, command[".set".len()..].trim());
+        return true;
+    }
+
+    if cmd == ".x" || cmd.starts_with(".x ") {
+        let expanded = match command.split_whitespace().nth(1) {
+            // Bare `.x` toggles, like psql's `\x`
+            None if options.expanded == Expanded::On => Some(Expanded::Off),
+            None | Some("on") => Some(Expanded::On),
+            Some("off") => Some(Expanded::Off),
+            Some("auto") => Some(Expanded::Auto),
+            Some(_) => None,
+        };
+        match expanded {
+            Some(expanded) => {
+                options.expanded = expanded;
+                println!("Expanded display is {}", expanded.name());
+            }
+            None => println!("Usage: .x [on|off|auto]"),
+        }
+        return true;
+    }
+
+    if cmd == ".width" || cmd.starts_with(".width ") {
+        let args: Vec<&str> = command.split_whitespace().skip(1).collect();
+        if let Err(usage) = set_width(options, &args) {
+            println!("{usage}");
+        }
+        return true;
+    }
+
+    false
+}
+
+/// `.width [COLUMN] [N|off]`: show limits, or set/clear the global or a
+/// per-column limit. `.width off` clears every limit.
+fn set_width(options: &mut ReplOptions, args: &[&str]) -> std::result::Result<(), &'static str> {
+    const USAGE: &str = "Usage: .width [column] N|off";
+    let parse = |arg: &str| match arg {
+        "off" | "0" => Ok(None),
+        n => n.parse::<usize>().map(Some).map_err(|_| USAGE),
+    };
+    match args {
+        [] => {
+            let global = options.max_width.map_or_else(|| "off".to_string(), |w| w.to_string());
+            println!("all columns: {global}");
+            for (column, width) in &options.column_widths {
+                println!("{column}: {width}");
+            }
+        }
+        [arg] => {
+            options.max_width = parse(arg)?;
+            if options.max_width.is_none() {
+                options.column_widths.clear();
+            }
+        }
+        [column, arg] => {
+            let width = parse(arg)?;
+            options.column_widths.retain(|(name, _)| !name.eq_ignore_ascii_case(column));
+            if let Some(width) = width {
+                options.column_widths.push(((*column).to_string(), width));
+            }
+        }
+        _ => return Err(USAGE),
+    }
+    Ok(())
+}
+
+/// Print a result, or write it to the `.once` file (truncated) or the
+/// `.output` file (appended).
+fn emit(options: &mut ReplOptions, text: &str) {
+    let (path, append) = match (options.once.take(), &options.output) {
+        (Some(path), _) => (path, false),
+        (None, Some(path)) => (path.clone(), true),
+        (None, None) if options.pager => {
+            if let Err(e) = crate::pager::print(text) {
+                eprintln!("Pager failed ({e}); use .pager off to disable");
+                println!("{text}");
+            }
+            return;
+        }
+        (None, None) => {
+            println!("{text}");
+            return;
+        }
+    };
+    let written = fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(append)
+        .truncate(!append)
+        .open(&path)
+        .and_then(|mut file| writeln!(file, "{text}"));
+    if let Err(e) = written {
+        eprintln!("Error: {}: {e}", path.display());
+    }
+}
+
+/// `.output [FILE]` / `.once FILE`: the redirect target, `None` for stdout.
+fn redirect_target(command: &str) -> Option<PathBuf> {
+    let arg = command.split_once(char::is_whitespace)?.1.trim();
+    (!arg.is_empty() && arg != "stdout").then(|| PathBuf::from(arg))
+}
+
+/// Run `sql` under `EXPLAIN [ANALYZE]` and render the plan text with the
+/// wall-clock time it took.
+fn explain_query(session: &QuerySession, sql: &str, analyze: Analyze) -> Result<String> {
+    let start = Instant::now();
+    let result = session.query(&analyze.wrap(sql))?;
+    let elapsed = start.elapsed();
+
+    // EXPLAIN returns (explain_key, explain_value); the plan is pre-rendered text
+    let plan = result
+        .rows()
+        .iter()
+        .filter_map(|row| row.last())
+        .map(|plan| plan.trim_end())
+        .collect::<Vec<_>>()
+        .join("\n");
+    Ok(format!("{plan}\nTime: {:.1}ms", elapsed.as_secs_f64() * 1000.0))
+}
+
+/// `.explain [analyze] QUERY`: the plan as an indented operator tree, falling
+/// back to `DuckDB`'s drawn plan when it can't produce JSON.
+fn explain_tree(session: &QuerySession, args: &str, options: &mut ReplOptions) {
+    let (analyze, sql) = match args.split_once(char::is_whitespace) {
+        Some((word, rest)) if word.eq_ignore_ascii_case("analyze") => (Analyze::Profile, rest),
+        _ => (Analyze::Explain, args),
+    };
+    let sql = sql.trim().trim_end_matches(';');
+    if sql.is_empty() {
+        println!("Usage: .explain [analyze] <query>");
+        return;
+    }
+    let format = match analyze {
+        Analyze::Explain => "EXPLAIN (FORMAT JSON)",
+        Analyze::Profile => "EXPLAIN (ANALYZE, FORMAT JSON)",
+    };
+    let start = Instant::now();
+    let tree = interruptible(session, || session.query(&format!("{format} {sql}"))).map(|result| {
+        result.rows().iter().filter_map(|row| row.last()).find_map(|json| crate::plan::render(json))
+    });
+    let elapsed_ms = start.elapsed().as_secs_f64() * 1000.0;
+    let text = match tree {
+        Ok(Some(tree)) => Ok(format!("{tree}\nTime: {elapsed_ms:.1}ms")),
+        Ok(None) => explain_query(session, sql, analyze),
+        Err(e) => Err(e),
+    };
+    match text {
+        Ok(text) => emit(options, &text),
+        Err(e) if is_interrupted(&e) => eprintln!("Query cancelled"),
+        Err(e) => eprintln!("Error: {e}"),
+    }
+}
+
+fn handle_dot_command(
+    command: &str,
+    session: &mut QuerySession,
+    options: &mut ReplOptions,
+    editor: &mut ReplEditor,
+) -> DotCommandResult {
+    let cmd = command.to_lowercase();
+
+    if cmd == ".quit" || cmd == ".exit" || cmd == ".q" {
+        return DotCommandResult::Exit;
+    }
+
+    if cmd == ".help" || cmd == ".h" {
+        print_help();
+        return DotCommandResult::Continue;
+    }
+
+    if cmd.starts_with(".help ") || cmd.starts_with(".h ") {
+        let topic = command.split_whitespace().nth(1).unwrap_or_default();
+        print_topic(session, topic, options);
+        return DotCommandResult::Continue;
+    }
+
+    if handle_output_setting(&cmd, command, session, options) {
+        return DotCommandResult::Continue;
+    }
+
+    if cmd == ".tables" {
+        execute_query(session, &tables_sql(), options);
+        return DotCommandResult::Continue;
+    }
+
+    if cmd == ".last" || cmd.starts_with(".last ") {
+        show_last(command, options);
+        return DotCommandResult::Continue;
+    }
+
+    if cmd == ".clip" || cmd.starts_with(".clip ") {
+        copy_last(command, options);
+        return DotCommandResult::Continue;
+    }
+
+    if cmd == ".grep" || cmd.starts_with(".grep ") {
+        let pattern = command[".grep".len()..].trim();
+        if pattern.is_empty() {
+            println!("Usage: .grep <regex>");
+        } else {
+            execute_query(session, &grep_sql(pattern), options);
+        }
+        return DotCommandResult::Continue;
+    }
+
+    if cmd == ".cost" || cmd.starts_with(".cost ") {
+        show_cost(session, command, options);
+        return DotCommandResult::Continue;
+    }
+
+    if cmd == ".watch" || cmd.starts_with(".watch ") {
+        watch_query(session, command[".watch".len()..].trim(), options);
+        return DotCommandResult::Continue;
+    }
+
+    if cmd == ".info" {
+        print_info(session);
+        return DotCommandResult::Continue;
+    }
+
+    if cmd == ".sessions" || cmd.starts_with(".sessions ") {
+        let sql = sessions_sql(command.split_whitespace().nth(1));
+        execute_query(session, &sql, options);
+        return DotCommandResult::Continue;
+    }
+
+    if cmd == ".schema" || cmd == ".s" {
+        for view in VIEWS {
+            println!("\n=== {view} ===");
+            execute_query(session, &format!("DESCRIBE {view}"), options);
+        }
+        return DotCommandResult::Continue;
+    }
+
+    if cmd.starts_with(".schema ") || cmd.starts_with(".s ") {
+        let view = command.split_whitespace().nth(1).unwrap_or("");
+        execute_query(session, &format!("DESCRIBE {view}"), options);
+        return DotCommandResult::Continue;
+    }
+
+    if handle_plan_command(&cmd, command, session, options) {
+        return DotCommandResult::Continue;
+    }
+
+    if handle_scope_command(&cmd, command, session)
+        || handle_saved_query(&cmd, command, session, options)
+    {
+        return DotCommandResult::Continue;
+    }
+
+    if handle_materialize(&cmd, command, session, options) {
+        return DotCommandResult::Continue;
+    }
+
+    if cmd == ".read" || cmd.starts_with(".read ") {
+        let path = command[".read".len()..].trim();
+        if path.is_empty() {
+            println!("Usage: .read <file>");
+            return DotCommandResult::Continue;
+        }
+        return read_script(path, session, options, editor);
+    }
+
+    if handle_message_command(&cmd, command, session, options) {
+        return DotCommandResult::Continue;
+    }
+
+    if cmd == ".paste" || cmd.starts_with(".paste ") {
+        match command.split_whitespace().nth(1) {
+            Some(table) => paste_table(session, editor, table),
+            None => println!("Usage: .paste <table>"),
+        }
+        return DotCommandResult::Continue;
+    }
+
+    println!("Unknown command: {command}. Type .help for usage.");
+    DotCommandResult::Continue
+}
+
+/// `.transcript`, `.tree`, `.source`, and `.show`; returns whether `cmd` was one of them.
+fn handle_message_command(
+    cmd: &str,
+    command: &str,
+    session: &QuerySession,
+    options: &mut ReplOptions,
+) -> bool {
+    if cmd == ".transcript" || cmd.starts_with(".transcript ") {
+        let mut args = command.split_whitespace().skip(1);
+        match (args.next(), args.next().map(str::parse::<usize>)) {
+            (Some(id), None) => show_transcript(session, id, TRANSCRIPT_DEPTH, options),
+            (Some(id), Some(Ok(depth))) => show_transcript(session, id, depth, options),
+            _ => println!("Usage: .transcript <uuid|session> [depth]"),
+        }
+        return true;
+    }
+
+    if cmd == ".tree" || cmd.starts_with(".tree ") {
+        match command.split_whitespace().nth(1) {
+            Some(prefix) => show_tree(session, prefix, options),
+            None => println!("Usage: .tree <session>"),
+        }
+        return true;
+    }
+
+    if cmd == ".source" || cmd.starts_with(".source ") {
+        match command.split_whitespace().nth(1) {
+            Some(uuid) => open_source(session, uuid),
+            None => println!("Usage: .source <uuid>"),
+        }
+        return true;
+    }
+
+    if cmd == ".show" || cmd.starts_with(".show ") {
+        let mut args = command.split_whitespace().skip(1);
+        match (args.next(), args.next().map(str::parse::<usize>)) {
+            (Some(uuid), None) => show_message(session, uuid, None, options.color),
+            (Some(uuid), Some(Ok(depth))) => {
+                show_message(session, uuid, Some(depth), options.color);
+            }
+            _ => println!("Usage: .show <uuid> [depth]"),
+        }
+        return true;
+    }
+
+    false
+}
+
+/// `.explain` and `.check`; returns whether `cmd` was one of them.
+fn handle_plan_command(
+    cmd: &str,
+    command: &str,
+    session: &QuerySession,
+    options: &mut ReplOptions,
+) -> bool {
+    if cmd == ".explain" || cmd.starts_with(".explain ") {
+        explain_tree(session, command[".explain".len()..].trim(), options);
+        return true;
+    }
+
+    if cmd == ".check" || cmd.starts_with(".check ") {
+        let sql = command[".check".len()..].trim();
+        if sql.is_empty() {
+            println!("Usage: .check <query>");
+        } else {
+            match check_statement(session, sql) {
+                Ok(()) => println!("ok"),
+                Err(e) => eprintln!("Error: {e}"),
+            }
+        }
+        return true;
+    }
+
+    false
+}
+
+/// `.open` and `.reload`; returns whether `cmd` was one of them.
+fn handle_scope_command(cmd: &str, command: &str, session: &mut QuerySession) -> bool {
+    if cmd == ".open" || cmd.starts_with(".open ") {
+        match command.split_whitespace().nth(1) {
+            Some("all") => switch_scope(session, None),
+            Some(arg) => open_scope_or_source(session, arg),
+            None => println!("Usage: .open <session-prefix>|<uuid>|all"),
+        }
+        return true;
+    }
+
+    if cmd == ".reload" {
+        match session.reload() {
+            Ok(()) => print_loaded(session.info()),
+            Err(e) => eprintln!("Error: {e}"),
+        }
+        return true;
+    }
+
+    false
+}
+
+/// `.save`, `.saved`, and `.run`; returns whether `cmd` was one of them.
+fn handle_saved_query(
+    cmd: &str,
+    command: &str,
+    session: &QuerySession,
+    options: &mut ReplOptions,
+) -> bool {
+    if cmd == ".saved" {
+        list_saved();
+    } else if cmd == ".save" || cmd.starts_with(".save ") {
+        save_query(command, options);
+    } else if cmd == ".run" || cmd.starts_with(".run ") {
+        run_saved(session, command, options);
+    } else {
+        return false;
+    }
+    true
+}
+
+/// `.export` and `.store`; returns whether `cmd` was one of them.
+fn handle_materialize(
+    cmd: &str,
+    command: &str,
+    session: &QuerySession,
+    options: &ReplOptions,
+) -> bool {
+    if cmd == ".export" || cmd.starts_with(".export ") {
+        match export_sql(command, options.last_sql.as_deref()) {
+            Ok((sql, path)) => match session.query(&sql) {
+                Ok(_) => println!("Exported to {path}"),
+                Err(e) => eprintln!("Error: {e}"),
+            },
+            Err(usage) => println!("{usage}"),
+        }
+        return true;
+    }
+
+    if cmd == ".store" || cmd.starts_with(".store ") {
+        let stored = store_sql(command, options.last_sql.as_deref()).map(|(sql, table)| {
+            session
+                .query(&sql)
+                .and_then(|_| session.query(&format!("SELECT count(*) FROM {}", sql_ident(&table))))
+                .map(|count| (table, count))
+        });
+        match stored {
+            Ok(Ok((table, count))) => {
+                let rows = count.rows().first().map_or("0", |row| row[0].as_str());
+                println!("Stored {rows} row(s) in temp table {table}");
+            }
+            Ok(Err(e)) => eprintln!("Error: {e}"),
+            Err(usage) => println!("{usage}"),
+        }
+        return true;
+    }
+
+    false
+}
+
+/// `.store NAME [QUERY]`: the statement saving the query, or the last one
+/// run, as temp table NAME, and the name.
+fn store_sql(command: &str, last_sql: Option<&str>) -> std::result::Result<(String, String), String> {
+    let args = command[".store".len()..].trim();
+    let (name, query) = args
+        .split_once(char::is_whitespace)
+        .map_or((args, None), |(name, query)| (name, Some(query.trim())));
+    if name.is_empty() {
+        return Err("Usage: .store <name> [query]".to_string());
+    }
+    let query = query
+        .or(last_sql)
+        .ok_or_else(|| "No query to store: run one first or give it inline".to_string())?;
+    let query = query.trim().trim_end_matches(';');
+    let sql = format!("CREATE OR REPLACE TEMP TABLE {} AS {query}", sql_ident(name));
+    Ok((sql, name.to_string()))
+}
+
+/// `.export FORMAT FILE [QUERY]`: the `COPY` statement writing the query, or
+/// the last one run, to FILE, and the path.
+fn export_sql(command: &str, last_sql: Option<&str>) -> std::result::Result<(String, String), String> {
+    let usage = || "Usage: .export csv|json|ndjson|parquet <file> [query]".to_string();
+    let args = command[".export".len()..].trim();
+    let (format, rest) = args.split_once(char::is_whitespace).ok_or_else(usage)?;
+    let rest = rest.trim();
+    let (path, query) = rest
+        .split_once(char::is_whitespace)
+        .map_or((rest, None), |(path, query)| (path, Some(query.trim())));
+    let options = match format.to_lowercase().as_str() {
+        "csv" => "FORMAT csv, HEADER true",
+        "json" => "FORMAT json, ARRAY true",
+        "ndjson" => "FORMAT json",
+        "parquet" => "FORMAT parquet",
+        _ => return Err(usage()),
+    };
+    let query = query
+        .or(last_sql)
+        .ok_or_else(|| "No query to export: run one first or give it inline".to_string())?;
+    let query = query.trim().trim_end_matches(';');
+    Ok((format!("COPY ({query}) TO {} ({options})", sql_quote(path)), path.to_string()))
+}
+
+/// `.read FILE`: run a script's statements and dot commands as if typed.
+fn read_script(
+    path: &str,
+    session: &mut QuerySession,
+    options: &mut ReplOptions,
+    editor: &mut ReplEditor,
+) -> DotCommandResult {
+    let script = match fs::read_to_string(path) {
+        Ok(script) => script,
+        Err(e) => {
+            eprintln!("Error: {path}: {e}");
+            return DotCommandResult::Continue;
+        }
+    };
+    run_interactively(&script, session, options, editor)
+}
+
+/// `.edit`: open `draft` in `$VISUAL`/`$EDITOR`, then run what was saved as
+/// if typed at the prompt. Saving an empty file runs nothing.
+fn edit_and_run(
+    draft: &str,
+    session: &mut QuerySession,
+    options: &mut ReplOptions,
+    editor: &mut ReplEditor,
+) -> DotCommandResult {
+    let text = match crate::source::edit_text(draft) {
+        Ok(Some(text)) => text,
+        Ok(None) => {
+            eprintln!("Error: set $VISUAL or $EDITOR to use .edit");
+            return DotCommandResult::Continue;
+        }
+        Err(e) => {
+            eprintln!("Error: {e}; nothing run");
+            return DotCommandResult::Continue;
+        }
+    };
+    let text = text.trim();
+    if text.is_empty() {
+        println!("Nothing to run");
+        return DotCommandResult::Continue;
+    }
+    // Echo it, so the scrollback shows what produced the result
+    println!("{text}");
+    let _ = editor.add_history_entry(text);
+    let result = run_interactively(text, session, options, editor);
+    options.last_sql = Some(text.to_string());
+    result
+}
+
+/// Run each statement and dot command in `script` as if typed at the
+/// prompt, stopping early on `.quit`.
+fn run_interactively(
+    script: &str,
+    session: &mut QuerySession,
+    options: &mut ReplOptions,
+    editor: &mut ReplEditor,
+) -> DotCommandResult {
+    for statement in script_commands(script) {
+        if statement.starts_with('.') {
+            if matches!(
+                handle_dot_command(statement, session, options, editor),
+                DotCommandResult::Exit
+            ) {
+                return DotCommandResult::Exit;
+            }
+        } else {
+            execute_query(session, statement, options);
+        }
+    }
+    DotCommandResult::Continue
+}
+
+/// Split a script into dot commands and SQL statements in source order, the
+/// way the prompt reads them: a line starting with `.` is a dot command
+/// unless it continues an unfinished statement.
+fn script_commands(script: &str) -> Vec<&str> {
+    let mut commands = Vec::new();
+    let mut pending = 0;
+    let mut offset = 0;
+    for line in script.split_inclusive('\n') {
+        let sql = &script[pending..offset];
+        let trimmed = line.trim();
+        if trimmed.starts_with('.') && (sql.trim().is_empty() || is_complete_statement(sql)) {
+            commands.extend(split_statements(sql));
+            commands.push(trimmed);
+            pending = offset + line.len();
+        }
+        offset += line.len();
+    }
+    commands.extend(split_statements(&script[pending..]));
+    commands
+}
+
+/// `.save NAME [SQL]`: save SQL, or the last query entered, under NAME.
+fn save_query(command: &str, options: &ReplOptions) {
+    let args = command[".save".len()..].trim();
+    let (name, sql) = args
+        .split_once(char::is_whitespace)
+        .map_or((args, None), |(name, sql)| (name, Some(sql.trim())));
+    let Some(sql) = sql.or(options.last_sql.as_deref()) else {
+        println!("Usage: .save <name> [query] (without a query, saves the last one run)");
+        return;
+    };
+    if name.is_empty() {
+        println!("Usage: .save <name> [query]");
+        return;
+    }
+    let Some(dir) = crate::saved::dir() else {
+        eprintln!("Error: No home directory");
+        return;
+    };
+    match crate::saved::save(&dir, name, sql) {
+        Ok(path) => println!("Saved {name} to {}", path.display()),
+        Err(e) => eprintln!("Error: {e}"),
+    }
 }
 
-fn print_banner(session: &QuerySession) {
-    let info = session.info();
-    if info.project_count() > 1 {
-        println!(
-            "Loaded {} project(s), {} session(s), {} agent file(s)",
-            info.project_count(),
-            info.session_count(),
-            info.agent_count()
-        );
-    } else {
-        println!(
-            "Loaded {} session(s), {} agent file(s)",
-            info.session_count(),
-            info.agent_count()
-        );
+/// `.saved`: list saved queries with the first line of each.
+fn list_saved() {
+    let queries = crate::saved::dir().map(|dir| crate::saved::list(&dir)).unwrap_or_default();
+    if queries.is_empty() {
+        println!("No saved queries (.save <name> to add one)");
+    }
+    let width = queries.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+    for (name, sql) in &queries {
+        println!("{name:width$}  {}", sql.lines().next().unwrap_or_default());
     }
-    println!("Type \".help\" for usage hints.\n");
 }
 
-fn run_repl_loop(editor: &mut DefaultEditor, session: &QuerySession) -> Result<()> {
-    let mut multiline_buffer = String::new();
+/// `.run NAME [ARGS...]`: run a saved query with `$1`-style arguments.
+fn run_saved(session: &QuerySession, command: &str, options: &mut ReplOptions) {
+    let mut words = command.split_whitespace().skip(1);
+    let Some(name) = words.next() else {
+        println!("Usage: .run <name> [args...]");
+        return;
+    };
+    let args: Vec<&str> = words.collect();
+    let sql = crate::saved::dir()
+        .ok_or_else(|| Error::from(io::Error::new(io::ErrorKind::NotFound, "No home directory")))
+        .and_then(|dir| crate::saved::load(&dir, name))
+        .and_then(|sql| crate::saved::substitute_args(&sql, &args));
+    match sql {
+        Ok(sql) => {
+            for statement in split_statements(&sql) {
+                execute_query(session, statement, options);
+            }
+        }
+        Err(e) => eprintln!("Error: {e}"),
+    }
+}
 
-    loop {
-        let prompt = if multiline_buffer.is_empty() {
-            PROMPT
-        } else {
-            CONTINUATION_PROMPT
-        };
+/// `.cost [account]`: tokens and estimated cost by model, or by account type
+/// and model.
+fn show_cost(session: &QuerySession, command: &str, options: &mut ReplOptions) {
+    match command.split_whitespace().nth(1) {
+        None => execute_query(session, crate::pricing::SUMMARY_SQL, options),
+        Some("account") => execute_query(session, crate::pricing::ACCOUNT_SUMMARY_SQL, options),
+        Some(_) => println!("Usage: .cost [account]"),
+    }
+}
 
-        match editor.readline(prompt) {
-            Ok(line) => {
-                let trimmed = line.trim();
+/// `.open`: rebuild the views over the sessions matching `prefix`, or every
+/// session when `None`, keeping temp tables and REPL settings.
+fn switch_scope(session: &mut QuerySession, prefix: Option<String>) {
+    match session.rescope(prefix) {
+        Ok(()) => print_loaded(session.info()),
+        Err(e) => eprintln!("Error: {e}"),
+    }
+}
 
-                // Handle multi-line mode
-                if !multiline_buffer.is_empty() {
-                    multiline_buffer.push('\n');
-                    multiline_buffer.push_str(&line);
+/// `.open ARG`: narrow to the sessions matching ARG; when no session file
+/// does, open the source line of the message whose uuid starts with ARG.
+fn open_scope_or_source(session: &mut QuerySession, arg: &str) {
+    match session.rescope(Some(arg.to_string())) {
+        Ok(()) => print_loaded(session.info()),
+        Err(Error::SessionNotFound(_)) => open_source(session, arg),
+        Err(e) => eprintln!("Error: {e}"),
+    }
+}
 
-                    // Check if query ends with semicolon
-                    if trimmed.ends_with(';') {
-                        let _ = editor.add_history_entry(&multiline_buffer);
-                        execute_query(session, &multiline_buffer);
-                        multiline_buffer.clear();
-                    }
-                    continue;
-                }
+/// `.transcript UUID [DEPTH]`: the message with this many ancestors and
+/// levels of replies; `.transcript SESSION`: the whole session.
+fn show_transcript(session: &QuerySession, id: &str, depth: usize, options: &mut ReplOptions) {
+    let messages = match crate::inspect::fetch(session, id) {
+        Ok(view) => crate::replay::load_thread(session, &view.uuid, depth),
+        Err(Error::MessageNotFound(_)) => session
+            .resolve_session_id(id)
+            .and_then(|session_id| crate::replay::load(session, &session_id, false)),
+        Err(e) => Err(e),
+    };
+    match messages {
+        Ok(messages) if messages.is_empty() => println!("No messages"),
+        Ok(messages) => emit(options, crate::replay::render_all(&messages).trim_end()),
+        Err(e) => eprintln!("Error: {e}"),
+    }
+}
 
-                // Handle dot commands
-                if trimmed.starts_with('.') {
-                    let _ = editor.add_history_entry(trimmed);
-                    if matches!(handle_dot_command(trimmed, session), DotCommandResult::Exit) {
-                        break;
-                    }
-                }
-                // Handle SQL queries
-                else if !trimmed.is_empty() {
-                    if trimmed.ends_with(';') {
-                        let _ = editor.add_history_entry(trimmed);
-                        execute_query(session, trimmed);
-                    } else {
-                        // Start multi-line mode
-                        multiline_buffer = line;
-                    }
-                }
+/// `.tree SESSION`: the session's messages as a parent/child tree.
+fn show_tree(session: &QuerySession, prefix: &str, options: &mut ReplOptions) {
+    let nodes = session
+        .resolve_session_id(prefix)
+        .and_then(|session_id| crate::tree::load(session, &session_id));
+    match nodes {
+        Ok(nodes) if nodes.is_empty() => println!("No messages"),
+        Ok(nodes) => emit(options, crate::tree::render(&nodes).trim_end()),
+        Err(e) => eprintln!("Error: {e}"),
+    }
+}
+
+/// Open a message's source line in the editor, or print `path:line`.
+fn open_source(session: &QuerySession, uuid: &str) {
+    match crate::source::locate(session, uuid) {
+        Ok(location) => {
+            println!("{location}");
+            if let Err(e) = crate::source::open_in_editor(&location) {
+                eprintln!("Error: {e}");
             }
-            Err(ReadlineError::Interrupted | ReadlineError::Eof) => break,
-            Err(e) => return Err(e.into()),
         }
+        Err(e) => eprintln!("Error: {e}"),
     }
+}
 
-    println!("Goodbye!");
-    Ok(())
+/// Print a message's raw JSON, folding containers deeper than `depth`.
+fn show_message(session: &QuerySession, uuid: &str, depth: Option<usize>, color: bool) {
+    match crate::inspect::fetch(session, uuid) {
+        Ok(view) => println!("{}", crate::inspect::render_message(&view, depth, color)),
+        Err(e) => eprintln!("Error: {e}"),
+    }
 }
 
-fn execute_query(session: &QuerySession, sql: &str) {
-    match session.query(sql) {
-        Ok(result) => {
-            println!("{}", result.to_table());
+/// Read TSV/CSV lines (header first) until a lone `.` and load them into a
+/// temp table, letting `DuckDB` sniff column types.
+fn paste_table(session: &QuerySession, editor: &mut ReplEditor, table: &str) {
+    println!("Paste TSV or CSV with a header row; end with a line containing only \".\"");
+    let mut lines = Vec::new();
+    loop {
+        match editor.readline(PASTE_PROMPT) {
+            Ok(line) if line.trim() == "." => break,
+            Ok(line) => lines.push(line),
+            Err(_) => {
+                println!("Paste cancelled");
+                return;
+            }
         }
+    }
+    let Some(first) = lines.first() else {
+        println!("Nothing pasted");
+        return;
+    };
+
+    let delimiter = detect_delimiter(first);
+    let file = tempfile::Builder::new()
+        .prefix("ccq-paste-")
+        .suffix(".csv")
+        .tempfile()
+        .and_then(|mut file| {
+            file.write_all((lines.join("\n") + "\n").as_bytes())?;
+            file.flush()?;
+            Ok(file)
+        });
+    let file = match file {
+        Ok(file) => file,
         Err(e) => {
             eprintln!("Error: {e}");
+            return;
         }
+    };
+    let sql = paste_table_sql(table, &file.path().to_string_lossy(), delimiter);
+    let result = session
+        .query(&sql)
+        .and_then(|_| session.query(&format!("SELECT count(*) FROM {}", sql_ident(table))));
+    drop(file);
+
+    match result {
+        Ok(count) => {
+            let rows = count.rows().first().map_or("0", |row| row[0].as_str());
+            println!("Created temp table {table} ({rows} rows)");
+        }
+        Err(e) => eprintln!("Error: {e}"),
     }
 }
 
-fn handle_dot_command(command: &str, session: &QuerySession) -> DotCommandResult {
-    let cmd = command.to_lowercase();
+/// Tabs win when present, since pasted spreadsheet cells are tab-separated.
+fn detect_delimiter(header: &str) -> char {
+    if header.contains('\t') { '\t' } else { ',' }
+}
 
-    if cmd == ".quit" || cmd == ".exit" || cmd == ".q" {
-        return DotCommandResult::Exit;
-    }
+fn paste_table_sql(table: &str, path: &str, delimiter: char) -> String {
+    format!(
+        "CREATE OR REPLACE TEMP TABLE {} AS SELECT * FROM read_csv({}, delim={}, header=true)",
+        sql_ident(table),
+        sql_quote(path),
+        sql_quote(&delimiter.to_string())
+    )
+}
 
-    if cmd == ".help" || cmd == ".h" {
-        print_help();
-        return DotCommandResult::Continue;
-    }
+fn print_help() {
+    println!("{}", crate::help::overview());
+}
 
-    if cmd == ".schema" || cmd == ".s" {
-        for view in VIEWS {
-            println!("\n=== {view} ===");
-            execute_query(session, &format!("DESCRIBE {view}"));
-        }
-        return DotCommandResult::Continue;
+/// `.help TOPIC`: a view's notes, live columns, and examples, or all examples.
+fn print_topic(session: &QuerySession, name: &str, options: &mut ReplOptions) {
+    if name.eq_ignore_ascii_case("examples") {
+        emit(options, crate::help::examples().trim_end());
+        return;
     }
+    let Some(topic) = crate::help::topic(name) else {
+        let views: Vec<&str> = crate::help::VIEWS.iter().map(|v| v.name).collect();
+        println!("No help for {name}. Topics: examples, {}", views.join(", "));
+        return;
+    };
+    println!("{}\n\nColumns:", crate::help::describe(topic));
+    execute_query(session, &format!("DESCRIBE {}", topic.name), options);
+    println!("\nExamples:\n{}", crate::help::format_examples(topic.examples));
+}
 
-    if cmd.starts_with(".schema ") || cmd.starts_with(".s ") {
-        let view = command.split_whitespace().nth(1).unwrap_or("");
-        execute_query(session, &format!("DESCRIBE {view}"));
-        return DotCommandResult::Continue;
-    }
+/// Outcome of a non-interactive script run.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ScriptSummary {
+    /// SQL statements executed (dot commands excluded)
+    pub statements: usize,
+    /// Rows returned across all statements
+    pub rows: usize,
+    /// Statements that failed
+    pub errors: usize,
+    /// Stopped early by SIGTERM
+    pub terminated: bool,
+    /// Wall time for the whole script
+    pub duration_ms: f64,
+    /// One entry per statement, in order
+    pub results: Vec<StatementReport>,
+}
 
-    println!("Unknown command: {command}. Type .help for usage.");
-    DotCommandResult::Continue
+/// What one statement of a script did.
+#[derive(Debug, Clone, Serialize)]
+pub struct StatementReport {
+    pub sql: String,
+    pub rows: usize,
+    pub duration_ms: f64,
+    pub error: Option<String>,
 }
 
-fn print_help() {
-    println!(
-        r"
-Commands:
-  .help, .h      Show this help
-  .schema, .s    Show schemas for all views
-  .schema <view> Show schema for a specific view
-  .quit, .q      Exit
-
-Views:
-  messages            All messages (user, assistant, system)
-  user_messages       User messages with user-specific fields
-  human_messages      Human-typed messages (excludes tool results)
-  assistant_messages  Assistant messages with error, requestId, etc.
-  system_messages     System messages with hooks, retry info, etc.
-  raw_messages        Raw JSON for each message by uuid
-  tool_uses           All tool calls with unnested content blocks
-  tool_results        Tool results with duration and error status
-  token_usage         Token counts per assistant message
-  bash_commands       Bash tool calls with extracted command
-  file_operations     Read/Write/Edit/Glob/Grep with file paths
-
-Example queries:
-  -- Count messages by type
-  SELECT type, count(*) as cnt FROM messages GROUP BY type ORDER BY cnt DESC;
-
-  -- Messages by project (when querying all projects)
-  SELECT project, count(*) as cnt FROM messages GROUP BY project ORDER BY cnt DESC;
-
-  -- Recent assistant messages
-  SELECT timestamp, message->>'role', message->>'stop_reason'
-  FROM assistant_messages ORDER BY timestamp DESC LIMIT 10;
-
-  -- Tool usage
-  SELECT message->>'stop_reason' as reason, count(*) as cnt
-  FROM assistant_messages
-  GROUP BY reason ORDER BY cnt DESC;
-
-  -- Sessions summary
-  SELECT sessionId, count(*) as msgs, min(timestamp) as started
-  FROM messages GROUP BY sessionId ORDER BY started DESC;
-
-  -- System message subtypes
-  SELECT subtype, count(*) FROM system_messages GROUP BY subtype;
-
-  -- Agent vs main session breakdown
-  SELECT isAgent, count(*) FROM messages GROUP BY isAgent;
-
-JSON field access (DuckDB syntax):
-  message->'field'        Access JSON field (returns JSON)
-  message->>'field'       Access JSON field as string
-  message->'a'->'b'       Nested access
-
-Useful functions:
-  arr[n]                 Get nth element (1-indexed)
-  UNNEST(arr)            Expand array into rows
-  json_extract_string()  Extract string from JSON
-"
-    );
+impl ScriptSummary {
+    fn record(&mut self, sql: &str, started: Instant, outcome: std::result::Result<usize, String>) {
+        self.statements += 1;
+        let (rows, error) = match outcome {
+            Ok(rows) => (rows, None),
+            Err(e) => {
+                self.errors += 1;
+                (0, Some(e))
+            }
+        };
+        self.rows += rows;
+        self.results.push(StatementReport {
+            sql: sql.to_string(),
+            rows,
+            duration_ms: started.elapsed().as_secs_f64() * 1000.0,
+            error,
+        });
+    }
+
+    /// Machine-readable form for `--summary-json`.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
 }
 
 /// Execute piped queries from stdin.
 ///
 /// # Errors
 /// Returns error if I/O or query execution fails.
-pub fn run_piped(session: &QuerySession) -> Result<()> {
+pub fn run_piped(session: &QuerySession, options: &ReplOptions) -> Result<ScriptSummary> {
     let mut input = String::new();
     io::stdin().read_to_string(&mut input)?;
+    run_script(session, &input, options)
+}
+
+/// Execute a SQL script, streaming TSV results to stdout.
+///
+/// A closed stdout (e.g. piping into `head`) ends the script quietly. The
+/// first SIGTERM stops at the next write after flushing what was already
+/// written and marks the summary `terminated`; a second exits immediately.
+///
+/// # Errors
+/// Returns error if writing output fails.
+pub fn run_script(
+    session: &QuerySession,
+    input: &str,
+    options: &ReplOptions,
+) -> Result<ScriptSummary> {
+    let substituted;
+    let input = if options.env_subst {
+        substituted = substitute_env(input, |name| std::env::var(name).ok())?;
+        substituted.as_str()
+    } else {
+        input
+    };
+
+    let terminated = Arc::new(AtomicBool::new(false));
+    let signals = [
+        signal_hook::flag::register_conditional_shutdown(SIGTERM, 143, Arc::clone(&terminated))?,
+        signal_hook::flag::register(SIGTERM, Arc::clone(&terminated))?,
+    ];
 
     let stdout = io::stdout();
-    let mut writer = BufWriter::new(stdout.lock());
+    let mut writer = StopOnSignal {
+        inner: BufWriter::new(stdout.lock()),
+        stop: &terminated,
+    };
 
     // Split on top-level `;`, skipping semicolons that appear inside SQL
     // comments and string literals. A naive `input.split(';')` fragments
     // valid SQL like `SELECT 1 -- trailing ;\nFROM t` into garbage.
-    let statements = split_statements(&input);
+    let statements = split_statements(input);
+
+    let mut summary = ScriptSummary::default();
+    let script_started = Instant::now();
+    let outcome = run_statements(session, &statements, options, &mut writer, &mut summary)
+        .and_then(|()| Ok(writer.flush()?));
+    for id in signals {
+        signal_hook::low_level::unregister(id);
+    }
+    summary.duration_ms = script_started.elapsed().as_secs_f64() * 1000.0;
+
+    match outcome {
+        Ok(()) => {}
+        // The reader went away; everything it wanted has been written
+        Err(e) if is_broken_pipe(&e) => {}
+        Err(_) if terminated.load(Ordering::Relaxed) => {
+            let _ = writer.inner.flush();
+            summary.terminated = true;
+        }
+        Err(e) => return Err(e),
+    }
+    Ok(summary)
+}
 
+fn run_statements(
+    session: &QuerySession,
+    statements: &[&str],
+    options: &ReplOptions,
+    writer: &mut StopOnSignal<'_, impl Write>,
+    summary: &mut ScriptSummary,
+) -> Result<()> {
     let mut is_first = true;
 
-    for stmt in statements {
+    for &stmt in statements {
+        if writer.stop.load(Ordering::Relaxed) {
+            return Err(io::Error::other("terminated").into());
+        }
+        let started = Instant::now();
         if stmt.starts_with('.') {
             writer.flush()?; // Flush before dot command output
             if matches!(handle_dot_command_piped(stmt, session), DotCommandResult::Exit) {
                 break;
             }
+        } else if options.check {
+            let n = summary.statements + 1;
+            let first_line = stmt.lines().next().unwrap_or_default();
+            let outcome = check_statement(session, stmt);
+            match &outcome {
+                Ok(()) => writeln!(writer, "ok     {n}: {first_line}")?,
+                Err(e) => writeln!(writer, "error  {n}: {first_line}\n       {e}")?,
+            }
+            summary.record(stmt, started, outcome.map(|()| 0).map_err(|e| e.to_string()));
         } else {
             if !is_first {
                 writeln!(writer, "---")?;
             }
-            match session.query_tsv_streaming(stmt, &mut writer) {
-                Ok(_) => {
-                    is_first = false;
+            let outcome = if let Some(analyze) = options.analyze {
+                match explain_query(session, stmt, analyze) {
+                    Ok(plan) => {
+                        writeln!(writer, "{plan}")?;
+                        Ok(0)
+                    }
+                    Err(e) => Err(e),
+                }
+            } else {
+                session.query_tsv_streaming(stmt, &mut *writer)
+            };
+            match outcome {
+                Err(e) if is_broken_pipe(&e) || writer.stop.load(Ordering::Relaxed) => {
+                    return Err(e);
                 }
-                Err(e) => {
+                Ok(_) => is_first = false,
+                Err(ref e) => {
                     writer.flush()?;
                     eprintln!("Error: {e}");
                 }
             }
+            summary.record(stmt, started, outcome.map_err(|e| e.to_string()));
         }
     }
-    writer.flush()?;
-
     Ok(())
 }
 
-/// Split a SQL input into statements on top-level `;` separators, skipping
-/// semicolons that appear inside:
+fn is_broken_pipe(error: &Error) -> bool {
+    matches!(error, Error::Io(e) if e.kind() == io::ErrorKind::BrokenPipe)
+}
+
+/// Writer that fails once `stop` is set, so streaming ends at the next row.
+struct StopOnSignal<'a, W: Write> {
+    inner: W,
+    stop: &'a AtomicBool,
+}
+
+impl<W: Write> Write for StopOnSignal<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.stop.load(Ordering::Relaxed) {
+            return Err(io::Error::other("terminated"));
+        }
+        self.inner.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Validate a statement without running it. View and macro definitions are
+/// applied (they read no data) so later statements can refer to them.
+fn check_statement(session: &QuerySession, sql: &str) -> Result<()> {
+    if is_definition(sql) {
+        session.query(sql).map(|_| ())
+    } else {
+        session.check(sql)
+    }
+}
+
+fn is_definition(sql: &str) -> bool {
+    let words: Vec<String> = sql
+        .split_whitespace()
+        .take(5)
+        .map(str::to_uppercase)
+        .collect();
+    let mut words = words.iter().map(String::as_str).peekable();
+    if words.next() != Some("CREATE") {
+        return false;
+    }
+    if words.peek() == Some(&"OR") {
+        words.next();
+        words.next(); // REPLACE
+    }
+    if matches!(words.peek(), Some(&("TEMP" | "TEMPORARY"))) {
+        words.next();
+    }
+    matches!(words.next(), Some("VIEW" | "MACRO" | "FUNCTION"))
+}
+
+/// Replace `${NAME}` with `lookup(NAME)`. Inside a single-quoted string the
+/// value's quotes are doubled, so `'${SESSION}'` stays one literal whatever
+/// the variable holds; elsewhere the value is inserted verbatim.
+///
+/// # Errors
+/// Returns error if a referenced variable is undefined.
+fn substitute_env(input: &str, lookup: impl Fn(&str) -> Option<String>) -> Result<String> {
+    let mut out = String::with_capacity(input.len());
+    let mut in_string = false;
+    let mut rest = input;
+
+    while let Some(c) = rest.chars().next() {
+        if c == '\'' {
+            in_string = !in_string; // `''` toggles twice, staying inside
+        } else if let Some(name) = rest
+            .strip_prefix("${")
+            .and_then(|r| r.split_once('}'))
+            .map(|(name, _)| name)
+            .filter(|name| is_env_name(name))
+        {
+            let value = lookup(name).ok_or_else(|| Error::UndefinedVariable(name.to_string()))?;
+            if in_string {
+                out.push_str(&value.replace('\'', "''"));
+            } else {
+                out.push_str(&value);
+            }
+            rest = &rest[name.len() + 3..];
+            continue;
+        }
+        out.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    Ok(out)
+}
+
+fn is_env_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// What [`scan_sql`] found at an offset: a whole comment, a whole quoted
+/// string or identifier, or a single byte of ordinary SQL.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SqlLexeme {
+    Comment { closed: bool },
+    Quoted { closed: bool },
+    Byte(u8),
+}
+
+/// Walk SQL text, calling `visit` with the byte offset of each lexeme.
+/// Comments and quoted text are reported whole, so a `;` inside them is never
+/// seen as a `Byte`. Recognizes:
 ///   - line comments (`-- …\n`)
 ///   - block comments (`/* … */`, nestable — matches DuckDB/PostgreSQL)
 ///   - single-quoted strings (`'…'`, with `''` as the escape for `'`)
 ///   - double-quoted identifiers (`"…"`, with `""` as the escape for `"`)
 ///   - dollar-quoted strings (`$tag$…$tag$`; tag may be empty: `$$…$$`)
 ///
-/// Returns trimmed, non-empty statements in source order. Unterminated
-/// comments or strings consume to end of input; the resulting tail is
-/// returned as the final statement and DuckDB surfaces the real parse
-/// error on execution. Does not attempt to handle `E'…'` escape strings
-/// or other dialect extensions — those aren't used in Claude Code
-/// transcripts or the typical `ccq` workflow.
-fn split_statements(input: &str) -> Vec<&str> {
+/// Unterminated comments or strings consume to end of input and are reported
+/// with `closed: false`. Does not attempt to handle `E'…'` escape strings or
+/// other dialect extensions — those aren't used in Claude Code transcripts or
+/// the typical `ccq` workflow.
+fn scan_sql(input: &str, mut visit: impl FnMut(usize, SqlLexeme)) {
     let bytes = input.as_bytes();
-    let mut statements = Vec::new();
-    let mut start = 0usize;
     let mut i = 0usize;
 
     while i < bytes.len() {
         let c = bytes[i];
+        let at = i;
         match c {
-            // Line comment: skip to end of line (leave the `\n` for normal processing).
+            // Line comment: runs to end of line (the `\n` is scanned normally).
+            // Always closed, since the end of input ends it too.
             b'-' if bytes.get(i + 1) == Some(&b'-') => {
                 i += 2;
                 while i < bytes.len() && bytes[i] != b'\n' {
                     i += 1;
                 }
+                visit(at, SqlLexeme::Comment { closed: true });
             }
             // Block comment, supporting nesting (DuckDB/PG semantics).
             b'/' if bytes.get(i + 1) == Some(&b'*') => {
@@ -323,11 +2309,13 @@ fn split_statements(input: &str) -> Vec<&str> {
                         _ => i += 1,
                     }
                 }
+                visit(at, SqlLexeme::Comment { closed: depth == 0 });
             }
             // Single-quoted string or double-quoted identifier. `''` / `""` is
             // the standard doubled-quote escape in both.
             b'\'' | b'"' => {
                 let quote = c;
+                let mut closed = false;
                 i += 1;
                 while i < bytes.len() {
                     if bytes[i] == quote {
@@ -335,12 +2323,14 @@ fn split_statements(input: &str) -> Vec<&str> {
                             i += 2; // doubled quote = escape
                         } else {
                             i += 1; // closing quote
+                            closed = true;
                             break;
                         }
                     } else {
                         i += 1;
                     }
                 }
+                visit(at, SqlLexeme::Quoted { closed });
             }
             // Dollar-quoted string: `$TAG$…$TAG$` where TAG is [A-Za-z0-9_]*.
             b'$' => {
@@ -354,39 +2344,56 @@ fn split_statements(input: &str) -> Vec<&str> {
                 if tag_end < bytes.len() && bytes[tag_end] == b'$' {
                     // Valid opener.
                     let tag_len = tag_end - tag_start;
+                    let mut closed = false;
                     i = tag_end + 1;
                     // Scan for matching `$TAG$` closer.
-                    loop {
-                        if i >= bytes.len() {
-                            break;
-                        }
+                    while i < bytes.len() {
                         if bytes[i] == b'$'
                             && i + 1 + tag_len < bytes.len()
                             && bytes[i + 1 + tag_len] == b'$'
                             && bytes[i + 1..i + 1 + tag_len] == bytes[tag_start..tag_end]
                         {
                             i = i + 1 + tag_len + 1;
+                            closed = true;
                             break;
                         }
                         i += 1;
                     }
+                    visit(at, SqlLexeme::Quoted { closed });
                 } else {
                     // Bare `$` not opening a dollar quote (e.g. `$1` bind param).
                     i += 1;
+                    visit(at, SqlLexeme::Byte(c));
                 }
             }
-            // Top-level statement terminator.
-            b';' => {
-                let stmt = input[start..i].trim();
-                if !stmt.is_empty() {
-                    statements.push(stmt);
-                }
+            _ => {
                 i += 1;
-                start = i;
+                visit(at, SqlLexeme::Byte(c));
             }
-            _ => i += 1,
         }
     }
+}
+
+/// Split a SQL input into statements on top-level `;` separators, skipping
+/// semicolons inside comments and quoted text (see [`scan_sql`]).
+///
+/// Returns trimmed, non-empty statements in source order. An unterminated
+/// comment or string ends up in the final statement, and `DuckDB` surfaces
+/// the real parse error on execution.
+pub(crate) fn split_statements(input: &str) -> Vec<&str> {
+    let mut statements = Vec::new();
+    let mut start = 0usize;
+
+    scan_sql(input, |i, lexeme| {
+        // Top-level statement terminator.
+        if lexeme == SqlLexeme::Byte(b';') {
+            let stmt = input[start..i].trim();
+            if !stmt.is_empty() {
+                statements.push(stmt);
+            }
+            start = i + 1;
+        }
+    });
 
     // Trailing content (input without a terminal `;`).
     let tail = input[start..].trim();
@@ -408,6 +2415,27 @@ fn handle_dot_command_piped(command: &str, session: &QuerySession) -> DotCommand
         return DotCommandResult::Continue;
     }
 
+    if cmd == ".info" {
+        print_info(session);
+        return DotCommandResult::Continue;
+    }
+
+    if cmd == ".tables" {
+        if let Ok(result) = session.query(&tables_sql()) {
+            println!("{}", result.to_table());
+        }
+        return DotCommandResult::Continue;
+    }
+
+    if cmd == ".sessions" || cmd.starts_with(".sessions ") {
+        let sql = sessions_sql(command.split_whitespace().nth(1));
+        match session.query(&sql) {
+            Ok(result) => println!("{}", result.to_table()),
+            Err(e) => eprintln!("Error: {e}"),
+        }
+        return DotCommandResult::Continue;
+    }
+
     if cmd == ".schema" || cmd == ".s" {
         for view in VIEWS {
             println!("\n=== {view} ===");
@@ -438,7 +2466,262 @@ mod tests {
     fn test_views_list() {
         assert!(VIEWS.contains(&"messages"));
         assert!(VIEWS.contains(&"tool_uses"));
-        assert_eq!(VIEWS.len(), 11);
+        assert_eq!(VIEWS.len(), 43);
+    }
+
+    #[test]
+    fn test_every_view_has_help() {
+        let topics: Vec<&str> = crate::help::VIEWS.iter().map(|v| v.name).collect();
+        assert_eq!(topics, VIEWS);
+    }
+
+    #[test]
+    fn test_analyze_wrap() {
+        assert_eq!(Analyze::Explain.wrap("SELECT 1;"), "EXPLAIN SELECT 1");
+        assert_eq!(
+            Analyze::Profile.wrap(" SELECT 1 "),
+            "EXPLAIN ANALYZE SELECT 1"
+        );
+    }
+
+    #[test]
+    fn test_grep_sql_quotes_pattern() {
+        let sql = grep_sql("it's (fixed|done)");
+        assert!(sql.contains("regexp_matches(text, 'it''s (fixed|done)', 'i')"));
+        assert!(sql.contains("'(?is).{0,40}(?:' || 'it''s (fixed|done)' || ').{0,40}'"));
+        assert!(sql.contains("'\\s+', ' ', 'g'"));
+    }
+
+    #[test]
+    fn test_confirmed_rows() {
+        assert_eq!(confirmed_rows("y", 20_000), Some(20_000));
+        assert_eq!(confirmed_rows("YES", 20_000), Some(20_000));
+        assert_eq!(confirmed_rows("500", 20_000), Some(500));
+        assert_eq!(confirmed_rows("50000", 20_000), Some(20_000));
+        assert_eq!(confirmed_rows("", 20_000), None);
+        assert_eq!(confirmed_rows("n", 20_000), None);
+        assert_eq!(confirmed_rows("0", 20_000), None);
+    }
+
+    #[test]
+    fn test_apply_setting() {
+        let mut options = ReplOptions::default();
+        assert_eq!(unquote("'∅'"), "∅");
+        assert_eq!(unquote("\"a b\""), "a b");
+        assert_eq!(unquote("'x\""), "'x\"");
+        apply_setting(&mut options, "nullvalue", unquote("'∅'")).unwrap();
+        assert_eq!(options.table_options().null_value.as_deref(), Some("∅"));
+        apply_setting(&mut options, "maxwidth", "60").unwrap();
+        assert_eq!(options.max_width, Some(60));
+        apply_setting(&mut options, "maxrows", "off").unwrap();
+        assert_eq!(options.max_rows, None);
+        apply_setting(&mut options, "timer", "on").unwrap();
+        assert!(options.timer);
+        apply_setting(&mut options, "confirmrows", "5000").unwrap();
+        assert_eq!(options.confirm_rows, Some(5000));
+        assert!(apply_setting(&mut options, "pager", "maybe").is_err());
+    }
+
+    #[test]
+    fn test_freshness_line() {
+        let now = SystemTime::UNIX_EPOCH + Duration::from_mins(28_949_190); // 2025-01-15 14:30
+        let modified = now - Duration::from_mins(3);
+        assert_eq!(
+            span_line("2025-01-02 09:15:00.000", "2025-01-15 14:26:59.9"),
+            "Messages from 2025-01-02 09:15 to 2025-01-15 14:26 UTC"
+        );
+        assert_eq!(
+            written_line(modified, now),
+            "Newest file written 2025-01-15 14:27 UTC (3 minutes ago)"
+        );
+        assert_eq!(age_text(Duration::from_secs(5)), "just now");
+        assert_eq!(age_text(Duration::from_hours(1)), "1 hour ago");
+        assert_eq!(age_text(Duration::from_hours(72)), "3 days ago");
+    }
+
+    #[test]
+    fn test_paste_table_sql() {
+        assert_eq!(detect_delimiter("a\tb"), '\t');
+        assert_eq!(detect_delimiter("a,b"), ',');
+        assert_eq!(
+            paste_table_sql("ids", "/tmp/p.csv", '\t'),
+            "CREATE OR REPLACE TEMP TABLE \"ids\" AS SELECT * FROM read_csv('/tmp/p.csv', delim='\t', header=true)"
+        );
+    }
+
+    #[test]
+    fn test_script_summary_record() {
+        let mut summary = ScriptSummary::default();
+        summary.record("SELECT 1", Instant::now(), Ok(1));
+        summary.record("SELEC 2", Instant::now(), Err("syntax error".to_string()));
+        assert_eq!((summary.statements, summary.rows, summary.errors), (2, 1, 1));
+
+        let json: serde_json::Value = serde_json::from_str(&summary.to_json()).unwrap();
+        assert_eq!(json["results"][0]["rows"], 1);
+        assert_eq!(json["results"][1]["error"], "syntax error");
+    }
+
+    #[test]
+    fn test_stop_on_signal_writer() {
+        let stop = AtomicBool::new(false);
+        let mut writer = StopOnSignal {
+            inner: Vec::new(),
+            stop: &stop,
+        };
+        writeln!(writer, "a\t1").unwrap();
+        stop.store(true, Ordering::Relaxed);
+        assert!(writeln!(writer, "b\t2").is_err());
+        assert_eq!(writer.inner, b"a\t1\n");
+    }
+
+    #[test]
+    fn test_tables_sql_marks_builtin_views() {
+        let sql = tables_sql();
+        assert!(sql.contains("IN ('messages', 'user_messages',"));
+        assert!(sql.contains("duckdb_views()") && sql.contains("duckdb_tables()"));
+    }
+
+    #[test]
+    fn test_timer_line() {
+        assert_eq!(timer_line(Duration::from_micros(12_340), 1), "Run Time: 12.3ms (1 row)");
+        assert_eq!(timer_line(Duration::from_millis(250), 40), "Run Time: 250.0ms (40 rows)");
+    }
+
+    #[test]
+    fn test_set_width() {
+        let mut options = ReplOptions::default();
+        set_width(&mut options, &["80"]).unwrap();
+        set_width(&mut options, &["message", "200"]).unwrap();
+        set_width(&mut options, &["Message", "300"]).unwrap();
+        assert_eq!(options.max_width, Some(80));
+        assert_eq!(options.column_widths, vec![("Message".to_string(), 300)]);
+        assert!(set_width(&mut options, &["wide"]).is_err());
+
+        set_width(&mut options, &["off"]).unwrap();
+        assert_eq!(options.max_width, None);
+        assert!(options.column_widths.is_empty());
+    }
+
+    #[test]
+    fn test_is_wider_than() {
+        assert!(!is_wider_than("┌──┐\n│ab│", 4));
+        assert!(is_wider_than("short\nmuch longer line", 10));
+    }
+
+    #[test]
+    fn test_history_slug() {
+        assert_eq!(history_slug(&SessionOptions::default()), "all");
+        let project = SessionOptions {
+            project_dir: Some(PathBuf::from("/home/me/code/my.app")),
+            ..SessionOptions::default()
+        };
+        assert_eq!(history_slug(&project), "-home-me-code-my-app");
+        let data = SessionOptions {
+            data_dirs: vec![PathBuf::from("/nonexistent/a"), PathBuf::from("/nonexistent/b")],
+            ..SessionOptions::default()
+        };
+        assert_eq!(history_slug(&data), "-nonexistent-a+-nonexistent-b");
+    }
+
+    #[test]
+    fn test_sessions_sql_prefix_filter() {
+        assert!(!sessions_sql(None).contains("starts_with"));
+        let sql = sessions_sql(Some("AB'c"));
+        assert!(sql.contains("starts_with(sessionId::VARCHAR, 'ab''c')"));
+    }
+
+    #[test]
+    fn test_export_sql() {
+        let (sql, path) = export_sql(".export csv out.csv", Some("SELECT 1;")).unwrap();
+        assert_eq!(sql, "COPY (SELECT 1) TO 'out.csv' (FORMAT csv, HEADER true)");
+        assert_eq!(path, "out.csv");
+        let (sql, _) = export_sql(".export parquet t.parquet FROM tool_uses", None).unwrap();
+        assert_eq!(sql, "COPY (FROM tool_uses) TO 't.parquet' (FORMAT parquet)");
+        assert!(export_sql(".export xml out.xml", Some("SELECT 1")).is_err());
+        assert!(export_sql(".export json out.json", None).is_err());
+    }
+
+    #[test]
+    fn test_is_complete_statement() {
+        assert!(is_complete_statement("SELECT 1;"));
+        assert!(is_complete_statement("SELECT 1; -- done"));
+        assert!(!is_complete_statement("SELECT 1"));
+        assert!(!is_complete_statement("SELECT ';"));
+        assert!(is_complete_statement("SELECT 'a;b', 'it''s';"));
+        assert!(!is_complete_statement("SELECT count(*) FROM (SELECT 1;"));
+        assert!(is_complete_statement("SELECT (1);"));
+        assert!(!is_complete_statement("SELECT 1 /* ; */"));
+        assert!(!is_complete_statement("SELECT 1; /* open"));
+        assert!(!is_complete_statement("SELECT \"a;"));
+        assert!(is_complete_statement("SELECT $$a;b$$;"));
+        assert!(!is_complete_statement("SELECT $$a;"));
+        assert!(!is_complete_statement("SELECT $tag$a;"));
+        assert!(is_complete_statement("SELECT 1 /* /* */ ; */;"));
+        assert!(!is_complete_statement("SELECT 1 /* /* */ ;"));
+    }
+
+    #[test]
+    fn test_store_sql() {
+        let (sql, name) = store_sql(".store hot_files SELECT 1;", None).unwrap();
+        assert_eq!(sql, "CREATE OR REPLACE TEMP TABLE \"hot_files\" AS SELECT 1");
+        assert_eq!(name, "hot_files");
+        let (sql, _) = store_sql(".store t", Some("FROM tool_uses")).unwrap();
+        assert_eq!(sql, "CREATE OR REPLACE TEMP TABLE \"t\" AS FROM tool_uses");
+        assert!(store_sql(".store", Some("SELECT 1")).is_err());
+        assert!(store_sql(".store t", None).is_err());
+    }
+
+    #[test]
+    fn test_redirect_target() {
+        assert_eq!(redirect_target(".once out.csv"), Some(PathBuf::from("out.csv")));
+        assert_eq!(redirect_target(".output"), None);
+        assert_eq!(redirect_target(".output stdout"), None);
+    }
+
+    #[test]
+    fn test_is_definition() {
+        assert!(is_definition("CREATE VIEW v AS SELECT 1"));
+        assert!(is_definition("create or replace temp view v as select 1"));
+        assert!(is_definition("CREATE MACRO m(x) AS x + 1"));
+        assert!(!is_definition("CREATE TABLE t AS SELECT 1"));
+        assert!(!is_definition("SELECT 1"));
+    }
+
+    #[test]
+    fn test_substitute_env() {
+        let lookup = |name: &str| match name {
+            "SINCE" => Some("2025-01-01".to_string()),
+            "WHO" => Some("o'brien".to_string()),
+            "N" => Some("10".to_string()),
+            _ => None,
+        };
+        assert_eq!(
+            substitute_env("SELECT * FROM m WHERE t > '${SINCE}' LIMIT ${N}", lookup).unwrap(),
+            "SELECT * FROM m WHERE t > '2025-01-01' LIMIT 10"
+        );
+        assert_eq!(
+            substitute_env("SELECT 'it''s ${WHO}'", lookup).unwrap(),
+            "SELECT 'it''s o''brien'"
+        );
+        assert_eq!(substitute_env("SELECT '${ x }'", lookup).unwrap(), "SELECT '${ x }'");
+        assert!(substitute_env("SELECT ${MISSING}", lookup).is_err());
+    }
+
+    #[test]
+    fn test_script_commands() {
+        let script = ".tables\nSELECT 1;\n.mode json\nSELECT 2; SELECT 3;\nSELECT\n.5;\n.quit";
+        assert_eq!(
+            script_commands(script),
+            vec![
+                ".tables",
+                "SELECT 1",
+                ".mode json",
+                "SELECT 2",
+                "SELECT 3",
+                "SELECT\n.5",
+                ".quit"
+            ]
+        );
     }
 
     // --- split_statements() — SQL-aware statement splitter ------------------
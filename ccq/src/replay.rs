@@ -0,0 +1,373 @@
+//! Step through a session message by message (`ccq replay`).
+
+use std::fmt::Write;
+
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Style, Stylize};
+use ratatui::text::{Line, Span, Text};
+use ratatui::widgets::{Block, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+use serde_json::Value;
+
+use crate::query_session::QueryResult;
+use crate::utils::sql_quote;
+use crate::{QuerySession, Result};
+
+/// Tool results longer than this are cut in non-interactive output.
+const PRINT_RESULT_CHARS: usize = 2000;
+
+/// One message in replay order.
+#[derive(Debug, Clone)]
+pub struct ReplayMessage {
+    pub uuid: String,
+    pub kind: String,
+    pub subtype: Option<String>,
+    pub timestamp: String,
+    pub agent_id: Option<String>,
+    pub message: Value,
+    /// System message text
+    pub content: Option<String>,
+}
+
+/// A renderable piece of a message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Part {
+    User(String),
+    Assistant(String),
+    Thinking(String),
+    ToolCall { name: String, input: String },
+    ToolResult { id: String, is_error: bool, text: String },
+    System(String),
+}
+
+/// Load a session's messages in order.
+///
+/// # Errors
+/// Returns error if the query fails.
+pub fn load(
+    session: &QuerySession,
+    session_id: &str,
+    include_agents: bool,
+) -> Result<Vec<ReplayMessage>> {
+    let agents = if include_agents { "" } else { " AND NOT isAgent" };
+    let result = session.query(&format!(
+        "SELECT uuid, type, subtype, timestamp, agentId, message, content
+         FROM messages
+         WHERE sessionId = {}{agents}
+         ORDER BY timestamp, rownum",
+        sql_quote(session_id)
+    ))?;
+    Ok(from_rows(&result))
+}
+
+/// Load a message's thread: up to `depth` ancestors along `parentUuid` and
+/// descendants `depth` levels down, in order.
+///
+/// # Errors
+/// Returns error if the query fails.
+pub fn load_thread(
+    session: &QuerySession,
+    uuid: &str,
+    depth: usize,
+) -> Result<Vec<ReplayMessage>> {
+    let uuid = sql_quote(uuid);
+    let result = session.query(&format!(
+        "WITH RECURSIVE
+           ancestors(uuid, parentUuid, depth) AS (
+             SELECT uuid::VARCHAR, parentUuid::VARCHAR, 0 FROM messages WHERE uuid::VARCHAR = {uuid}
+             UNION
+             SELECT m.uuid::VARCHAR, m.parentUuid::VARCHAR, a.depth + 1
+             FROM messages m JOIN ancestors a ON m.uuid::VARCHAR = a.parentUuid
+             WHERE a.depth < {depth}
+           ),
+           descendants(uuid, depth) AS (
+             SELECT uuid::VARCHAR, 0 FROM messages WHERE uuid::VARCHAR = {uuid}
+             UNION
+             SELECT m.uuid::VARCHAR, d.depth + 1
+             FROM messages m JOIN descendants d ON m.parentUuid::VARCHAR = d.uuid
+             WHERE d.depth < {depth}
+           )
+         SELECT uuid, type, subtype, timestamp, agentId, message, content
+         FROM messages
+         WHERE uuid::VARCHAR IN (SELECT uuid FROM ancestors UNION SELECT uuid FROM descendants)
+         ORDER BY timestamp, rownum"
+    ))?;
+    Ok(from_rows(&result))
+}
+
+fn from_rows(result: &QueryResult) -> Vec<ReplayMessage> {
+    let optional = |s: &String| (s != "NULL" && !s.is_empty()).then(|| s.clone());
+    result
+        .rows()
+        .iter()
+        .map(|row| ReplayMessage {
+            uuid: row[0].clone(),
+            kind: row[1].clone(),
+            subtype: optional(&row[2]),
+            timestamp: row[3].clone(),
+            agent_id: optional(&row[4]),
+            message: serde_json::from_str(&row[5]).unwrap_or(Value::Null),
+            content: optional(&row[6]),
+        })
+        .collect()
+}
+
+/// Split a message into renderable parts.
+pub fn parts(msg: &ReplayMessage) -> Vec<Part> {
+    if msg.kind == "system" {
+        let subtype = msg.subtype.as_deref().unwrap_or("system");
+        let text = msg.content.clone().unwrap_or_default();
+        return vec![Part::System(format!("[{subtype}] {text}"))];
+    }
+
+    let user = msg.kind == "user";
+    match msg.message.get("content") {
+        Some(Value::String(text)) if user => vec![Part::User(text.clone())],
+        Some(Value::String(text)) => vec![Part::Assistant(text.clone())],
+        Some(Value::Array(blocks)) => blocks.iter().filter_map(|b| block_part(b, user)).collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn block_part(block: &Value, user: bool) -> Option<Part> {
+    let text = |key: &str| block.get(key).and_then(Value::as_str).unwrap_or_default().to_string();
+    match block.get("type").and_then(Value::as_str)? {
+        "text" if user => Some(Part::User(text("text"))),
+        "text" => Some(Part::Assistant(text("text"))),
+        "thinking" => Some(Part::Thinking(text("thinking"))),
+        "tool_use" => Some(Part::ToolCall {
+            name: text("name"),
+            input: block
+                .get("input")
+                .and_then(|i| serde_json::to_string_pretty(i).ok())
+                .unwrap_or_default(),
+        }),
+        "tool_result" => Some(Part::ToolResult {
+            id: text("tool_use_id"),
+            is_error: block.get("is_error").and_then(Value::as_bool).unwrap_or(false),
+            text: result_text(block.get("content")),
+        }),
+        "image" => Some(if user {
+            Part::User("[image]".to_string())
+        } else {
+            Part::Assistant("[image]".to_string())
+        }),
+        _ => None,
+    }
+}
+
+/// Tool result content is either a string or a list of text/image blocks.
+fn result_text(content: Option<&Value>) -> String {
+    match content {
+        Some(Value::String(s)) => s.clone(),
+        Some(Value::Array(items)) => items
+            .iter()
+            .map(|item| {
+                item.get("text")
+                    .and_then(Value::as_str)
+                    .map_or_else(|| "[image]".to_string(), str::to_string)
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => String::new(),
+    }
+}
+
+fn header(messages: &[ReplayMessage], index: usize) -> String {
+    let msg = &messages[index];
+    let agent = msg
+        .agent_id
+        .as_deref()
+        .map_or_else(String::new, |a| format!(" (agent {a})"));
+    format!(
+        "[{}/{}] {} {}{agent} {}",
+        index + 1,
+        messages.len(),
+        msg.timestamp,
+        msg.kind,
+        msg.uuid
+    )
+}
+
+pub const fn part_label(part: &Part) -> &'static str {
+    match part {
+        Part::User(_) => "USER",
+        Part::Assistant(_) => "ASSISTANT",
+        Part::Thinking(_) => "THINKING",
+        Part::ToolCall { .. } => "TOOL CALL",
+        Part::ToolResult { is_error: true, .. } => "TOOL ERROR",
+        Part::ToolResult { .. } => "TOOL RESULT",
+        Part::System(_) => "SYSTEM",
+    }
+}
+
+pub fn part_body(part: &Part) -> String {
+    match part {
+        Part::User(t) | Part::Assistant(t) | Part::Thinking(t) | Part::System(t) => t.clone(),
+        Part::ToolCall { name, input } => format!("{name} {input}"),
+        Part::ToolResult { id, text, .. } => format!("({id})\n{text}"),
+    }
+}
+
+/// Print every message in order (non-interactive output).
+pub fn print_all(messages: &[ReplayMessage]) {
+    print!("{}", render_all(messages));
+}
+
+/// Every message in order as readable text, long tool results cut.
+pub fn render_all(messages: &[ReplayMessage]) -> String {
+    let mut out = String::new();
+    for i in 0..messages.len() {
+        let _ = writeln!(out, "── {}", header(messages, i));
+        for part in parts(&messages[i]) {
+            let mut body = part_body(&part);
+            if matches!(part, Part::ToolResult { .. }) && body.chars().count() > PRINT_RESULT_CHARS {
+                body = body.chars().take(PRINT_RESULT_CHARS).collect::<String>() + "…";
+            }
+            let _ = writeln!(out, "{}: {body}", part_label(&part));
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// Step through messages interactively.
+///
+/// Keys: `n`/`Space`/`→` next, `p`/`←` previous, `j`/`k` scroll,
+/// `g`/`G` first/last, `q` quit.
+///
+/// # Errors
+/// Returns error if the terminal can't be drawn.
+pub fn run(messages: &[ReplayMessage]) -> Result<()> {
+    let mut terminal = ratatui::init();
+    let result = event_loop(&mut terminal, messages);
+    ratatui::restore();
+    result
+}
+
+fn event_loop(terminal: &mut DefaultTerminal, messages: &[ReplayMessage]) -> Result<()> {
+    let mut index = 0usize;
+    let mut scroll = 0u16;
+    let last = messages.len().saturating_sub(1);
+
+    loop {
+        terminal.draw(|frame| draw(frame, messages, index, scroll))?;
+
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        let before = index;
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return Ok(()),
+            KeyCode::Char('n' | ' ') | KeyCode::Right | KeyCode::Enter => {
+                index = (index + 1).min(last);
+            }
+            KeyCode::Char('p') | KeyCode::Left | KeyCode::Backspace => {
+                index = index.saturating_sub(1);
+            }
+            KeyCode::Char('g') | KeyCode::Home => index = 0,
+            KeyCode::Char('G') | KeyCode::End => index = last,
+            KeyCode::Char('j') | KeyCode::Down => scroll = scroll.saturating_add(1),
+            KeyCode::Char('k') | KeyCode::Up => scroll = scroll.saturating_sub(1),
+            KeyCode::PageDown => scroll = scroll.saturating_add(20),
+            KeyCode::PageUp => scroll = scroll.saturating_sub(20),
+            _ => {}
+        }
+        if index != before {
+            scroll = 0;
+        }
+    }
+}
+
+fn draw(frame: &mut Frame, messages: &[ReplayMessage], index: usize, scroll: u16) {
+    let [body, footer] =
+        Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(frame.area());
+
+    let mut lines: Vec<Line> = Vec::new();
+    for part in parts(&messages[index]) {
+        let style = match part {
+            Part::User(_) => Style::new().fg(Color::Green),
+            Part::Assistant(_) => Style::new().fg(Color::Cyan),
+            Part::Thinking(_) => Style::new().dim(),
+            Part::ToolCall { .. } => Style::new().fg(Color::Yellow),
+            Part::ToolResult { is_error: true, .. } => Style::new().fg(Color::Red),
+            Part::ToolResult { .. } => Style::new().fg(Color::Magenta),
+            Part::System(_) => Style::new().fg(Color::Blue),
+        };
+        lines.push(Line::from(Span::styled(part_label(&part), style.bold())));
+        lines.extend(part_body(&part).lines().map(|l| Line::from(l.to_string())));
+        lines.push(Line::default());
+    }
+
+    frame.render_widget(
+        Paragraph::new(Text::from(lines))
+            .wrap(Wrap { trim: false })
+            .scroll((scroll, 0))
+            .block(Block::bordered().title(format!(" {} ", header(messages, index)))),
+        body,
+    );
+    frame.render_widget(
+        Line::from(" n/→ next · p/← previous · j/k scroll · g/G first/last · q quit").dim(),
+        footer,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn message(kind: &str, message: Value) -> ReplayMessage {
+        ReplayMessage {
+            uuid: "u".to_string(),
+            kind: kind.to_string(),
+            subtype: None,
+            timestamp: "2025-01-01 00:00:00.000".to_string(),
+            agent_id: None,
+            message,
+            content: None,
+        }
+    }
+
+    #[test]
+    fn test_parts_assistant_blocks() {
+        let msg = message(
+            "assistant",
+            json!({"content": [
+                {"type": "thinking", "thinking": "hmm"},
+                {"type": "text", "text": "Let me look"},
+                {"type": "tool_use", "id": "t1", "name": "Bash", "input": {"command": "ls"}}
+            ]}),
+        );
+        let parts = parts(&msg);
+        assert_eq!(parts[0], Part::Thinking("hmm".to_string()));
+        assert_eq!(parts[1], Part::Assistant("Let me look".to_string()));
+        assert!(matches!(&parts[2], Part::ToolCall { name, .. } if name == "Bash"));
+    }
+
+    #[test]
+    fn test_parts_user_prompt_and_tool_result() {
+        assert_eq!(
+            parts(&message("user", json!({"content": "fix it"}))),
+            vec![Part::User("fix it".to_string())]
+        );
+        let result = message(
+            "user",
+            json!({"content": [{"type": "tool_result", "tool_use_id": "t1", "is_error": true,
+                                "content": [{"type": "text", "text": "boom"}]}]}),
+        );
+        assert_eq!(
+            parts(&result),
+            vec![Part::ToolResult {
+                id: "t1".to_string(),
+                is_error: true,
+                text: "boom".to_string()
+            }]
+        );
+    }
+}
@@ -0,0 +1,130 @@
+//! Named queries saved from the REPL (`.save` / `.run`), stored one
+//! `<name>.sql` file each in `~/.config/ccq/queries/`.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::utils::ccq_config_dir;
+use crate::{Error, Result};
+
+/// Directory holding saved queries.
+pub fn dir() -> Option<PathBuf> {
+    ccq_config_dir().map(|dir| dir.join("queries"))
+}
+
+fn path(dir: &Path, name: &str) -> Result<PathBuf> {
+    let valid = !name.is_empty()
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(dir.join(format!("{name}.sql")))
+    } else {
+        Err(Error::InvalidQueryName(name.to_string()))
+    }
+}
+
+/// Save `sql` as `name`, replacing any query of that name.
+///
+/// # Errors
+/// Returns error if the name is invalid or the file can't be written.
+pub fn save(dir: &Path, name: &str, sql: &str) -> Result<PathBuf> {
+    let path = path(dir, name)?;
+    fs::create_dir_all(dir)?;
+    fs::write(&path, format!("{}\n", sql.trim()))?;
+    Ok(path)
+}
+
+/// Read the query saved as `name`.
+///
+/// # Errors
+/// Returns error if no such query exists or it can't be read.
+pub fn load(dir: &Path, name: &str) -> Result<String> {
+    match fs::read_to_string(path(dir, name)?) {
+        Ok(sql) => Ok(sql.trim().to_string()),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            Err(Error::SavedQueryNotFound(name.to_string()))
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Saved `(name, sql)` pairs sorted by name; empty if the directory is missing.
+pub fn list(dir: &Path) -> Vec<(String, String)> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut queries: Vec<_> = entries
+        .filter_map(std::result::Result::ok)
+        .filter_map(|entry| {
+            let path = entry.path();
+            let name = path.file_stem()?.to_str()?.to_string();
+            let is_sql = path.extension().is_some_and(|ext| ext == "sql");
+            let sql = fs::read_to_string(&path).ok().filter(|_| is_sql)?;
+            Some((name, sql.trim().to_string()))
+        })
+        .collect();
+    queries.sort();
+    queries
+}
+
+/// Replace `$1`, `$2`, ... with `args`, verbatim.
+///
+/// Quote in the saved query where a string is expected
+/// (`WHERE sessionId LIKE '$1%'`). A `$` not followed by a digit (JSON paths
+/// like `'$.a'`) is left alone.
+///
+/// # Errors
+/// Returns error if the query references an argument that wasn't given.
+pub fn substitute_args(sql: &str, args: &[&str]) -> Result<String> {
+    let mut out = String::with_capacity(sql.len());
+    let mut rest = sql;
+    while let Some(i) = rest.find('$') {
+        out.push_str(&rest[..i]);
+        let after = &rest[i + 1..];
+        let digits = after.find(|c: char| !c.is_ascii_digit()).unwrap_or(after.len());
+        match after[..digits].parse::<usize>() {
+            Ok(n) if n >= 1 => {
+                out.push_str(args.get(n - 1).ok_or(Error::MissingArgument(n))?);
+                rest = &after[digits..];
+            }
+            _ => {
+                out.push('$');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_substitute_args() {
+        let sql = "SELECT message->>'$.id' FROM messages WHERE sessionId LIKE '$1%' LIMIT $2";
+        assert_eq!(
+            substitute_args(sql, &["abc", "10"]).unwrap(),
+            "SELECT message->>'$.id' FROM messages WHERE sessionId LIKE 'abc%' LIMIT 10"
+        );
+        assert!(matches!(substitute_args(sql, &["abc"]), Err(Error::MissingArgument(2))));
+        assert_eq!(substitute_args("SELECT $0", &[]).unwrap(), "SELECT $0");
+    }
+
+    #[test]
+    fn test_save_load_list() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let dir = tmp.path().join("queries");
+        assert!(list(&dir).is_empty());
+
+        save(&dir, "tools", "SELECT tool_name FROM tool_uses;\n").unwrap();
+        save(&dir, "by-day", "SELECT 1").unwrap();
+        assert_eq!(load(&dir, "tools").unwrap(), "SELECT tool_name FROM tool_uses;");
+        let names: Vec<_> = list(&dir).into_iter().map(|(name, _)| name).collect();
+        assert_eq!(names, ["by-day", "tools"]);
+
+        assert!(matches!(load(&dir, "missing"), Err(Error::SavedQueryNotFound(_))));
+        assert!(matches!(save(&dir, "../x", "SELECT 1"), Err(Error::InvalidQueryName(_))));
+    }
+}
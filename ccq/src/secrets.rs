@@ -0,0 +1,185 @@
+//! Secret scanning over session content.
+
+use crate::utils::sql_quote;
+use crate::{QuerySession, Result};
+
+/// A named regular expression (RE2 syntax, evaluated by `DuckDB`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Detector {
+    pub name: String,
+    pub pattern: String,
+}
+
+impl Detector {
+    /// Create a detector from a name and RE2 pattern.
+    pub fn new(name: impl Into<String>, pattern: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            pattern: pattern.into(),
+        }
+    }
+
+    /// Parse a `NAME=REGEX` detector specification.
+    pub fn parse(spec: &str) -> Option<Self> {
+        let (name, pattern) = spec.split_once('=')?;
+        let name = name.trim();
+        if name.is_empty() || pattern.is_empty() {
+            return None;
+        }
+        Some(Self::new(name, pattern))
+    }
+}
+
+/// Built-in detectors: AWS credentials, PEM private keys, and JWTs.
+pub fn builtin_detectors() -> Vec<Detector> {
+    vec![
+        Detector::new("aws-access-key", r"\b(?:AKIA|ASIA)[0-9A-Z]{16}\b"),
+        Detector::new(
+            "aws-secret-key",
+            r"(?i)aws_?secret_?access_?key\W{1,6}[A-Za-z0-9/+=]{40}",
+        ),
+        Detector::new("private-key", r"-----BEGIN (?:[A-Z0-9]+ )*PRIVATE KEY-----"),
+        Detector::new(
+            "jwt",
+            r"\beyJ[A-Za-z0-9_-]{8,}\.eyJ[A-Za-z0-9_-]{8,}\.[A-Za-z0-9_-]{8,}",
+        ),
+    ]
+}
+
+/// A single detector match with its source location.
+#[derive(Debug, Clone)]
+pub struct Finding {
+    pub detector: String,
+    pub file: String,
+    /// 1-based line number within `file`
+    pub line: usize,
+    pub uuid: String,
+    pub matched: String,
+}
+
+impl Finding {
+    /// The matched text with everything but a short prefix masked.
+    pub fn redacted(&self) -> String {
+        redact(&self.matched)
+    }
+}
+
+/// Scan message content, tool results, and system content in every loaded
+/// JSONL file with the given detectors.
+///
+/// # Errors
+/// Returns error if the scan query fails (e.g. an invalid regex).
+pub fn scan(session: &QuerySession, detectors: &[Detector]) -> Result<Vec<Finding>> {
+    if detectors.is_empty() {
+        return Ok(Vec::new());
+    }
+    let sql = build_scan_sql(&session.info().file_pattern().to_string(), detectors);
+    let result = session.query(&sql)?;
+
+    Ok(result
+        .rows()
+        .iter()
+        .map(|row| Finding {
+            detector: row[0].clone(),
+            file: row[1].clone(),
+            line: row[2].parse().unwrap_or(0),
+            uuid: row[3].clone(),
+            matched: row[4].clone(),
+        })
+        .collect())
+}
+
+/// Print findings as `file:line` references followed by a summary line.
+pub fn print_report(findings: &[Finding]) {
+    for f in findings {
+        println!("{}:{}  {}  {}  {}", f.file, f.line, f.detector, f.redacted(), f.uuid);
+    }
+
+    let mut files: Vec<&str> = findings.iter().map(|f| f.file.as_str()).collect();
+    files.sort_unstable();
+    files.dedup();
+    let word = if findings.len() == 1 { "finding" } else { "findings" };
+    println!("{} {word} in {} file(s)", findings.len(), files.len());
+}
+
+/// Build the scan query. Files are split into lines before parsing, so line
+/// numbers stay right after blank, malformed, or truncated lines.
+fn build_scan_sql(pattern_sql: &str, detectors: &[Detector]) -> String {
+    let detectors_sql = detectors
+        .iter()
+        .map(|d| format!("({}, {})", sql_quote(&d.name), sql_quote(&d.pattern)))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    format!(
+        r"
+    WITH files AS (
+      SELECT filename, string_split(content, chr(10)) as raw_lines
+      FROM read_text({pattern_sql})
+    ),
+    numbered AS (
+      SELECT filename, unnest(raw_lines) as raw, generate_subscripts(raw_lines, 1) as line
+      FROM files
+    ),
+    lines AS (
+      SELECT
+        filename,
+        line,
+        json->>'uuid' as uuid,
+        concat_ws(' ',
+          (json->'message')::VARCHAR,
+          (json->'toolUseResult')::VARCHAR,
+          (json->'content')::VARCHAR
+        ) as text
+      FROM (
+        SELECT filename, line, CASE WHEN json_valid(raw) THEN raw::JSON END as json
+        FROM numbered
+      )
+    ),
+    detectors(name, pattern) AS (VALUES {detectors_sql})
+    SELECT d.name, l.filename, l.line, COALESCE(l.uuid, ''), m.matched
+    FROM lines l
+    CROSS JOIN detectors d,
+    LATERAL UNNEST(regexp_extract_all(l.text, d.pattern)) as m(matched)
+    ORDER BY l.filename, l.line, d.name
+  "
+    )
+}
+
+/// Mask all but the first four characters of a secret.
+fn redact(secret: &str) -> String {
+    let total = secret.chars().count();
+    let shown: String = secret.chars().take(4).collect();
+    if total <= 4 {
+        return "*".repeat(total);
+    }
+    format!("{shown}{} ({total} chars)", "*".repeat((total - 4).min(12)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detector_parse() {
+        let d = Detector::parse("slack=xox[bp]-[0-9A-Za-z-]+").unwrap();
+        assert_eq!(d.name, "slack");
+        assert_eq!(d.pattern, "xox[bp]-[0-9A-Za-z-]+");
+        assert!(Detector::parse("no-equals").is_none());
+        assert!(Detector::parse("=pattern").is_none());
+    }
+
+    #[test]
+    fn test_redact() {
+        assert_eq!(redact("abc"), "***");
+        assert_eq!(redact("AKIAABCDEFGH"), "AKIA******** (12 chars)");
+    }
+
+    #[test]
+    fn test_build_scan_sql_escapes_patterns() {
+        let sql = build_scan_sql("'/p/*.jsonl'", &[Detector::new("q", "it's")]);
+        assert!(sql.contains("('q', 'it''s')"));
+        assert!(sql.contains("read_text('/p/*.jsonl')"));
+        assert!(sql.contains("generate_subscripts(raw_lines, 1) as line"));
+    }
+}
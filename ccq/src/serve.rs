@@ -0,0 +1,276 @@
+//! HTTP API over the loaded session data.
+//!
+//! Routes:
+//! - `POST /query` — JSON body `{"sql": "..."}` sent as `application/json`,
+//!   returns `{"columns", "row_count", "rows"}`
+//! - `GET /views` — names of the available views
+//!
+//! There is no authentication; bind to a non-loopback address only on
+//! trusted networks. Web pages can't use the API: requests with a foreign
+//! `Origin` or a domain-name `Host` other than localhost are refused, and
+//! requiring a JSON body forces a CORS preflight, which is never granted.
+//! Queries can only read files under the session data directories.
+
+use std::io::{self, Read};
+use std::net::IpAddr;
+
+use serde_json::{Value, json};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::{QuerySession, Result};
+
+/// Largest accepted request body.
+const MAX_BODY_BYTES: u64 = 1024 * 1024;
+
+/// What a vetted request asks for.
+#[derive(Debug, PartialEq, Eq)]
+enum Action {
+    Query(String),
+    Views,
+    Reject(u16, String),
+}
+
+/// Serve the API on `addr` until the process is terminated.
+///
+/// Requests are handled one at a time on the session's connection.
+///
+/// # Errors
+/// Returns error if file access can't be restricted or the address can't be bound.
+pub fn serve(session: &QuerySession, addr: &str) -> Result<()> {
+    session.restrict_file_access()?;
+    let server = Server::http(addr).map_err(io::Error::other)?;
+    eprintln!("Listening on http://{addr}");
+
+    for mut request in server.incoming_requests() {
+        let (status, body) = match route(&mut request, addr) {
+            Action::Query(sql) => query(session, &sql),
+            Action::Views => views(session),
+            Action::Reject(status, message) => error(status, &message),
+        };
+        let response = Response::from_string(body.to_string())
+            .with_status_code(status)
+            .with_header(json_header());
+        if let Err(e) = request.respond(response) {
+            eprintln!("Error: {e}");
+        }
+    }
+    Ok(())
+}
+
+fn json_header() -> Header {
+    Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..])
+        .expect("static header is valid")
+}
+
+fn route(request: &mut Request, addr: &str) -> Action {
+    if let Err(message) = check_origin(request, addr) {
+        return Action::Reject(403, message);
+    }
+    match (request.method(), request.url()) {
+        (Method::Post, "/query") => {
+            let is_json =
+                header(request, "Content-Type").is_some_and(|v| v.starts_with("application/json"));
+            if !is_json {
+                return Action::Reject(415, "Content-Type must be application/json".to_string());
+            }
+            match read_sql(request) {
+                Ok(sql) => Action::Query(sql),
+                Err(message) => Action::Reject(400, message),
+            }
+        }
+        (Method::Get, "/views") => Action::Views,
+        (_, "/query" | "/views") => Action::Reject(405, "method not allowed".to_string()),
+        _ => Action::Reject(404, "not found".to_string()),
+    }
+}
+
+fn header<'r>(request: &'r Request, name: &'static str) -> Option<&'r str> {
+    request
+        .headers()
+        .iter()
+        .find(|h| h.field.equiv(name))
+        .map(|h| h.value.as_str())
+}
+
+/// Refuse requests a browser sends on behalf of another site: an `Origin`
+/// that isn't the server itself, or a `Host` naming some other domain (DNS
+/// rebinding). `Host` may be localhost, an IP address, or the bind address.
+fn check_origin(request: &Request, addr: &str) -> std::result::Result<(), String> {
+    let host = header(request, "Host");
+    if let Some(host) = host {
+        let name = host_name(host);
+        let bound = host_name(addr);
+        if !(name.eq_ignore_ascii_case("localhost")
+            || name.parse::<IpAddr>().is_ok()
+            || name.eq_ignore_ascii_case(bound))
+        {
+            return Err(format!("Host not allowed: {host}"));
+        }
+    }
+    if let Some(origin) = header(request, "Origin") {
+        let authority = origin
+            .strip_prefix("http://")
+            .or_else(|| origin.strip_prefix("https://"));
+        if authority.is_none() || authority != host {
+            return Err(format!("Origin not allowed: {origin}"));
+        }
+    }
+    Ok(())
+}
+
+/// Host part of a `host[:port]` authority, without IPv6 brackets.
+fn host_name(authority: &str) -> &str {
+    if let Some(rest) = authority.strip_prefix('[') {
+        return rest.split(']').next().unwrap_or(rest);
+    }
+    authority
+        .rsplit_once(':')
+        .map_or(authority, |(host, _)| host)
+}
+
+fn query(session: &QuerySession, sql: &str) -> (u16, Value) {
+    match session.query_json(sql) {
+        Ok(result) => (200, result),
+        Err(e) => error(400, &e.to_string()),
+    }
+}
+
+fn views(session: &QuerySession) -> (u16, Value) {
+    let sql = "SELECT view_name FROM duckdb_views() WHERE NOT internal ORDER BY view_name";
+    match session.query(sql) {
+        Ok(result) => {
+            let names: Vec<&str> = result.rows().iter().map(|row| row[0].as_str()).collect();
+            (200, json!({ "views": names }))
+        }
+        Err(e) => error(500, &e.to_string()),
+    }
+}
+
+fn error(status: u16, message: &str) -> (u16, Value) {
+    (status, json!({ "error": message }))
+}
+
+/// Read the SQL from a JSON request body.
+fn read_sql(request: &mut Request) -> std::result::Result<String, String> {
+    let mut body = String::new();
+    request
+        .as_reader()
+        .take(MAX_BODY_BYTES)
+        .read_to_string(&mut body)
+        .map_err(|e| e.to_string())?;
+    parse_sql_body(&body)
+}
+
+fn parse_sql_body(body: &str) -> std::result::Result<String, String> {
+    let value: Value = serde_json::from_str(body).map_err(|e| e.to_string())?;
+    let sql = value
+        .get("sql")
+        .and_then(Value::as_str)
+        .ok_or("expected a JSON object with a \"sql\" string")?;
+    if sql.trim().is_empty() {
+        return Err("empty query".to_string());
+    }
+    Ok(sql.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tiny_http::TestRequest;
+
+    const ADDR: &str = "127.0.0.1:7878";
+
+    fn with_header(request: TestRequest, field: &str, value: &str) -> TestRequest {
+        request.with_header(Header::from_bytes(field.as_bytes(), value.as_bytes()).unwrap())
+    }
+
+    fn post_query(body: &'static str, content_type: &str) -> TestRequest {
+        let request = TestRequest::new()
+            .with_method(Method::Post)
+            .with_path("/query")
+            .with_body(body);
+        with_header(
+            with_header(request, "Host", ADDR),
+            "Content-Type",
+            content_type,
+        )
+    }
+
+    fn route_test(request: TestRequest) -> Action {
+        route(&mut request.into(), ADDR)
+    }
+
+    #[test]
+    fn test_parse_sql_body() {
+        assert_eq!(
+            parse_sql_body(r#"{"sql": "SELECT 2"}"#).unwrap(),
+            "SELECT 2"
+        );
+        assert!(parse_sql_body(r#"{"query": "SELECT 2"}"#).is_err());
+        assert!(parse_sql_body(r#"{"sql": "  \n"}"#).is_err());
+        assert!(parse_sql_body("SELECT 1").is_err());
+    }
+
+    #[test]
+    fn test_route_query_requires_json() {
+        assert_eq!(
+            route_test(post_query(
+                r#"{"sql": "SELECT 1"}"#,
+                "application/json; charset=utf-8"
+            )),
+            Action::Query("SELECT 1".to_string())
+        );
+        assert!(matches!(
+            route_test(post_query("SELECT 1", "text/plain")),
+            Action::Reject(415, _)
+        ));
+        assert!(matches!(
+            route_test(post_query("{}", "application/json")),
+            Action::Reject(400, _)
+        ));
+    }
+
+    #[test]
+    fn test_route_rejects_foreign_origin_and_host() {
+        let body = r#"{"sql": "SELECT 1"}"#;
+        let foreign = with_header(
+            post_query(body, "application/json"),
+            "Origin",
+            "https://evil.example",
+        );
+        assert!(matches!(route_test(foreign), Action::Reject(403, _)));
+        let same = with_header(
+            post_query(body, "application/json"),
+            "Origin",
+            "http://127.0.0.1:7878",
+        );
+        assert_eq!(route_test(same), Action::Query("SELECT 1".to_string()));
+
+        let views = TestRequest::new().with_path("/views");
+        assert_eq!(
+            route_test(with_header(views, "Host", "localhost:7878")),
+            Action::Views
+        );
+        let rebound = with_header(
+            TestRequest::new().with_path("/views"),
+            "Host",
+            "evil.example:7878",
+        );
+        assert!(matches!(route_test(rebound), Action::Reject(403, _)));
+    }
+
+    #[test]
+    fn test_route_unknown_paths_and_methods() {
+        let get_query = with_header(TestRequest::new().with_path("/query"), "Host", ADDR);
+        assert!(matches!(route_test(get_query), Action::Reject(405, _)));
+        let missing = with_header(TestRequest::new().with_path("/nope"), "Host", ADDR);
+        assert!(matches!(route_test(missing), Action::Reject(404, _)));
+    }
+
+    #[test]
+    fn test_host_name() {
+        assert_eq!(host_name("localhost:7878"), "localhost");
+        assert_eq!(host_name("[::1]:7878"), "::1");
+        assert_eq!(host_name("example.com"), "example.com");
+    }
+}
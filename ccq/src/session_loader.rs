@@ -4,10 +4,11 @@
 
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 use walkdir::WalkDir;
 use rayon::prelude::*;
 
-use crate::utils::{claude_projects_base, resolve_project_dir};
+use crate::utils::{claude_projects_base, resolve_project_dir, sql_quote};
 use crate::Result;
 
 /// Pattern for `DuckDB` to read JSONL files.
@@ -20,14 +21,41 @@ pub enum FilePattern {
     Multiple(Vec<String>),
 }
 
+impl FilePattern {
+    /// The individual glob patterns; empty when nothing was matched.
+    pub fn patterns(&self) -> Vec<&str> {
+        match self {
+            Self::Single(p) if p.is_empty() => Vec::new(),
+            Self::Single(p) => vec![p.as_str()],
+            Self::Multiple(ps) => ps.iter().map(String::as_str).collect(),
+        }
+    }
+
+    /// Directories the patterns read from: each pattern up to the last
+    /// separator before its first glob character, with the trailing slash.
+    pub fn root_dirs(&self) -> Vec<&str> {
+        let mut dirs: Vec<&str> = self
+            .patterns()
+            .into_iter()
+            .map(|p| {
+                let glob_at = p.find(['*', '?', '[', '{']).unwrap_or(p.len());
+                p[..glob_at].rfind(['/', '\\']).map_or("./", |i| &p[..=i])
+            })
+            .collect();
+        dirs.sort_unstable();
+        dirs.dedup();
+        dirs
+    }
+}
+
 impl std::fmt::Display for FilePattern {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::Single(p) => write!(f, "'{p}'"),
+            Self::Single(p) => write!(f, "{}", sql_quote(p)),
             Self::Multiple(ps) => {
                 let joined = ps
                     .iter()
-                    .map(|p| format!("'{p}'"))
+                    .map(|p| sql_quote(p))
                     .collect::<Vec<_>>()
                     .join(", ");
                 write!(f, "[{joined}]")
@@ -36,6 +64,29 @@ impl std::fmt::Display for FilePattern {
     }
 }
 
+/// Discovery counts for a single scanned directory.
+#[derive(Debug, Clone)]
+pub struct DirectoryStats {
+    /// Directory that was walked
+    pub path: PathBuf,
+    /// Session files matched
+    pub sessions: usize,
+    /// Agent files matched
+    pub agents: usize,
+    /// All JSONL files found, matched or not
+    pub jsonl_files: usize,
+    /// Modification time of the most recently written matched file
+    pub newest: Option<SystemTime>,
+    /// Session IDs (file names without `.jsonl`) of the matched session files
+    pub session_ids: Vec<String>,
+}
+
+impl DirectoryStats {
+    fn scan(dir: &Path, session_filter: Option<&str>) -> Self {
+        walk_and_count(dir, session_filter)
+    }
+}
+
 /// Information about discovered session files.
 #[derive(Debug, Clone)]
 pub struct SessionInfo {
@@ -43,6 +94,8 @@ pub struct SessionInfo {
     agent_count: usize,
     project_count: usize,
     file_pattern: FilePattern,
+    directories: Vec<DirectoryStats>,
+    counted: bool,
 }
 
 impl SessionInfo {
@@ -65,14 +118,49 @@ impl SessionInfo {
     pub const fn file_pattern(&self) -> &FilePattern {
         &self.file_pattern
     }
+
+    /// Per-directory discovery counts.
+    pub fn directories(&self) -> &[DirectoryStats] {
+        &self.directories
+    }
+
+    /// Whether the counts are real; `false` when discovery skipped the walk.
+    pub const fn is_counted(&self) -> bool {
+        self.counted
+    }
+
+    /// When the newest matched session or agent file was last written;
+    /// `None` when discovery skipped the walk.
+    pub fn newest_modified(&self) -> Option<SystemTime> {
+        self.directories.iter().filter_map(|d| d.newest).max()
+    }
+
+    /// Sorted, distinct IDs of the session files found; empty when discovery
+    /// skipped the walk.
+    pub fn session_ids(&self) -> Vec<String> {
+        let mut ids: Vec<String> = self
+            .directories
+            .iter()
+            .flat_map(|d| d.session_ids.iter().cloned())
+            .collect();
+        ids.sort();
+        ids.dedup();
+        ids
+    }
 }
 
-/// Single-pass file discovery that counts sessions, agents, and total JSONL files.
-/// Returns: (sessions, agents, `total_jsonl_files`)
-fn walk_and_count(dir: &Path, session_filter: Option<&str>) -> (usize, usize, usize) {
+/// Single-pass file discovery that counts sessions, agents, and total JSONL
+/// files, noting session IDs and the newest matched file's modification time.
+fn walk_and_count(dir: &Path, session_filter: Option<&str>) -> DirectoryStats {
     let mut sessions = 0;
+    let mut session_ids = Vec::new();
     let mut agents = 0;
     let mut total_jsonl = 0;
+    let mut newest: Option<SystemTime> = None;
+    let mut note_modified = |entry: &walkdir::DirEntry| {
+        let modified = entry.metadata().ok().and_then(|m| m.modified().ok());
+        newest = newest.max(modified);
+    };
 
     for entry in WalkDir::new(dir).into_iter().filter_map(|e| e.ok()) {
         if !entry.file_type().is_file() {
@@ -104,18 +192,29 @@ fn walk_and_count(dir: &Path, session_filter: Option<&str>) -> (usize, usize, us
                 };
                 if session_dir.starts_with(filter) {
                     agents += 1;
+                    note_modified(&entry);
                 }
             } else {
                 agents += 1;
+                note_modified(&entry);
             }
         } else if !basename.starts_with("agent-")
             && !is_subagent_path
             && session_filter.is_none_or(|f| basename.starts_with(f))
         {
             sessions += 1;
+            session_ids.push(basename.trim_end_matches(".jsonl").to_string());
+            note_modified(&entry);
         }
     }
-    (sessions, agents, total_jsonl)
+    DirectoryStats {
+        path: dir.to_path_buf(),
+        sessions,
+        agents,
+        jsonl_files: total_jsonl,
+        newest,
+        session_ids,
+    }
 }
 
 /// Get all project directories under ~/.claude/projects.
@@ -142,11 +241,13 @@ fn get_all_project_dirs() -> Vec<PathBuf> {
 pub fn get_session_files(
     project_path: Option<&Path>,
     session_filter: Option<&str>,
-    data_dir: Option<&Path>,
+    data_dirs: &[PathBuf],
 ) -> Result<SessionInfo> {
-    // Mode 1: Direct data directory
-    if let Some(dir) = data_dir {
-        return get_session_files_data_dir(dir, session_filter);
+    // Mode 1: Direct data directories
+    match data_dirs {
+        [] => {}
+        [dir] => return get_session_files_data_dir(dir, session_filter),
+        dirs => return get_session_files_data_dirs(dirs, session_filter),
     }
 
     // Mode 2: All projects (no project path specified)
@@ -159,10 +260,64 @@ pub fn get_session_files(
     get_session_files_project(&resolved.claude_data_dir, session_filter)
 }
 
+/// Build file patterns without walking the tree (`--no-count`).
+///
+/// Unfiltered patterns are the broad `**/*.jsonl` globs, emitted as-is.
+/// Session-filtered patterns are only kept when they match something, which
+/// needs a single directory listing rather than a recursive walk. Counts are
+/// left at zero; see [`SessionInfo::is_counted`].
+pub fn get_session_files_uncounted(
+    project_path: Option<&Path>,
+    session_filter: Option<&str>,
+    data_dirs: &[PathBuf],
+) -> SessionInfo {
+    let roots = if !data_dirs.is_empty() {
+        data_dirs.to_vec()
+    } else if let Some(project_path) = project_path {
+        vec![resolve_project_dir(&project_path.to_string_lossy()).claude_data_dir]
+    } else {
+        vec![claude_projects_base().join("*")]
+    };
+
+    let patterns: Vec<String> = roots
+        .iter()
+        .flat_map(|root| match session_filter {
+            None => vec![root.join("**/*.jsonl")],
+            Some(filter) => [
+                root.join(format!("{filter}*.jsonl")),
+                root.join(format!("{filter}*/subagents/*.jsonl")),
+            ]
+            .into_iter()
+            .filter(|p| glob_matches(p))
+            .collect(),
+        })
+        .map(|p| p.to_string_lossy().into_owned())
+        .collect();
+
+    SessionInfo {
+        session_count: 0,
+        agent_count: 0,
+        project_count: 0,
+        file_pattern: match patterns.len() {
+            0 => FilePattern::Single(String::new()),
+            1 => FilePattern::Single(patterns[0].clone()),
+            _ => FilePattern::Multiple(patterns),
+        },
+        directories: vec![],
+        counted: false,
+    }
+}
+
+fn glob_matches(pattern: &Path) -> bool {
+    glob::glob(&pattern.to_string_lossy())
+        .is_ok_and(|mut paths| paths.any(|p| p.is_ok()))
+}
+
 /// Get session files from a direct data directory.
 #[allow(clippy::unnecessary_wraps)]
 fn get_session_files_data_dir(dir: &Path, session_filter: Option<&str>) -> Result<SessionInfo> {
-    let (sessions, agents, total_jsonl) = walk_and_count(dir, session_filter);
+    let stats = DirectoryStats::scan(dir, session_filter);
+    let (sessions, agents, total_jsonl) = (stats.sessions, stats.agents, stats.jsonl_files);
 
     if sessions == 0 && agents == 0 {
         if total_jsonl == 0 {
@@ -171,6 +326,8 @@ fn get_session_files_data_dir(dir: &Path, session_filter: Option<&str>) -> Resul
                 agent_count: 0,
                 project_count: 0,
                 file_pattern: FilePattern::Single(String::new()),
+                directories: vec![stats],
+                counted: true,
             });
         }
 
@@ -180,6 +337,8 @@ fn get_session_files_data_dir(dir: &Path, session_filter: Option<&str>) -> Resul
             agent_count: 0,
             project_count: 1,
             file_pattern: FilePattern::Single(dir.join("**/*.jsonl").to_string_lossy().into()),
+            directories: vec![stats],
+            counted: true,
         });
     }
 
@@ -202,6 +361,53 @@ fn get_session_files_data_dir(dir: &Path, session_filter: Option<&str>) -> Resul
         agent_count: agents,
         project_count: 1,
         file_pattern,
+        directories: vec![stats],
+        counted: true,
+    })
+}
+
+/// Get session files from several data directories, merged into one pattern.
+/// Directories without any JSONL files are skipped so `DuckDB` never sees an
+/// unmatched glob, and so are directories the session filter matches nothing
+/// in, rather than falling back to all of their JSONL files.
+#[allow(clippy::unnecessary_wraps)]
+fn get_session_files_data_dirs(dirs: &[PathBuf], session_filter: Option<&str>) -> Result<SessionInfo> {
+    let infos: Vec<SessionInfo> = dirs
+        .par_iter()
+        .map(|dir| {
+            let info = get_session_files_data_dir(dir, session_filter)?;
+            let matched = info.directories.iter().any(|d| d.sessions + d.agents > 0);
+            if session_filter.is_none() || matched {
+                return Ok(info);
+            }
+            Ok(SessionInfo {
+                session_count: 0,
+                agent_count: 0,
+                project_count: 0,
+                file_pattern: FilePattern::Single(String::new()),
+                ..info
+            })
+        })
+        .collect::<Result<_>>()?;
+
+    let patterns: Vec<String> = infos
+        .iter()
+        .flat_map(|info| info.file_pattern.patterns())
+        .map(str::to_string)
+        .collect();
+    let file_pattern = if patterns.is_empty() {
+        FilePattern::Single(String::new())
+    } else {
+        FilePattern::Multiple(patterns)
+    };
+
+    Ok(SessionInfo {
+        session_count: infos.iter().map(|i| i.session_count).sum(),
+        agent_count: infos.iter().map(|i| i.agent_count).sum(),
+        project_count: infos.iter().map(|i| i.project_count).sum(),
+        file_pattern,
+        directories: infos.into_iter().flat_map(|i| i.directories).collect(),
+        counted: true,
     })
 }
 
@@ -211,10 +417,12 @@ fn get_session_files_all_projects(session_filter: Option<&str>) -> Result<Sessio
     let base = claude_projects_base();
     let project_dirs = get_all_project_dirs();
 
-    let (total_sessions, total_agents, _) = project_dirs
+    let directories: Vec<DirectoryStats> = project_dirs
         .par_iter()
-        .map(|dir| walk_and_count(dir, session_filter))
-        .reduce(|| (0, 0, 0), |(s1, a1, j1), (s2, a2, j2)| (s1 + s2, a1 + a2, j1 + j2));
+        .map(|dir| DirectoryStats::scan(dir, session_filter))
+        .collect();
+    let total_sessions: usize = directories.iter().map(|d| d.sessions).sum();
+    let total_agents: usize = directories.iter().map(|d| d.agents).sum();
 
     if total_sessions == 0 {
         return Ok(SessionInfo {
@@ -222,6 +430,8 @@ fn get_session_files_all_projects(session_filter: Option<&str>) -> Result<Sessio
             agent_count: 0,
             project_count: 0,
             file_pattern: FilePattern::Single(String::new()),
+            directories,
+            counted: true,
         });
     }
 
@@ -249,6 +459,8 @@ fn get_session_files_all_projects(session_filter: Option<&str>) -> Result<Sessio
         agent_count: total_agents,
         project_count: project_dirs.len(),
         file_pattern,
+        directories,
+        counted: true,
     })
 }
 
@@ -261,10 +473,13 @@ fn get_session_files_project(claude_dir: &Path, session_filter: Option<&str>) ->
             agent_count: 0,
             project_count: 1,
             file_pattern: FilePattern::Single(String::new()),
+            directories: vec![],
+            counted: true,
         });
     }
 
-    let (sessions, agents, _) = walk_and_count(claude_dir, session_filter);
+    let stats = DirectoryStats::scan(claude_dir, session_filter);
+    let (sessions, agents) = (stats.sessions, stats.agents);
 
     if sessions == 0 {
         return Ok(SessionInfo {
@@ -272,6 +487,8 @@ fn get_session_files_project(claude_dir: &Path, session_filter: Option<&str>) ->
             agent_count: 0,
             project_count: 1,
             file_pattern: FilePattern::Single(String::new()),
+            directories: vec![stats],
+            counted: true,
         });
     }
 
@@ -298,6 +515,8 @@ fn get_session_files_project(claude_dir: &Path, session_filter: Option<&str>) ->
         agent_count: agents,
         project_count: 1,
         file_pattern,
+        directories: vec![stats],
+        counted: true,
     })
 }
 
@@ -325,6 +544,38 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_file_pattern_display_escapes_quotes() {
+        let pattern = FilePattern::Single("/data/it's/*.jsonl".to_string());
+        assert_eq!(format!("{pattern}"), "'/data/it''s/*.jsonl'");
+    }
+
+    #[test]
+    fn test_file_pattern_root_dirs() {
+        let pattern = FilePattern::Multiple(vec![
+            "/data/live/abc*.jsonl".to_string(),
+            "/data/live/abc*/subagents/*.jsonl".to_string(),
+            "/data/archive/**/*.jsonl".to_string(),
+        ]);
+        assert_eq!(pattern.root_dirs(), ["/data/archive/", "/data/live/"]);
+        let single = FilePattern::Single("s.jsonl".to_string());
+        assert_eq!(single.root_dirs(), ["./"]);
+    }
+
+    #[test]
+    fn test_uncounted_skips_unmatched_filter_patterns() {
+        let tmp = TempDir::new().unwrap();
+        create_file(tmp.path(), "abc123.jsonl");
+
+        let info = get_session_files_uncounted(None, Some("abc"), &[tmp.path().to_path_buf()]);
+        assert!(!info.is_counted());
+        assert_eq!(info.file_pattern().patterns().len(), 1);
+        assert!(info.file_pattern().patterns()[0].ends_with("abc*.jsonl"));
+
+        let info = get_session_files_uncounted(None, None, &[tmp.path().to_path_buf()]);
+        assert!(info.file_pattern().patterns()[0].ends_with("**/*.jsonl"));
+    }
+
     fn create_file(dir: &Path, name: &str) {
         let path = dir.join(name);
         if let Some(parent) = path.parent() {
@@ -340,10 +591,10 @@ mod tests {
         create_file(tmp.path(), "abc123.jsonl");
         create_file(tmp.path(), "def456.jsonl");
 
-        let (sessions, agents, total) = walk_and_count(tmp.path(), None);
-        assert_eq!(sessions, 2);
-        assert_eq!(agents, 0);
-        assert_eq!(total, 2);
+        let stats = walk_and_count(tmp.path(), None);
+        assert_eq!(stats.sessions, 2);
+        assert_eq!(stats.agents, 0);
+        assert_eq!(stats.jsonl_files, 2);
     }
 
     #[test]
@@ -352,10 +603,64 @@ mod tests {
         create_file(tmp.path(), "abc123.jsonl");
         create_file(tmp.path(), "def456.jsonl");
 
-        let (sessions, agents, total) = walk_and_count(tmp.path(), Some("abc"));
-        assert_eq!(sessions, 1);
-        assert_eq!(agents, 0);
-        assert_eq!(total, 2);
+        let stats = walk_and_count(tmp.path(), Some("abc"));
+        assert_eq!(stats.sessions, 1);
+        assert_eq!(stats.agents, 0);
+        assert_eq!(stats.jsonl_files, 2);
+        assert_eq!(stats.session_ids, vec!["abc123"]);
+    }
+
+    #[test]
+    fn test_multiple_data_dirs_merge_patterns() {
+        let live = TempDir::new().unwrap();
+        let archive = TempDir::new().unwrap();
+        let empty = TempDir::new().unwrap();
+        fs::write(live.path().join("aaa.jsonl"), "{}\n").unwrap();
+        fs::write(archive.path().join("bbb.jsonl"), "{}\n").unwrap();
+
+        let dirs = vec![
+            live.path().to_path_buf(),
+            archive.path().to_path_buf(),
+            empty.path().to_path_buf(),
+        ];
+        let info = get_session_files(None, None, &dirs).unwrap();
+        assert_eq!(info.session_count(), 2);
+        assert_eq!(info.directories().len(), 3);
+        assert_eq!(info.file_pattern().patterns().len(), 2);
+        assert_eq!(info.session_ids(), vec!["aaa", "bbb"]);
+    }
+
+    #[test]
+    fn test_multiple_data_dirs_with_filter_skip_unmatched_dirs() {
+        let live = TempDir::new().unwrap();
+        let archive = TempDir::new().unwrap();
+        create_file(live.path(), "abc123.jsonl");
+        create_file(live.path(), "def456.jsonl");
+        create_file(archive.path(), "fff789.jsonl");
+
+        let dirs = vec![live.path().to_path_buf(), archive.path().to_path_buf()];
+        let info = get_session_files(None, Some("abc"), &dirs).unwrap();
+        assert_eq!(info.session_count(), 1);
+        assert_eq!(info.project_count(), 1);
+        assert_eq!(info.directories().len(), 2);
+        let patterns = info.file_pattern().patterns();
+        assert_eq!(patterns.len(), 1);
+        assert!(patterns[0].ends_with("abc*.jsonl"));
+        assert!(Path::new(patterns[0]).starts_with(live.path()));
+    }
+
+    #[test]
+    fn test_data_dir_records_directory_stats() {
+        let tmp = TempDir::new().unwrap();
+        create_file(tmp.path(), "abc123.jsonl");
+        create_file(tmp.path(), "abc123/subagents/agent-001.jsonl");
+
+        let info = get_session_files_data_dir(tmp.path(), None).unwrap();
+        let dirs = info.directories();
+        assert_eq!(dirs.len(), 1);
+        assert_eq!(dirs[0].path, tmp.path());
+        assert_eq!((dirs[0].sessions, dirs[0].agents, dirs[0].jsonl_files), (1, 1, 2));
+        assert!(info.newest_modified().is_some());
     }
 
     #[test]
@@ -364,9 +669,9 @@ mod tests {
         create_file(tmp.path(), "abc123.jsonl");
         create_file(tmp.path(), "abc123/subagents/agent-001.jsonl");
 
-        let (sessions, agents, total) = walk_and_count(tmp.path(), None);
-        assert_eq!(sessions, 1);
-        assert_eq!(agents, 1);
-        assert_eq!(total, 2);
+        let stats = walk_and_count(tmp.path(), None);
+        assert_eq!(stats.sessions, 1);
+        assert_eq!(stats.agents, 1);
+        assert_eq!(stats.jsonl_files, 2);
     }
 }
@@ -0,0 +1,176 @@
+//! Locate the JSONL line a message came from, and hand text to `$EDITOR`.
+
+use std::fs;
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use serde_json::Value;
+
+use crate::utils::sql_quote;
+use crate::{Error, QuerySession, Result};
+
+/// A message's source file and 1-based line number.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceLocation {
+    pub path: PathBuf,
+    pub line: usize,
+}
+
+impl std::fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.path.display(), self.line)
+    }
+}
+
+/// Find the source location of the message whose uuid starts with `prefix`.
+///
+/// # Errors
+/// Returns error if no message or more than one message matches.
+pub fn locate(session: &QuerySession, prefix: &str) -> Result<SourceLocation> {
+    let sql = format!(
+        "SELECT DISTINCT json->>'uuid' as uuid, filename
+         FROM read_ndjson_objects({}, filename=true, ignore_errors=true)
+         WHERE starts_with(json->>'uuid', {})
+         ORDER BY uuid, filename",
+        session.info().file_pattern(),
+        sql_quote(&prefix.to_lowercase())
+    );
+    let result = session.query(&sql)?;
+    let rows = result.rows();
+
+    let Some(first) = rows.first() else {
+        return Err(Error::MessageNotFound(prefix.to_string()));
+    };
+    let uuids = rows.iter().filter(|row| row[0] != first[0]).count() + 1;
+    if uuids > 1 {
+        return Err(Error::AmbiguousMessage {
+            prefix: prefix.to_string(),
+            matches: uuids,
+        });
+    }
+
+    let path = PathBuf::from(&first[1]);
+    let line = find_line(&path, &first[0])?
+        .ok_or_else(|| Error::MessageNotFound(prefix.to_string()))?;
+    Ok(SourceLocation { path, line })
+}
+
+/// Open the location in `$VISUAL`/`$EDITOR`. Returns false when neither is set.
+///
+/// # Errors
+/// Returns error if the editor can't be launched.
+pub fn open_in_editor(location: &SourceLocation) -> io::Result<bool> {
+    let Some((mut command, program)) = editor_command() else {
+        return Ok(false);
+    };
+    command.args(editor_args(&program, location));
+    command.status()?;
+    Ok(true)
+}
+
+/// Let the user edit `text` in `$VISUAL`/`$EDITOR` and return what they
+/// saved. Returns `None` when neither is set.
+///
+/// # Errors
+/// Returns error if the editor can't be launched or exits unsuccessfully
+/// (e.g. `:cq` in vim), so an abandoned edit isn't mistaken for a saved one.
+pub fn edit_text(text: &str) -> io::Result<Option<String>> {
+    let Some((mut command, _)) = editor_command() else {
+        return Ok(None);
+    };
+    // Created exclusively with a random name, so nothing else can plant a
+    // symlink at the path first; removed when dropped
+    let mut file = tempfile::Builder::new()
+        .prefix("ccq-edit-")
+        .suffix(".sql")
+        .tempfile()?;
+    file.write_all(text.as_bytes())?;
+    file.flush()?;
+    let status = command.arg(file.path()).status()?;
+    if !status.success() {
+        return Err(io::Error::other(format!("editor exited with {status}")));
+    }
+    fs::read_to_string(file.path()).map(Some)
+}
+
+/// The configured editor command with its arguments, and the program name.
+fn editor_command() -> Option<(Command, String)> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .ok()
+        .filter(|e| !e.trim().is_empty())?;
+    let mut parts = editor.split_whitespace();
+    let program = parts.next().unwrap_or_default().to_string();
+    let mut command = Command::new(&program);
+    command.args(parts);
+    Some((command, program))
+}
+
+/// Arguments that open `location` at its line for common editors.
+fn editor_args(program: &str, location: &SourceLocation) -> Vec<String> {
+    let name = Path::new(program)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or(program);
+    match name {
+        "code" | "code-insiders" | "cursor" | "codium" => {
+            vec!["--goto".to_string(), location.to_string()]
+        }
+        "subl" | "zed" => vec![location.to_string()],
+        // vi, vim, nvim, nano, emacs, micro, helix and most others accept +LINE
+        _ => vec![
+            format!("+{}", location.line),
+            location.path.to_string_lossy().into_owned(),
+        ],
+    }
+}
+
+/// Return the 1-based line whose `uuid` field equals `uuid`.
+fn find_line(path: &Path, uuid: &str) -> Result<Option<usize>> {
+    let reader = BufReader::new(fs::File::open(path)?);
+    for (i, line) in reader.lines().enumerate() {
+        let line = line?;
+        if !line.contains(uuid) {
+            continue;
+        }
+        let matches = serde_json::from_str::<Value>(&line)
+            .ok()
+            .and_then(|v| v.get("uuid").and_then(Value::as_str).map(|u| u == uuid))
+            .unwrap_or(false);
+        if matches {
+            return Ok(Some(i + 1));
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_find_line_matches_uuid_field_only() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("s.jsonl");
+        let mut f = fs::File::create(&path).unwrap();
+        writeln!(f, r#"{{"uuid":"a1","parentUuid":null}}"#).unwrap();
+        writeln!(f, r#"{{"uuid":"b2","parentUuid":"c3"}}"#).unwrap();
+        writeln!(f, r#"{{"uuid":"c3","parentUuid":"a1"}}"#).unwrap();
+
+        assert_eq!(find_line(&path, "c3").unwrap(), Some(3));
+        assert_eq!(find_line(&path, "zz").unwrap(), None);
+    }
+
+    #[test]
+    fn test_editor_args() {
+        let location = SourceLocation {
+            path: PathBuf::from("/p/s.jsonl"),
+            line: 42,
+        };
+        assert_eq!(editor_args("/usr/bin/vim", &location), vec!["+42", "/p/s.jsonl"]);
+        assert_eq!(editor_args("code", &location), vec!["--goto", "/p/s.jsonl:42"]);
+    }
+}
@@ -0,0 +1,250 @@
+//! Current and historical todo lists (`ccq todos`).
+
+use std::fmt::Write;
+
+use crate::utils::sql_quote;
+use crate::{QuerySession, Result};
+
+/// Todo list snapshots from `TodoWrite` calls and the `todos` column.
+const SNAPSHOTS_SQL: &str = r"
+    snapshots AS (
+      SELECT timestamp, sessionId, project, tool_input->'todos' as todos
+      FROM tool_uses
+      WHERE tool_name = 'TodoWrite' AND json_type(tool_input->'todos') = 'ARRAY'
+      UNION ALL
+      SELECT timestamp, sessionId, project, todos
+      FROM user_messages
+      WHERE json_type(todos) = 'ARRAY'
+    )";
+
+/// One todo in a list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TodoItem {
+    pub content: String,
+    pub status: String,
+}
+
+/// The most recent todo list recorded in a session.
+#[derive(Debug, Clone)]
+pub struct TodoList {
+    pub session_id: String,
+    pub project: String,
+    pub updated: String,
+    pub items: Vec<TodoItem>,
+}
+
+/// A todo's status change (`from` is `None` when the todo first appears).
+#[derive(Debug, Clone)]
+pub struct Transition {
+    pub session_id: String,
+    pub timestamp: String,
+    pub content: String,
+    pub from: Option<String>,
+    pub to: String,
+}
+
+/// Which sessions to report on.
+#[derive(Debug, Clone)]
+pub enum Scope {
+    /// A single session by full ID
+    Session(String),
+    /// The most recently updated sessions with todos
+    Recent(usize),
+}
+
+impl Scope {
+    fn filter_sql(&self) -> String {
+        match self {
+            Self::Session(id) => format!("sessionId = {}", sql_quote(id)),
+            Self::Recent(limit) => format!(
+                "sessionId IN (SELECT sessionId FROM snapshots GROUP BY sessionId ORDER BY max(timestamp) DESC LIMIT {limit})"
+            ),
+        }
+    }
+}
+
+/// Latest todo list per session in scope, most recently updated first.
+///
+/// # Errors
+/// Returns error if the query fails.
+pub fn latest(session: &QuerySession, scope: &Scope) -> Result<Vec<TodoList>> {
+    let sql = format!(
+        r"
+    WITH {SNAPSHOTS_SQL},
+    latest AS (
+      SELECT sessionId, any_value(project) as project, max(timestamp) as updated,
+             arg_max(todos, timestamp) as todos
+      FROM snapshots
+      WHERE {filter}
+      GROUP BY sessionId
+    )
+    SELECT sessionId, project, updated, todos->i->>'content', todos->i->>'status'
+    FROM (
+      SELECT *, unnest(range(greatest(json_array_length(todos), 1)::BIGINT)) as i
+      FROM latest
+    )
+    ORDER BY updated DESC, sessionId, i
+  ",
+        filter = scope.filter_sql()
+    );
+
+    let mut lists: Vec<TodoList> = Vec::new();
+    for row in session.query(&sql)?.rows() {
+        if lists.last().is_none_or(|l| l.session_id != row[0]) {
+            lists.push(TodoList {
+                session_id: row[0].clone(),
+                project: row[1].clone(),
+                updated: row[2].clone(),
+                items: Vec::new(),
+            });
+        }
+        // An empty list still yields one row, with NULL content
+        if row[3] != "NULL"
+            && let Some(list) = lists.last_mut()
+        {
+            list.items.push(TodoItem {
+                content: row[3].clone(),
+                status: row[4].clone(),
+            });
+        }
+    }
+    Ok(lists)
+}
+
+/// Status transitions for todos in scope, oldest first within each session.
+///
+/// # Errors
+/// Returns error if the query fails.
+pub fn transitions(session: &QuerySession, scope: &Scope) -> Result<Vec<Transition>> {
+    let sql = format!(
+        r"
+    WITH {SNAPSHOTS_SQL},
+    items AS (
+      SELECT timestamp, sessionId, todos->i->>'content' as content, todos->i->>'status' as status
+      FROM (
+        SELECT *, unnest(range(json_array_length(todos)::BIGINT)) as i
+        FROM snapshots
+        WHERE {filter}
+      )
+    ),
+    changes AS (
+      SELECT sessionId, timestamp, content, status,
+             lag(status) OVER (PARTITION BY sessionId, content ORDER BY timestamp) as previous
+      FROM items
+    )
+    SELECT sessionId, timestamp, content, previous, status
+    FROM changes
+    WHERE previous IS DISTINCT FROM status
+    ORDER BY sessionId, timestamp, content
+  ",
+        filter = scope.filter_sql()
+    );
+
+    Ok(session
+        .query(&sql)?
+        .rows()
+        .iter()
+        .map(|row| Transition {
+            session_id: row[0].clone(),
+            timestamp: row[1].clone(),
+            content: row[2].clone(),
+            from: (row[3] != "NULL").then(|| row[3].clone()),
+            to: row[4].clone(),
+        })
+        .collect())
+}
+
+/// Render lists, each followed by its transitions when `history` is given.
+pub fn render(lists: &[TodoList], history: Option<&[Transition]>) -> String {
+    let mut out = String::new();
+    for (i, list) in lists.iter().enumerate() {
+        if i > 0 {
+            out.push('\n');
+        }
+        let _ = writeln!(
+            out,
+            "Session {} ({}) · updated {}",
+            list.session_id.get(..8).unwrap_or(&list.session_id),
+            list.project,
+            list.updated
+        );
+        if list.items.is_empty() {
+            out.push_str("  (empty)\n");
+        }
+        for item in &list.items {
+            let _ = writeln!(out, "  {} {}", marker(&item.status), item.content);
+        }
+
+        let Some(history) = history else {
+            continue;
+        };
+        out.push_str("  History:\n");
+        for t in history.iter().filter(|t| t.session_id == list.session_id) {
+            let _ = writeln!(
+                out,
+                "    {}  {} → {}  {}",
+                t.timestamp,
+                t.from.as_deref().unwrap_or("(new)"),
+                t.to,
+                t.content
+            );
+        }
+    }
+    if lists.is_empty() {
+        out.push_str("No todo lists found\n");
+    }
+    out
+}
+
+fn marker(status: &str) -> &'static str {
+    match status {
+        "completed" => "[x]",
+        "in_progress" => "[~]",
+        _ => "[ ]",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_with_history() {
+        let lists = vec![TodoList {
+            session_id: "abcdef1234".to_string(),
+            project: "-p".to_string(),
+            updated: "2025-01-15 14:30:00.000".to_string(),
+            items: vec![
+                TodoItem {
+                    content: "Write parser".to_string(),
+                    status: "completed".to_string(),
+                },
+                TodoItem {
+                    content: "Docs".to_string(),
+                    status: "pending".to_string(),
+                },
+            ],
+        }];
+        let history = vec![Transition {
+            session_id: "abcdef1234".to_string(),
+            timestamp: "2025-01-15 14:00:00.000".to_string(),
+            content: "Write parser".to_string(),
+            from: Some("in_progress".to_string()),
+            to: "completed".to_string(),
+        }];
+
+        let out = render(&lists, Some(&history));
+        assert!(out.starts_with("Session abcdef12 (-p)"));
+        assert!(out.contains("  [x] Write parser\n  [ ] Docs\n"));
+        assert!(out.contains("in_progress → completed  Write parser"));
+    }
+
+    #[test]
+    fn test_scope_filter_sql() {
+        assert_eq!(
+            Scope::Session("a'b".to_string()).filter_sql(),
+            "sessionId = 'a''b'"
+        );
+        assert!(Scope::Recent(3).filter_sql().ends_with("LIMIT 3)"));
+    }
+}
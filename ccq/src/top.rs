@@ -0,0 +1,273 @@
+//! Live terminal dashboard (`ccq top`).
+//!
+//! The views read the JSONL files on every query, so re-running the
+//! snapshot queries picks up whatever Claude Code has appended since.
+
+use std::time::{Duration, Instant};
+
+use chrono::{Local, NaiveDateTime, TimeZone, Utc};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Style, Stylize};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Paragraph, Row, Sparkline, Table};
+use ratatui::{DefaultTerminal, Frame};
+
+use crate::{QuerySession, Result};
+
+/// Minutes of history shown in the token burn sparkline.
+const BURN_MINUTES: usize = 30;
+const RECENT_LIMIT: usize = 20;
+
+/// Everything drawn in one frame.
+#[derive(Debug, Clone, Default)]
+struct Snapshot {
+    /// (session ID, project, last message timestamp)
+    active: Option<(String, String, String)>,
+    /// Output tokens per minute, oldest first
+    burn: Vec<u64>,
+    /// (input, output, cache read) tokens over the last hour
+    hour_totals: (u64, u64, u64),
+    /// (time, tool, summary)
+    tools: Vec<[String; 3]>,
+    /// (time, kind, message)
+    errors: Vec<[String; 3]>,
+    refreshed: String,
+    error: Option<String>,
+}
+
+/// Run the dashboard until `q`, `Esc`, or `Ctrl-C`.
+///
+/// # Errors
+/// Returns error if the terminal can't be drawn.
+pub fn run(session: &QuerySession, interval: Duration) -> Result<()> {
+    let mut terminal = ratatui::init();
+    let result = event_loop(&mut terminal, session, interval);
+    ratatui::restore();
+    result
+}
+
+fn event_loop(
+    terminal: &mut DefaultTerminal,
+    session: &QuerySession,
+    interval: Duration,
+) -> Result<()> {
+    let mut snapshot = load(session);
+    let mut last_refresh = Instant::now();
+
+    loop {
+        terminal.draw(|frame| draw(frame, &snapshot))?;
+
+        let timeout = interval.saturating_sub(last_refresh.elapsed());
+        if event::poll(timeout)? {
+            if let Event::Key(key) = event::read()? {
+                if key.kind != KeyEventKind::Press {
+                    continue;
+                }
+                match key.code {
+                    KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                    KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        return Ok(());
+                    }
+                    KeyCode::Char('r') => {}
+                    _ => continue,
+                }
+            } else {
+                continue;
+            }
+        }
+        snapshot = load(session);
+        last_refresh = Instant::now();
+    }
+}
+
+/// Query a fresh snapshot; query failures are shown in the header.
+fn load(session: &QuerySession) -> Snapshot {
+    let now = Utc::now().naive_utc();
+    let mut snapshot = Snapshot {
+        refreshed: Local::now().format("%H:%M:%S").to_string(),
+        ..Snapshot::default()
+    };
+    if let Err(e) = fill(session, now, &mut snapshot) {
+        snapshot.error = Some(e.to_string());
+    }
+    snapshot
+}
+
+fn fill(session: &QuerySession, now: NaiveDateTime, snapshot: &mut Snapshot) -> Result<()> {
+    let now_sql = format!("TIMESTAMP '{}'", now.format("%Y-%m-%d %H:%M:%S"));
+
+    let active = session.query(
+        "SELECT sessionId, project, max(timestamp) as last
+         FROM messages GROUP BY ALL ORDER BY last DESC LIMIT 1",
+    )?;
+    snapshot.active = active
+        .rows()
+        .first()
+        .map(|row| (row[0].clone(), row[1].clone(), row[2].clone()));
+
+    let burn = session.query(&format!(
+        "SELECT CAST(floor(epoch({now_sql} - timestamp) / 60) AS BIGINT) as ago,
+                sum(output_tokens) as tokens
+         FROM response_usage
+         WHERE timestamp > {now_sql} - INTERVAL {BURN_MINUTES} MINUTE
+         GROUP BY ago"
+    ))?;
+    snapshot.burn = minute_buckets(
+        burn.rows()
+            .iter()
+            .filter_map(|row| Some((row[0].parse().ok()?, row[1].parse().unwrap_or(0)))),
+        BURN_MINUTES,
+    );
+
+    let totals = session.query(&format!(
+        "SELECT coalesce(sum(input_tokens), 0), coalesce(sum(output_tokens), 0),
+                coalesce(sum(cache_read_tokens), 0)
+         FROM response_usage WHERE timestamp > {now_sql} - INTERVAL 1 HOUR"
+    ))?;
+    if let Some(row) = totals.rows().first() {
+        let n = |i: usize| row[i].parse().unwrap_or(0);
+        snapshot.hour_totals = (n(0), n(1), n(2));
+    }
+
+    let tools = session.query(&format!(
+        "SELECT timestamp, tool_name,
+                coalesce(tool_input->>'command', tool_input->>'file_path', tool_input->>'pattern',
+                         tool_input->>'description', tool_input->>'url', '')
+         FROM tool_uses ORDER BY timestamp DESC LIMIT {RECENT_LIMIT}"
+    ))?;
+    snapshot.tools = tools
+        .rows()
+        .iter()
+        .map(|row| [local_time(&row[0]), row[1].clone(), one_line(&row[2])])
+        .collect();
+
+    let errors = session.query(&format!(
+        "SELECT timestamp, kind, detail FROM (
+           SELECT timestamp, 'tool' as kind, result_content as detail
+           FROM tool_results WHERE is_error
+           UNION ALL
+           SELECT timestamp, 'api' as kind, (message->'content'->0->>'text') as detail
+           FROM assistant_messages WHERE isApiErrorMessage
+         )
+         ORDER BY timestamp DESC LIMIT {RECENT_LIMIT}"
+    ))?;
+    snapshot.errors = errors
+        .rows()
+        .iter()
+        .map(|row| [local_time(&row[0]), row[1].clone(), one_line(&row[2])])
+        .collect();
+
+    Ok(())
+}
+
+/// Spread `(minutes ago, tokens)` pairs into `minutes` buckets, oldest first.
+fn minute_buckets(rows: impl Iterator<Item = (usize, u64)>, minutes: usize) -> Vec<u64> {
+    let mut buckets = vec![0; minutes];
+    for (ago, tokens) in rows {
+        if ago < minutes {
+            buckets[minutes - 1 - ago] += tokens;
+        }
+    }
+    buckets
+}
+
+/// Convert a UTC timestamp from the views to local `HH:MM:SS`.
+fn local_time(ts: &str) -> String {
+    NaiveDateTime::parse_from_str(ts, "%Y-%m-%d %H:%M:%S%.f").map_or_else(
+        |_| ts.to_string(),
+        |t| Local.from_utc_datetime(&t).format("%H:%M:%S").to_string(),
+    )
+}
+
+fn one_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn draw(frame: &mut Frame, snapshot: &Snapshot) {
+    let [header, burn, tables, footer] = Layout::vertical([
+        Constraint::Length(3),
+        Constraint::Length(6),
+        Constraint::Fill(1),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+
+    let status = snapshot.error.as_ref().map_or_else(
+        || {
+            snapshot.active.as_ref().map_or_else(
+                || Line::from("No messages yet"),
+                |(id, project, last)| {
+                    Line::from(format!(
+                        "Active session {} · {project} · last message {}",
+                        id.get(..8).unwrap_or(id),
+                        local_time(last)
+                    ))
+                },
+            )
+        },
+        |e| Line::from(format!("Query failed: {e}")).red(),
+    );
+    frame.render_widget(
+        Paragraph::new(status).block(
+            Block::bordered().title(format!(" ccq top · refreshed {} ", snapshot.refreshed)),
+        ),
+        header,
+    );
+
+    let (input, output, cache) = snapshot.hour_totals;
+    frame.render_widget(
+        Sparkline::default()
+            .block(Block::bordered().title(format!(
+                " Output tokens/min (last {BURN_MINUTES} min) · last hour: in {input} · out {output} · cache read {cache} "
+            )))
+            .data(&snapshot.burn)
+            .style(Style::new().fg(Color::Cyan)),
+        burn,
+    );
+
+    let [tools_area, errors_area] =
+        Layout::horizontal([Constraint::Percentage(55), Constraint::Percentage(45)])
+            .areas(tables);
+    frame.render_widget(
+        recent_table(" Recent tool calls ", ["time", "tool", "input"], &snapshot.tools),
+        tools_area,
+    );
+    frame.render_widget(
+        recent_table(" Errors ", ["time", "kind", "message"], &snapshot.errors)
+            .style(Style::new().fg(Color::Red)),
+        errors_area,
+    );
+
+    frame.render_widget(Line::from(" q quit · r refresh").dim(), footer);
+}
+
+fn recent_table<'a>(title: &'a str, header: [&'a str; 3], rows: &'a [[String; 3]]) -> Table<'a> {
+    Table::new(
+        rows.iter()
+            .map(|[a, b, c]| Row::new([a.as_str(), b.as_str(), c.as_str()])),
+        [
+            Constraint::Length(8),
+            Constraint::Length(12),
+            Constraint::Fill(1),
+        ],
+    )
+    .header(Row::new(header).bold())
+    .block(Block::bordered().title(title))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_minute_buckets_oldest_first() {
+        let buckets = minute_buckets([(0, 5), (2, 7), (0, 1), (9, 100)].into_iter(), 3);
+        assert_eq!(buckets, vec![7, 0, 6]);
+    }
+
+    #[test]
+    fn test_one_line() {
+        assert_eq!(one_line("git status\n  --short"), "git status --short");
+    }
+}
@@ -0,0 +1,199 @@
+//! A session's conversation DAG as an indented tree (`.tree`).
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+
+use serde_json::Value;
+
+use crate::formatter::truncate_cell;
+use crate::replay::{self, ReplayMessage};
+use crate::utils::sql_quote;
+use crate::{QuerySession, Result};
+
+/// Content previews are cut to this many characters.
+const PREVIEW_CHARS: usize = 60;
+
+/// One message and the message it hangs off.
+#[derive(Debug, Clone)]
+pub struct Node {
+    pub uuid: String,
+    pub parent: Option<String>,
+    /// Attached through `logicalParentUuid` (across a compaction) rather
+    /// than `parentUuid`
+    pub logical: bool,
+    pub kind: String,
+    pub timestamp: String,
+    pub sidechain: bool,
+    pub preview: String,
+}
+
+/// Load every message of a session, agent sidechains included.
+///
+/// # Errors
+/// Returns error if the query fails.
+pub fn load(session: &QuerySession, session_id: &str) -> Result<Vec<Node>> {
+    let result = session.query(&format!(
+        "SELECT uuid, type, subtype, timestamp, agentId, message, content,
+                coalesce(parentUuid, logicalParentUuid)::VARCHAR,
+                parentUuid IS NULL AND logicalParentUuid IS NOT NULL,
+                coalesce(isSidechain, false)
+         FROM messages
+         WHERE sessionId = {}
+         ORDER BY timestamp, rownum",
+        sql_quote(session_id)
+    ))?;
+
+    let optional = |s: &String| (s != "NULL" && !s.is_empty()).then(|| s.clone());
+    Ok(result
+        .rows()
+        .iter()
+        .map(|row| {
+            let msg = ReplayMessage {
+                uuid: row[0].clone(),
+                kind: row[1].clone(),
+                subtype: optional(&row[2]),
+                timestamp: row[3].clone(),
+                agent_id: optional(&row[4]),
+                message: serde_json::from_str(&row[5]).unwrap_or(Value::Null),
+                content: optional(&row[6]),
+            };
+            Node {
+                preview: preview(&msg),
+                kind: msg.subtype.unwrap_or(msg.kind),
+                uuid: msg.uuid,
+                timestamp: msg.timestamp,
+                parent: optional(&row[7]),
+                logical: row[8] == "true",
+                sidechain: row[9] == "true",
+            }
+        })
+        .collect())
+}
+
+/// The first part of a message on one line, e.g. `TOOL CALL: Bash {...}`.
+fn preview(msg: &ReplayMessage) -> String {
+    replay::parts(msg).first().map_or_else(String::new, |part| {
+        let body = replay::part_body(part);
+        let flat = body.split_whitespace().collect::<Vec<_>>().join(" ");
+        format!("{}: {}", replay::part_label(part), truncate_cell(&flat, PREVIEW_CHARS))
+    })
+}
+
+/// Render `nodes` (in time order) as a tree. A run of single replies stays
+/// at one indentation level; only branches indent.
+pub fn render(nodes: &[Node]) -> String {
+    let known: HashSet<&str> = nodes.iter().map(|n| n.uuid.as_str()).collect();
+    let mut children: HashMap<&str, Vec<&Node>> = HashMap::new();
+    let mut roots = Vec::new();
+    for node in nodes {
+        match node.parent.as_deref().filter(|p| known.contains(p)) {
+            Some(parent) => children.entry(parent).or_default().push(node),
+            None => roots.push(node),
+        }
+    }
+
+    let mut out = String::new();
+    let mut seen = HashSet::new();
+    for root in roots {
+        write_chain(&mut out, &children, &mut seen, root, "", "");
+    }
+    out
+}
+
+fn write_chain<'a>(
+    out: &mut String,
+    children: &HashMap<&str, Vec<&'a Node>>,
+    seen: &mut HashSet<&'a str>,
+    start: &'a Node,
+    lead: &str,
+    rest: &str,
+) {
+    let mut node = start;
+    let mut lead = lead.to_string();
+    // Iterate down chains so long conversations don't recurse per message
+    loop {
+        if !seen.insert(&node.uuid) {
+            return;
+        }
+        let _ = writeln!(out, "{lead}{}", line(node));
+        let kids = children.get(node.uuid.as_str()).map_or(&[][..], Vec::as_slice);
+        match kids {
+            [] => return,
+            [only] => {
+                node = only;
+                lead = rest.to_string();
+            }
+            _ => {
+                for (i, kid) in kids.iter().enumerate() {
+                    let last = i + 1 == kids.len();
+                    let (branch, indent) = if last { ("└─ ", "   ") } else { ("├─ ", "│  ") };
+                    let lead = format!("{rest}{branch}");
+                    let rest = format!("{rest}{indent}");
+                    write_chain(out, children, seen, kid, &lead, &rest);
+                }
+                return;
+            }
+        }
+    }
+}
+
+fn line(node: &Node) -> String {
+    let short: String = node.uuid.chars().take(8).collect();
+    let time = node.timestamp.get(..19).unwrap_or(&node.timestamp);
+    let mut line = format!("{short} {time} {:<9}", node.kind);
+    if node.logical {
+        line.push_str(" (logical)");
+    }
+    if node.sidechain {
+        line.push_str(" [sidechain]");
+    }
+    if !node.preview.is_empty() {
+        line.push(' ');
+        line.push_str(&node.preview);
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(uuid: &str, parent: Option<&str>) -> Node {
+        Node {
+            uuid: uuid.to_string(),
+            parent: parent.map(str::to_string),
+            logical: false,
+            kind: "user".to_string(),
+            timestamp: "2025-01-01 00:00:00.000".to_string(),
+            sidechain: false,
+            preview: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_render_indents_only_branches() {
+        let nodes = vec![
+            node("a", None),
+            node("b", Some("a")),
+            node("c", Some("b")),
+            node("d", Some("b")),
+            node("e", Some("c")),
+        ];
+        let text = render(&nodes);
+        let shape: Vec<String> = text
+            .lines()
+            .map(|l| l.split(" 2025").next().unwrap_or("").to_string())
+            .collect();
+        assert_eq!(shape, vec!["a", "b", "├─ c", "│  e", "└─ d"]);
+    }
+
+    #[test]
+    fn test_render_marks_logical_and_sidechain() {
+        let mut logical = node("b", Some("a"));
+        logical.logical = true;
+        logical.sidechain = true;
+        let text = render(&[node("a", Some("missing")), logical]);
+        assert!(text.lines().next().unwrap().starts_with("a 2025-01-01 00:00:00 user"));
+        assert!(text.contains("b 2025-01-01 00:00:00 user      (logical) [sidechain]"));
+    }
+}
@@ -22,6 +22,11 @@ pub fn claude_projects_base() -> PathBuf {
         .join("projects")
 }
 
+/// Returns the ccq config directory (~/.config/ccq), if a home directory exists.
+pub fn ccq_config_dir() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".config").join("ccq"))
+}
+
 /// Resolve a project path with tilde expansion and relative path handling.
 ///
 /// - `~/...` expands to home directory
@@ -55,10 +60,20 @@ fn resolve_project_path(path: &str) -> PathBuf {
 /// Generate a project slug from a path.
 ///
 /// Replaces `/` and `.` with `-` to create a filesystem-safe identifier.
-fn get_project_slug(path: &Path) -> String {
+pub(crate) fn get_project_slug(path: &Path) -> String {
     path.to_string_lossy().replace(['/', '.'], "-")
 }
 
+/// Quote a string as a SQL string literal, doubling embedded single quotes.
+pub fn sql_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "''"))
+}
+
+/// Quote a string as a SQL identifier, doubling embedded double quotes.
+pub fn sql_ident(s: &str) -> String {
+    format!("\"{}\"", s.replace('"', "\"\""))
+}
+
 /// Resolve a project path and return the Claude data directory.
 pub fn resolve_project_dir(path: &str) -> ResolvedProject {
     let project_path = resolve_project_path(path);
@@ -83,6 +98,18 @@ mod tests {
         assert_eq!(get_project_slug(path), "-home-user-code-my-project");
     }
 
+    #[test]
+    fn test_sql_quote() {
+        assert_eq!(sql_quote("abc"), "'abc'");
+        assert_eq!(sql_quote("it's"), "'it''s'");
+    }
+
+    #[test]
+    fn test_sql_ident() {
+        assert_eq!(sql_ident("t"), "\"t\"");
+        assert_eq!(sql_ident("a\"b"), "\"a\"\"b\"");
+    }
+
     #[test]
     fn test_claude_projects_base() {
         let base = claude_projects_base();
@@ -0,0 +1,250 @@
+//! Round-trip check that the typed schema faithfully represents raw lines.
+//!
+//! Each raw line is re-parsed with the same column schema the `messages` view
+//! uses, re-serialized, and compared key by key against the original JSON.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, NaiveDateTime};
+use serde_json::Value;
+
+use crate::query_session::SCHEMA_COLUMNS;
+use crate::utils::sql_quote;
+use crate::{Error, QuerySession, Result};
+
+/// How a field differs between the raw line and the typed row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DiffKind {
+    /// Key has no column in the schema
+    Dropped,
+    /// Key has a column, but the typed value doesn't match the raw value
+    Altered,
+}
+
+impl DiffKind {
+    const fn label(self) -> &'static str {
+        match self {
+            Self::Dropped => "dropped",
+            Self::Altered => "altered",
+        }
+    }
+}
+
+/// A field that failed the round trip, aggregated over all lines.
+#[derive(Debug, Clone)]
+pub struct FieldDiff {
+    pub key: String,
+    pub kind: DiffKind,
+    /// Number of lines where this field differed
+    pub lines: usize,
+    /// UUID of the first differing line
+    pub example_uuid: String,
+    /// Raw value from the first differing line
+    pub raw: String,
+    /// Typed value from the first differing line (empty when dropped)
+    pub parsed: String,
+}
+
+/// Result of verifying one session.
+#[derive(Debug, Clone, Default)]
+pub struct VerifyReport {
+    /// Lines compared
+    pub lines: usize,
+    /// Differing fields, sorted by kind then key
+    pub diffs: Vec<FieldDiff>,
+}
+
+/// Compare raw lines for sessions matching `prefix` against their typed form.
+///
+/// # Errors
+/// Returns error if the query fails or no message lines match `prefix`.
+pub fn verify(session: &QuerySession, prefix: &str) -> Result<VerifyReport> {
+    let result = session.query(&build_verify_sql(prefix))?;
+    if result.row_count() == 0 {
+        return Err(Error::SessionNotFound(prefix.to_string()));
+    }
+
+    let mut diffs: BTreeMap<(DiffKind, String), FieldDiff> = BTreeMap::new();
+    for row in result.rows() {
+        let (Ok(raw), Ok(parsed)) = (
+            serde_json::from_str::<Value>(&row[1]),
+            serde_json::from_str::<Value>(&row[2]),
+        ) else {
+            continue;
+        };
+        for (key, kind, raw_value, parsed_value) in compare_line(&raw, &parsed) {
+            diffs
+                .entry((kind, key.clone()))
+                .and_modify(|d| d.lines += 1)
+                .or_insert_with(|| FieldDiff {
+                    key,
+                    kind,
+                    lines: 1,
+                    example_uuid: row[0].clone(),
+                    raw: raw_value,
+                    parsed: parsed_value,
+                });
+        }
+    }
+
+    Ok(VerifyReport {
+        lines: result.row_count(),
+        diffs: diffs.into_values().collect(),
+    })
+}
+
+/// Print the report; returns true when every field round-tripped.
+pub fn print_report(report: &VerifyReport) -> bool {
+    println!("Verified {} line(s)", report.lines);
+    if report.diffs.is_empty() {
+        println!("✓ All fields round-trip through the typed schema");
+        return true;
+    }
+
+    println!(
+        "✗ {} field(s) not faithfully represented:",
+        report.diffs.len()
+    );
+    for d in &report.diffs {
+        println!(
+            "  {:<8} {} ({} line(s), e.g. {})",
+            d.kind.label(),
+            d.key,
+            d.lines,
+            d.example_uuid
+        );
+        println!("           raw:    {}", preview(&d.raw));
+        if d.kind == DiffKind::Altered {
+            println!("           parsed: {}", preview(&d.parsed));
+        }
+    }
+    false
+}
+
+/// Re-parse each raw line with the schema and serialize it back to JSON.
+fn build_verify_sql(prefix: &str) -> String {
+    let structure = Value::Object(
+        SCHEMA_COLUMNS
+            .iter()
+            .map(|(key, ty)| ((*key).to_string(), Value::String((*ty).to_string())))
+            .collect(),
+    );
+    format!(
+        r"
+    SELECT
+      uuid,
+      raw,
+      to_json(json_transform(raw, {structure})) as parsed
+    FROM raw_messages
+    WHERE raw->>'type' IN ('user', 'assistant', 'system')
+      AND starts_with(raw->>'sessionId', {prefix})
+  ",
+        structure = sql_quote(&structure.to_string()),
+        prefix = sql_quote(prefix),
+    )
+}
+
+/// Differing keys in one line as `(key, kind, raw, parsed)`.
+fn compare_line(raw: &Value, parsed: &Value) -> Vec<(String, DiffKind, String, String)> {
+    let (Some(raw), Some(parsed)) = (raw.as_object(), parsed.as_object()) else {
+        return Vec::new();
+    };
+
+    let mut diffs = Vec::new();
+    for (key, raw_value) in raw {
+        let Some(ty) = SCHEMA_COLUMNS
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, ty)| *ty)
+        else {
+            diffs.push((
+                key.clone(),
+                DiffKind::Dropped,
+                raw_value.to_string(),
+                String::new(),
+            ));
+            continue;
+        };
+        let parsed_value = parsed.get(key).unwrap_or(&Value::Null);
+        if !values_match(ty, raw_value, parsed_value) {
+            diffs.push((
+                key.clone(),
+                DiffKind::Altered,
+                raw_value.to_string(),
+                parsed_value.to_string(),
+            ));
+        }
+    }
+    diffs
+}
+
+/// Whether a typed value represents the raw value, allowing for the
+/// formatting changes the column type implies.
+fn values_match(ty: &str, raw: &Value, parsed: &Value) -> bool {
+    if raw == parsed {
+        return true;
+    }
+    match (ty, raw, parsed) {
+        ("UUID", Value::String(r), Value::String(p)) => r.eq_ignore_ascii_case(p),
+        ("TIMESTAMP", Value::String(r), Value::String(p)) => {
+            let raw_ts = DateTime::parse_from_rfc3339(r).map(|t| t.naive_utc());
+            let parsed_ts = NaiveDateTime::parse_from_str(p, "%Y-%m-%d %H:%M:%S%.f");
+            matches!((raw_ts, parsed_ts), (Ok(r), Ok(p)) if r == p)
+        }
+        (_, Value::Number(r), Value::Number(p)) => r.as_f64() == p.as_f64(),
+        _ => false,
+    }
+}
+
+fn preview(value: &str) -> String {
+    const MAX: usize = 80;
+    if value.chars().count() <= MAX {
+        return value.to_string();
+    }
+    let kept: String = value.chars().take(MAX).collect();
+    format!("{kept}…")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_compare_line_reports_dropped_and_altered() {
+        let raw = json!({
+            "uuid": "ABCD0000-0000-0000-0000-000000000000",
+            "timestamp": "2025-01-02T03:04:05.678Z",
+            "content": ["not", "a", "string"],
+            "newField": 1
+        });
+        let parsed = json!({
+            "uuid": "abcd0000-0000-0000-0000-000000000000",
+            "timestamp": "2025-01-02 03:04:05.678",
+            "content": "[\"not\",\"a\",\"string\"]",
+            "type": null
+        });
+
+        let diffs = compare_line(&raw, &parsed);
+        let keys: Vec<_> = diffs.iter().map(|(k, kind, _, _)| (k.as_str(), *kind)).collect();
+        assert_eq!(
+            keys,
+            vec![("content", DiffKind::Altered), ("newField", DiffKind::Dropped)]
+        );
+    }
+
+    #[test]
+    fn test_values_match_null_and_json() {
+        assert!(values_match("JSON", &json!({"a": [1, 2]}), &json!({"a": [1, 2]})));
+        assert!(!values_match("INTEGER", &json!("12"), &Value::Null));
+        assert!(values_match("INTEGER", &json!(3.0), &json!(3)));
+    }
+
+    #[test]
+    fn test_build_verify_sql_uses_schema_structure() {
+        let sql = build_verify_sql("ab'c");
+        assert!(sql.contains(r#"'{"uuid":"UUID","type":"VARCHAR""#));
+        assert!(sql.contains("starts_with(raw->>'sessionId', 'ab''c')"));
+        assert!(sql.contains("FROM raw_messages"));
+    }
+}
@@ -31,7 +31,7 @@ tokens AS (
     sum(input_tokens) as input_tokens,
     sum(output_tokens) as output_tokens,
     sum(cache_read_tokens) as cache_read_tokens
-  FROM token_usage
+  FROM response_usage
 ),
 agents AS (
   SELECT string_agg(agentId || ' (' || cnt || ' msgs)', ', ') as agent_summary
@@ -37,8 +37,40 @@ echo "Convenience Views:"
 run_query_test "view-tool-uses" "SELECT tool_name, count(*) as cnt FROM tool_uses GROUP BY tool_name ORDER BY tool_name LIMIT 10;"
 run_query_test "view-tool-results" "SELECT count(*) FROM tool_results;"
 run_query_test "view-token-usage" "SELECT count(*) FROM token_usage;"
+run_query_test "view-response-usage" "SELECT count(*) FROM response_usage;"
 run_query_test "view-bash-commands" "SELECT count(*) FROM bash_commands;"
 run_query_test "view-file-operations" "SELECT count(*) FROM file_operations;"
+run_query_test "view-thinking-blocks" "SELECT count(*) FROM thinking_blocks;"
+run_query_test "view-text-blocks" "SELECT count(*) FROM text_blocks;"
+run_query_test "view-attachments" "SELECT count(*) FROM attachments;"
+run_query_test "view-todo-events" "SELECT status, count(*) FROM todo_events GROUP BY status;"
+run_query_test "view-hooks" "SELECT event, count(*) FROM hooks GROUP BY event;"
+run_query_test "view-errors" "SELECT error_kind, count(*) FROM errors GROUP BY error_kind;"
+run_query_test "view-sessions" "SELECT count(*) FROM sessions;"
+run_query_test "view-threads" "SELECT count(*) FROM threads;"
+run_query_test "view-agents" "SELECT count(*) FROM agents;"
+run_query_test "view-edits" "SELECT count(*) FROM edits;"
+run_query_test "view-web-requests" "SELECT count(*) FROM web_requests;"
+run_query_test "view-task-launches" "SELECT count(*) FROM task_launches;"
+run_query_test "view-mcp-tool-uses" "SELECT count(*) FROM mcp_tool_uses;"
+run_query_test "view-tool-calls" "SELECT count(*) FROM tool_calls;"
+run_query_test "view-compactions" "SELECT count(*) FROM compactions;"
+run_query_test "view-interruptions" "SELECT count(*) FROM interruptions;"
+run_query_test "view-costs" "SELECT count(*) FROM costs;"
+run_query_test "view-daily-usage" "SELECT count(*) FROM daily_usage;"
+run_query_test "view-model-usage" "SELECT count(*) FROM model_usage;"
+run_query_test "view-slash-commands" "SELECT count(*) FROM slash_commands;"
+run_query_test "view-git-activity" "SELECT count(*) FROM git_activity;"
+run_query_test "view-file-history" "SELECT count(*) FROM file_history;"
+run_query_test "view-notebook-operations" "SELECT count(*) FROM notebook_operations;"
+run_query_test "view-searches" "SELECT count(*) FROM searches;"
+run_query_test "view-retries" "SELECT count(*) FROM retries;"
+run_query_test "view-sidechains" "SELECT count(*) FROM sidechains;"
+run_query_test "view-turns" "SELECT count(*) FROM turns;"
+run_query_test "view-response-latency" "SELECT count(*) FROM response_latency;"
+run_query_test "view-context-growth" "SELECT count(*) FROM context_growth;"
+run_query_test "view-stop-reasons" "SELECT count(*) FROM stop_reasons;"
+run_query_test "view-permission-denials" "SELECT count(*) FROM permission_denials;"
 
 # =============================================================================
 # JSON Access (from reflect skill)
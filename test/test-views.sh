@@ -0,0 +1,92 @@
+#!/bin/bash
+# Checks the derived views against a small hand-written session
+# Unlike test.sh, the fixture and expected outputs are committed: edit them
+# together when a view's semantics change
+# Exit 0 if all pass, exit 1 if any fail
+
+SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)"
+FIXTURES_DIR="$SCRIPT_DIR/views/projects"
+EXPECTED_DIR="$SCRIPT_DIR/views/expected"
+CC_QUERY="${CC_QUERY:-$SCRIPT_DIR/../ccq/target/release/ccq}"
+
+# Keep a local config (pricing overrides, startup SQL) out of the results
+export CCQ_CONFIG=/dev/null
+
+PASSED=0
+FAILED=0
+FAILED_TESTS=""
+
+echo "=== ccq View Semantics ==="
+echo ""
+
+run_query_test() {
+  local name="$1"
+  local query="$2"
+  local expected_file="$EXPECTED_DIR/$name.txt"
+
+  local output
+  output=$(echo "$query" | "$CC_QUERY" -d "$FIXTURES_DIR" 2>&1)
+
+  if [[ "$output" == "$(cat "$expected_file")" ]]; then
+    echo "PASS: $name"
+    PASSED=$((PASSED + 1))
+  else
+    echo "FAIL: $name"
+    diff <(echo "$output") "$expected_file" | sed 's/^/    /'
+    FAILED=$((FAILED + 1))
+    FAILED_TESTS="$FAILED_TESTS $name"
+  fi
+}
+
+# The session (views/projects/-home-me-widget/5e551011-*.jsonl):
+#   turn 1  001  "Fix the login bug"
+#           002  opus: Grep, 003 result
+#           004  opus: "Found it.", 04b its git checkout, 04c its Task
+#           005  git checkout result, 05b Task result
+#           006  opus on fix-login: Grep, 007 result
+#           008  system: 529 overloaded, retry 1/10
+#           009  sonnet: git commit, 010 error
+#           011  opus: "Done."
+#   turn 2  012  "Thanks"
+#           12a  opus: thinking, 013 its "You're welcome."
+#           13b  system: stop hook blocked continuing
+#   turn 3  014  "Run the tests"
+#           015  opus: "Running the tests.", cut off by 016 "[Request interrupted by user]"
+#   turn 4  017  "Run them in the background"
+#           018  opus: cargo test, 019 interrupted result
+#   turn 5  01a  "Delete the branch instead"
+#           01b  opus: git branch -D, 01c rejected by the user
+#   turn 6  01d  "Never mind", never answered
+# 004-04c, 12a/013, and a02/a05/a06 are each one response split across lines, as
+# Claude Code logs a line per content block: they share message.id, requestId,
+# and usage.
+# The subagent (agent-a1.jsonl) is 04c's Task, running during turn 1: a01 prompt
+# whose parent isn't loaded, a04 system: flat 500 error, retry 1/10, a02 haiku
+# with unsigned thinking and an unanswered Glob, a05 its Write and a06 its rm,
+# a07 Write not granted, a08 rm denied by a rule, a03 <synthetic>
+run_query_test "threads" "SELECT right(uuid::VARCHAR, 3) as msg, right(parentUuid::VARCHAR, 3) as parent, right(root_uuid::VARCHAR, 3) as root, depth, isSidechain, agentId, len(path) = depth + 1 AND path[1] = root_uuid AND path[-1] = uuid as path_ok FROM threads ORDER BY root, depth, msg;"
+run_query_test "tool-calls" "SELECT tool_name, right(uuid::VARCHAR, 3) as msg, right(result_uuid::VARCHAR, 3) as result, epoch(latency) as latency_s, duration_ms, is_error, result_chars FROM tool_calls ORDER BY timestamp;"
+run_query_test "costs" "SELECT right(uuid::VARCHAR, 3) as msg, model, price_prefix, (cost_usd * 1e6)::BIGINT as micro_usd FROM costs ORDER BY timestamp;"
+run_query_test "model-usage" "SELECT right(uuid::VARCHAR, 3) as msg, model, previous_model, model_changed, off_primary, primary_model FROM model_usage ORDER BY timestamp;"
+run_query_test "git-activity" "SELECT right(uuid::VARCHAR, 3) as msg, kind, gitBranch, previous_branch, subcommand, command, is_error FROM git_activity ORDER BY timestamp, kind;"
+run_query_test "searches" "SELECT tool_name, pattern, path, glob, output_mode, num_files, match_count, is_error, epoch(latency) as latency_s FROM searches ORDER BY timestamp;"
+run_query_test "retries" "SELECT right(uuid::VARCHAR, 3) as msg, retry_attempt, max_retries, retry_in_ms, requestId, status, error_type, error_message, model FROM retries ORDER BY timestamp;"
+run_query_test "turns" "SELECT turn_index, right(uuid::VARCHAR, 3) as msg, prompt, messages, assistant_messages, tool_calls, input_tokens, output_tokens, total_tokens, strftime(ended, '%M:%S.%g') as ended, epoch(duration) as duration_s FROM turns ORDER BY turn_index;"
+run_query_test "response-latency" "SELECT right(uuid::VARCHAR, 3) as msg, right(reply_uuid::VARCHAR, 3) as reply, model, epoch(first_response) as first_response_s, epoch(first_text) as first_text_s FROM response_latency ORDER BY timestamp;"
+run_query_test "context-growth" "SELECT right(uuid::VARCHAR, 3) as msg, model, isAgent, message_index, context_tokens, cumulative_input_tokens, cumulative_cache_read_tokens, context_pct FROM context_growth ORDER BY timestamp;"
+run_query_test "thinking-blocks" "SELECT right(uuid::VARCHAR, 3) as msg, model, thinking_chars, estimated_tokens, has_signature, block_index FROM thinking_blocks ORDER BY timestamp;"
+run_query_test "agents" "SELECT agentId, tool_use_id, right(parent_uuid::VARCHAR, 3) as parent, subagent_type, description, messages, epoch(duration) as duration_s, input_tokens, output_tokens, total_tokens FROM agents;"
+run_query_test "interruptions" "SELECT right(uuid::VARCHAR, 3) as msg, kind, tool_name, left(text, 30) as text FROM interruptions ORDER BY timestamp;"
+run_query_test "stop-reasons" "SELECT right(uuid::VARCHAR, 3) as msg, source, stop_reason, detail, model, is_truncated, is_abnormal FROM stop_reasons ORDER BY timestamp;"
+run_query_test "permission-denials" "SELECT right(uuid::VARCHAR, 3) as msg, isAgent, tool_name, denial_kind FROM permission_denials ORDER BY timestamp;"
+
+echo ""
+echo "=== Summary ==="
+echo "Passed: $PASSED"
+echo "Failed: $FAILED"
+
+if [[ $FAILED -gt 0 ]]; then
+  echo ""
+  echo "Failed tests:$FAILED_TESTS"
+  exit 1
+fi